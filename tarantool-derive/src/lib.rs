@@ -0,0 +1,271 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, spanned::Spanned, DeriveInput, Error, Ident, Lit, Meta, NestedMeta};
+
+/// See `tarantool::tuple::Tuple` for the documentation.
+#[proc_macro_derive(Tuple, attributes(tuple))]
+pub fn proc_macro_derive_tuple(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    Record::new(&input)
+        .map(|r| r.expand())
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+struct Record<'a> {
+    name: &'a Ident,
+    /// Fields sorted by position.
+    fields: Vec<Field<'a>>,
+}
+
+struct Field<'a> {
+    ident: &'a Ident,
+    ty: &'a syn::Type,
+    position: usize,
+    is_optional: bool,
+}
+
+impl<'a> Record<'a> {
+    fn new(input: &'a DeriveInput) -> Result<Self, Error> {
+        let name = &input.ident;
+        if !input.generics.params.is_empty() {
+            return Err(Error::new(
+                input.generics.span(),
+                "generic types are not supported by #[derive(Tuple)]",
+            ))
+        }
+        let named = match &input.data {
+            syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(f), .. }) => &f.named,
+            _ => return Err(Error::new(
+                name.span(),
+                "#[derive(Tuple)] only supports structs with named fields",
+            )),
+        };
+
+        let mut fields = Vec::with_capacity(named.len());
+        let mut next_position = 0;
+        for field in named {
+            let mut position = next_position;
+            let mut is_optional = false;
+            for attr in field.attrs.iter().filter(|a| a.path.is_ident("tuple")) {
+                let list = match attr.parse_meta()? {
+                    Meta::List(list) => list,
+                    other => return Err(Error::new(other.span(), "expected #[tuple(...)]")),
+                };
+                for nested in list.nested {
+                    match nested {
+                        NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("field") => {
+                            position = match &nv.lit {
+                                Lit::Int(i) => i.base10_parse()?,
+                                other => return Err(Error::new(
+                                    other.span(), "expected a field number",
+                                )),
+                            };
+                        }
+                        NestedMeta::Meta(Meta::Path(p)) if p.is_ident("optional") => {
+                            is_optional = true;
+                        }
+                        other => return Err(Error::new(
+                            other.span(),
+                            "unknown attribute, expected `field = <number>` or `optional`",
+                        )),
+                    }
+                }
+            }
+            if let Some(other) = fields.iter().find(|f: &&Field| f.position == position) {
+                return Err(Error::new(
+                    field.span(),
+                    format!("field {} is already taken by `{}`", position, other.ident),
+                ))
+            }
+            next_position = position + 1;
+            fields.push(Field {
+                ident: field.ident.as_ref().unwrap(),
+                ty: &field.ty,
+                position,
+                is_optional,
+            });
+        }
+        fields.sort_by_key(|f| f.position);
+
+        // Optional fields may be omitted, so they must be the last ones
+        if let Some(i) = fields.iter().position(|f| f.is_optional) {
+            if let Some(f) = fields[i..].iter().find(|f| !f.is_optional) {
+                return Err(Error::new(
+                    f.ident.span(),
+                    "fields following an optional field must be optional too",
+                ))
+            }
+        }
+
+        Ok(Self { name, fields })
+    }
+
+    /// Total number of tuple fields including the gaps.
+    fn len(&self) -> usize {
+        self.fields.last().map(|f| f.position + 1).unwrap_or(0)
+    }
+
+    fn expand(&self) -> TokenStream {
+        let name = self.name;
+        let serialize = self.serialize();
+        let deserialize = self.deserialize();
+        let format = self.format();
+        quote! {
+            impl tarantool::__private::serde::Serialize for #name {
+                fn serialize<__S>(&self, __serializer: __S)
+                    -> ::std::result::Result<__S::Ok, __S::Error>
+                where
+                    __S: tarantool::__private::serde::Serializer,
+                {
+                    #serialize
+                }
+            }
+
+            impl<'de> tarantool::__private::serde::Deserialize<'de> for #name {
+                fn deserialize<__D>(__deserializer: __D)
+                    -> ::std::result::Result<Self, __D::Error>
+                where
+                    __D: tarantool::__private::serde::Deserializer<'de>,
+                {
+                    #deserialize
+                }
+            }
+
+            impl tarantool::tuple::AsTuple for #name {}
+
+            impl tarantool::tuple::Record for #name {
+                fn format() -> ::std::vec::Vec<tarantool::space::SpaceFieldFormat> {
+                    #format
+                }
+            }
+        }
+    }
+
+    fn serialize(&self) -> TokenStream {
+        let len = self.len();
+        // Trailing optional fields which are `None` are omitted
+        let truncate = self.fields.iter().rev()
+            .take_while(|f| f.is_optional)
+            .map(|Field { ident, position, .. }| quote! {
+                if __len == #position + 1 && self.#ident.is_none() {
+                    __len = #position;
+                }
+            });
+        let elements = (0..len).map(|position| {
+            match self.fields.iter().find(|f| f.position == position) {
+                Some(Field { ident, .. }) => quote! {
+                    if #position < __len {
+                        __seq.serialize_element(&self.#ident)?;
+                    }
+                },
+                None => quote! {
+                    if #position < __len {
+                        __seq.serialize_element(&())?;
+                    }
+                },
+            }
+        });
+        quote! {
+            use tarantool::__private::serde::ser::SerializeSeq;
+            #[allow(unused_mut)]
+            let mut __len: usize = #len;
+            #( #truncate )*
+            let mut __seq = __serializer.serialize_seq(::std::option::Option::Some(__len))?;
+            #( #elements )*
+            __seq.end()
+        }
+    }
+
+    fn deserialize(&self) -> TokenStream {
+        let name = self.name;
+        let expecting = format!("a tuple of type {}", name);
+        let len = self.len();
+        let vars: Vec<_> = self.fields.iter()
+            .map(|f| format_ident!("__field_{}", f.position))
+            .collect();
+        let elements = (0..len).map(|position| {
+            match self.fields.iter().find(|f| f.position == position) {
+                Some(Field { ty, is_optional: true, .. }) => {
+                    let var = format_ident!("__field_{}", position);
+                    quote! {
+                        let #var: #ty = __seq.next_element()?.unwrap_or_default();
+                    }
+                }
+                Some(Field { ty, .. }) => {
+                    let var = format_ident!("__field_{}", position);
+                    quote! {
+                        let #var: #ty = match __seq.next_element()? {
+                            ::std::option::Option::Some(v) => v,
+                            ::std::option::Option::None => return ::std::result::Result::Err(
+                                __de::Error::invalid_length(#position, &self)
+                            ),
+                        };
+                    }
+                }
+                None => quote! {
+                    let _: ::std::option::Option<__de::IgnoredAny> = __seq.next_element()?;
+                },
+            }
+        });
+        let idents = self.fields.iter().map(|f| f.ident);
+        quote! {
+            use tarantool::__private::serde::de as __de;
+
+            struct __Visitor;
+
+            impl<'de> __de::Visitor<'de> for __Visitor {
+                type Value = #name;
+
+                fn expecting(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    f.write_str(#expecting)
+                }
+
+                fn visit_seq<__A>(self, mut __seq: __A)
+                    -> ::std::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __de::SeqAccess<'de>,
+                {
+                    #( #elements )*
+                    // skip the fields which aren't mapped
+                    while let ::std::option::Option::Some(__de::IgnoredAny) = __seq.next_element()? {}
+                    ::std::result::Result::Ok(#name { #( #idents: #vars ),* })
+                }
+            }
+
+            __deserializer.deserialize_seq(__Visitor)
+        }
+    }
+
+    fn format(&self) -> TokenStream {
+        let fields = (0..self.len()).map(|position| {
+            match self.fields.iter().find(|f| f.position == position) {
+                Some(Field { ident, ty, .. }) => {
+                    let name = ident.to_string();
+                    let name = name.trim_start_matches("r#");
+                    quote! {
+                        tarantool::space::SpaceFieldFormat {
+                            name: #name.into(),
+                            field_type: <#ty as tarantool::tuple::TupleField>::field_type(),
+                            is_nullable: <#ty as tarantool::tuple::TupleField>::is_nullable(),
+                        }
+                    }
+                }
+                None => {
+                    // field names are 1-based in tarantool
+                    let name = format!("field_{}", position + 1);
+                    quote! {
+                        tarantool::space::SpaceFieldFormat {
+                            name: #name.into(),
+                            field_type: tarantool::space::SpaceFieldType::Any,
+                            is_nullable: true,
+                        }
+                    }
+                }
+            }
+        });
+        quote! {
+            vec![ #( #fields ),* ]
+        }
+    }
+}
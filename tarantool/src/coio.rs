@@ -6,12 +6,15 @@ use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
 use std::convert::TryFrom;
 use std::ffi::c_void;
+use std::future::Future;
 use std::io::{self, Read, Write};
 use std::mem::forget;
 use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
 use std::os::raw::c_char;
 use std::os::unix::io::{AsRawFd, IntoRawFd, RawFd};
+use std::pin::Pin;
 use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
 use std::time::Duration;
 
 use core::ptr::null_mut;
@@ -19,7 +22,7 @@ use num_traits::Zero;
 
 use crate::error::{Error, TarantoolError};
 use crate::ffi::tarantool as ffi;
-use crate::fiber::{unpack_callback, Cond};
+use crate::fiber::{self, unpack_callback, Cond, UnitJoinHandle};
 
 const TIMEOUT_INFINITY: f64 = 365.0 * 86400.0 * 100.0;
 
@@ -82,6 +85,41 @@ impl CoIOStream {
     ) -> Result<usize, io::Error> {
         write(self.fd, buf, timeout)
     }
+
+    /// Pull some bytes from this source into the specified buffer. Returns how
+    /// many bytes were read.
+    ///
+    /// Instead of blocking the current fiber until the socket is ready, the
+    /// returned future yields [`Poll::Pending`] and wakes the task when the
+    /// data arrives. Must be polled by a fiber based executor like
+    /// [`fiber::block_on`].
+    pub async fn read_async(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        loop {
+            match try_read(self.fd, buf) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    CoIOWait::new(self.fd, ffi::CoIOFlags::READ).await?
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Write a buffer into this writer. Returning how many bytes were written.
+    ///
+    /// Instead of blocking the current fiber until the socket is ready, the
+    /// returned future yields [`Poll::Pending`] and wakes the task when the
+    /// socket becomes writable. Must be polled by a fiber based executor like
+    /// [`fiber::block_on`].
+    pub async fn write_async(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
+        loop {
+            match try_write(self.fd, buf) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    CoIOWait::new(self.fd, ffi::CoIOFlags::WRITE).await?
+                }
+                res => return res,
+            }
+        }
+    }
 }
 
 impl IntoRawFd for CoIOStream {
@@ -174,6 +212,76 @@ pub fn coio_wait(fd: RawFd, flags: ffi::CoIOFlags, timeout: f64) -> Result<(), i
     }
 }
 
+/// A future which resolves once a `READ` or `WRITE` event happens on the
+/// socket (`fd`). This is the asynchronous counterpart of [`coio_wait`].
+///
+/// When first polled a helper fiber is started which blocks in [`coio_wait`]
+/// and wakes the task once the event happens. If the future is dropped before
+/// that, the helper fiber is cancelled.
+pub struct CoIOWait {
+    fd: RawFd,
+    flags: ffi::CoIOFlags,
+    waiter: Option<(Rc<WaitState>, UnitJoinHandle)>,
+}
+
+#[derive(Default)]
+struct WaitState {
+    result: RefCell<Option<Result<(), io::Error>>>,
+    waker: RefCell<Option<Waker>>,
+}
+
+impl CoIOWait {
+    /// - `fd` - non-blocking socket file description
+    /// - `flags` - requested events to wait. Combination of [CoIOFlags::READ | CoIOFlags::WRITE](struct.CoIOFlags.html) bit flags.
+    pub fn new(fd: RawFd, flags: ffi::CoIOFlags) -> Self {
+        Self { fd, flags, waiter: None }
+    }
+}
+
+impl Future for CoIOWait {
+    type Output = Result<(), io::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let (fd, flags) = (self.fd, self.flags);
+        let (state, _) = self.waiter.get_or_insert_with(|| {
+            let state = Rc::new(WaitState::default());
+            let jh = {
+                let state = state.clone();
+                fiber::start_proc(move || {
+                    let res = coio_wait(fd, flags, TIMEOUT_INFINITY);
+                    state.result.replace(Some(res));
+                    if let Some(waker) = state.waker.borrow_mut().take() {
+                        waker.wake()
+                    }
+                })
+            };
+            (state, jh)
+        });
+
+        let res = state.result.borrow_mut().take();
+        match res {
+            Some(res) => {
+                let (_, jh) = self.waiter.take().unwrap();
+                jh.join();
+                Poll::Ready(res)
+            }
+            None => {
+                state.waker.replace(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for CoIOWait {
+    fn drop(&mut self) {
+        if let Some((_, jh)) = self.waiter.take() {
+            jh.cancel();
+            jh.join();
+        }
+    }
+}
+
 /// Create new eio task with specified function and
 /// arguments. Yield and wait until the task is complete
 /// or a timeout occurs.
@@ -233,21 +341,35 @@ pub fn getaddrinfo(
     }
 }
 
+#[inline(always)]
+fn try_read(fd: RawFd, buf: &mut [u8]) -> Result<usize, io::Error> {
+    let result = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut c_void, buf.len()) };
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result as usize)
+    }
+}
+
+#[inline(always)]
+fn try_write(fd: RawFd, buf: &[u8]) -> Result<usize, io::Error> {
+    let result = unsafe { libc::write(fd, buf.as_ptr() as *mut c_void, buf.len()) };
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result as usize)
+    }
+}
+
 #[inline(always)]
 pub(crate) fn read(
     fd: RawFd,
     buf: &mut [u8],
     timeout: Option<Duration>,
 ) -> Result<usize, io::Error> {
-    let buf_len = buf.len();
-    let result = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut c_void, buf_len) };
-    if result >= 0 {
-        return Ok(result as usize);
-    }
-
-    let err = io::Error::last_os_error();
-    if err.kind() != io::ErrorKind::WouldBlock {
-        return Err(err);
+    match try_read(fd, buf) {
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+        res => return res,
     }
 
     let timeout = match timeout {
@@ -256,24 +378,14 @@ pub(crate) fn read(
     };
 
     coio_wait(fd, ffi::CoIOFlags::READ, timeout)?;
-    let result = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut c_void, buf_len) };
-    if result < 0 {
-        Err(io::Error::last_os_error())
-    } else {
-        Ok(result as usize)
-    }
+    try_read(fd, buf)
 }
 
 #[inline(always)]
 pub(crate) fn write(fd: RawFd, buf: &[u8], timeout: Option<Duration>) -> Result<usize, io::Error> {
-    let result = unsafe { libc::write(fd, buf.as_ptr() as *mut c_void, buf.len()) };
-    if result >= 0 {
-        return Ok(result as usize);
-    }
-
-    let err = io::Error::last_os_error();
-    if err.kind() != io::ErrorKind::WouldBlock {
-        return Err(err);
+    match try_write(fd, buf) {
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+        res => return res,
     }
 
     let timeout = match timeout {
@@ -282,12 +394,7 @@ pub(crate) fn write(fd: RawFd, buf: &[u8], timeout: Option<Duration>) -> Result<
     };
 
     coio_wait(fd, ffi::CoIOFlags::WRITE, timeout)?;
-    let result = unsafe { libc::write(fd, buf.as_ptr() as *mut c_void, buf.len()) };
-    if result < 0 {
-        Err(io::Error::last_os_error())
-    } else {
-        Ok(result as usize)
-    }
+    try_write(fd, buf)
 }
 
 /// Creates a new asynchronous channel, returning the sender/receiver halves.
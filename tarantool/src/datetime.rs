@@ -0,0 +1,310 @@
+//! Date and time types
+//!
+//! [`Datetime`] and [`Interval`] correspond to the `datetime` and `interval`
+//! types of tarantool (since version 2.10). They are encoded as the MsgPack
+//! extensions, so they can be used as tuple fields.
+//!
+//! See also:
+//! - [Module datetime](https://www.tarantool.io/en/doc/latest/reference/reference_lua/datetime/)
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::Cursor;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::msgpack::{Ext, MP_DATETIME, MP_INTERVAL};
+
+////////////////////////////////////////////////////////////////////////////////
+// Datetime
+////////////////////////////////////////////////////////////////////////////////
+
+/// A point in time with an optional time zone offset.
+///
+/// Two datetimes are equal only if both the point in time and the time zone
+/// are the same.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Datetime {
+    secs: i64,
+    nsec: i32,
+    tzoffset: i16,
+    tzindex: i16,
+}
+
+impl Datetime {
+    /// Creates a datetime `secs` seconds and `nsec` nanoseconds after the Unix
+    /// epoch in UTC.
+    ///
+    /// Panics if `nsec` is not less than `1_000_000_000`.
+    pub fn from_timestamp(secs: i64, nsec: u32) -> Self {
+        assert!(nsec < 1_000_000_000, "nanoseconds out of range: {}", nsec);
+        Self { secs, nsec: nsec as _, tzoffset: 0, tzindex: 0 }
+    }
+
+    /// Returns the current time in UTC.
+    pub fn now() -> Self {
+        SystemTime::now().into()
+    }
+
+    /// Returns the same point in time with the time zone offset set to
+    /// `minutes` east of UTC.
+    pub fn with_tzoffset(self, minutes: i16) -> Self {
+        Self { tzoffset: minutes, tzindex: 0, ..self }
+    }
+
+    /// Returns the number of whole seconds since the Unix epoch.
+    pub fn timestamp(&self) -> i64 {
+        self.secs
+    }
+
+    /// Returns the number of nanoseconds since the last whole second.
+    pub fn nsec(&self) -> u32 {
+        self.nsec as _
+    }
+
+    /// Returns the time zone offset in minutes east of UTC.
+    pub fn tzoffset(&self) -> i16 {
+        self.tzoffset
+    }
+
+    /// Returns the index of the time zone in tarantool's time zone database,
+    /// or 0 if the time zone is only specified with the offset.
+    pub fn tzindex(&self) -> i16 {
+        self.tzindex
+    }
+
+    pub fn to_system_time(&self) -> SystemTime {
+        if self.secs >= 0 {
+            UNIX_EPOCH + Duration::new(self.secs as _, self.nsec as _)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(self.secs.wrapping_neg() as u64)
+                + Duration::from_nanos(self.nsec as _)
+        }
+    }
+}
+
+impl From<SystemTime> for Datetime {
+    fn from(t: SystemTime) -> Self {
+        match t.duration_since(UNIX_EPOCH) {
+            Ok(d) => Self::from_timestamp(d.as_secs() as _, d.subsec_nanos()),
+            Err(e) => {
+                let d = e.duration();
+                let mut secs = -(d.as_secs() as i64);
+                let mut nsec = d.subsec_nanos();
+                if nsec > 0 {
+                    secs -= 1;
+                    nsec = 1_000_000_000 - nsec;
+                }
+                Self::from_timestamp(secs, nsec)
+            }
+        }
+    }
+}
+
+/// Formats the datetime in ISO 8601 format, e.g.
+/// `2022-03-01T12:30:00.5+0300`.
+impl fmt::Display for Datetime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let local = self.secs + self.tzoffset as i64 * 60;
+        let (year, month, day) = civil_from_days(local.div_euclid(86400));
+        let secs_of_day = local.rem_euclid(86400);
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            year, month, day,
+            secs_of_day / 3600, secs_of_day / 60 % 60, secs_of_day % 60,
+        )?;
+        if self.nsec != 0 {
+            let nsec = format!("{:09}", self.nsec);
+            write!(f, ".{}", nsec.trim_end_matches('0'))?;
+        }
+        if self.tzoffset == 0 {
+            f.write_str("Z")
+        } else {
+            let sign = if self.tzoffset < 0 { '-' } else { '+' };
+            let offset = (self.tzoffset as i32).abs();
+            write!(f, "{}{:02}{:02}", sign, offset / 60, offset % 60)
+        }
+    }
+}
+
+/// Converts the number of days since the Unix epoch into a (year, month, day)
+/// triple in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+impl Ext for Datetime {
+    const TYPE: i8 = MP_DATETIME;
+    const NAME: &'static str = "Datetime";
+
+    fn to_ext_data(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(16);
+        data.extend_from_slice(&self.secs.to_le_bytes());
+        // the rest is omitted if it's all zeros
+        if self.nsec != 0 || self.tzoffset != 0 || self.tzindex != 0 {
+            data.extend_from_slice(&self.nsec.to_le_bytes());
+            data.extend_from_slice(&self.tzoffset.to_le_bytes());
+            data.extend_from_slice(&self.tzindex.to_le_bytes());
+        }
+        data
+    }
+
+    fn from_ext_data(data: &[u8]) -> Result<Self, String> {
+        if data.len() != 8 && data.len() != 16 {
+            return Err(format!(
+                "Invalid Datetime data: expected 8 or 16 bytes, got {}", data.len()
+            ))
+        }
+        let mut res = Self {
+            secs: i64::from_le_bytes(<[u8; 8]>::try_from(&data[0..8]).unwrap()),
+            ..Self::default()
+        };
+        if data.len() == 16 {
+            res.nsec = i32::from_le_bytes(<[u8; 4]>::try_from(&data[8..12]).unwrap());
+            res.tzoffset = i16::from_le_bytes(<[u8; 2]>::try_from(&data[12..14]).unwrap());
+            res.tzindex = i16::from_le_bytes(<[u8; 2]>::try_from(&data[14..16]).unwrap());
+        }
+        Ok(res)
+    }
+}
+
+crate::msgpack::impl_serde_for_ext!(Datetime);
+
+////////////////////////////////////////////////////////////////////////////////
+// Interval
+////////////////////////////////////////////////////////////////////////////////
+
+/// A time interval which can be added to or subtracted from a [`Datetime`]
+/// in tarantool.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Interval {
+    pub year: i32,
+    pub month: i32,
+    pub week: i32,
+    pub day: i32,
+    pub hour: i64,
+    pub min: i64,
+    pub sec: i64,
+    pub nsec: i32,
+    pub adjust: Adjust,
+}
+
+/// Defines how to adjust the day of month when adding months or years to
+/// the last days of a month.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Adjust {
+    /// Clamp the day to the last day of the resulting month.
+    None,
+    /// Overflow into the next month.
+    Excess,
+    /// Keep the last day of month as the last day.
+    Last,
+}
+
+impl Default for Adjust {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl Adjust {
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Excess => 0,
+            Self::None => 1,
+            Self::Last => 2,
+        }
+    }
+
+    fn from_i64(v: i64) -> Option<Self> {
+        match v {
+            0 => Some(Self::Excess),
+            1 => Some(Self::None),
+            2 => Some(Self::Last),
+            _ => None,
+        }
+    }
+}
+
+// field ids in the interval encoding
+const FIELD_YEAR: u8 = 0;
+const FIELD_MONTH: u8 = 1;
+const FIELD_WEEK: u8 = 2;
+const FIELD_DAY: u8 = 3;
+const FIELD_HOUR: u8 = 4;
+const FIELD_MINUTE: u8 = 5;
+const FIELD_SECOND: u8 = 6;
+const FIELD_NANOSECOND: u8 = 7;
+const FIELD_ADJUST: u8 = 8;
+
+impl Ext for Interval {
+    const TYPE: i8 = MP_INTERVAL;
+    const NAME: &'static str = "Interval";
+
+    fn to_ext_data(&self) -> Vec<u8> {
+        let fields = [
+            (FIELD_YEAR, self.year as i64),
+            (FIELD_MONTH, self.month as i64),
+            (FIELD_WEEK, self.week as i64),
+            (FIELD_DAY, self.day as i64),
+            (FIELD_HOUR, self.hour),
+            (FIELD_MINUTE, self.min),
+            (FIELD_SECOND, self.sec),
+            (FIELD_NANOSECOND, self.nsec as i64),
+        ];
+        let adjust = (FIELD_ADJUST, self.adjust.to_u8() as i64);
+        // only the non default values are encoded
+        let fields: Vec<_> = fields.iter()
+            .copied()
+            .filter(|&(_, v)| v != 0)
+            .chain(Some(adjust).filter(|_| self.adjust != Adjust::None))
+            .collect();
+
+        let mut data = vec![fields.len() as u8];
+        for (id, value) in fields {
+            data.push(id);
+            rmp::encode::write_sint(&mut data, value).unwrap();
+        }
+        data
+    }
+
+    fn from_ext_data(data: &[u8]) -> Result<Self, String> {
+        fn read(data: &[u8]) -> Result<Interval, Box<dyn std::error::Error>> {
+            let mut cur = Cursor::new(data);
+            let mut res = Interval::default();
+            let count = rmp::decode::read_data_u8(&mut cur)?;
+            for _ in 0..count {
+                let id = rmp::decode::read_data_u8(&mut cur)?;
+                let value: i64 = rmp::decode::read_int(&mut cur)?;
+                match id {
+                    FIELD_YEAR => res.year = i32::try_from(value)?,
+                    FIELD_MONTH => res.month = i32::try_from(value)?,
+                    FIELD_WEEK => res.week = i32::try_from(value)?,
+                    FIELD_DAY => res.day = i32::try_from(value)?,
+                    FIELD_HOUR => res.hour = value,
+                    FIELD_MINUTE => res.min = value,
+                    FIELD_SECOND => res.sec = value,
+                    FIELD_NANOSECOND => res.nsec = i32::try_from(value)?,
+                    FIELD_ADJUST => {
+                        res.adjust = Adjust::from_i64(value)
+                            .ok_or_else(|| format!("unknown adjust value {}", value))?
+                    }
+                    _ => return Err(format!("unknown field {}", id).into()),
+                }
+            }
+            Ok(res)
+        }
+
+        read(data).map_err(|e| format!("Invalid Interval data: {}", e))
+    }
+}
+
+crate::msgpack::impl_serde_for_ext!(Interval);
@@ -572,57 +572,36 @@ impl_try_into_int!{u64 usize => ffi::decimal_to_uint64}
 /// Tuple
 ////////////////////////////////////////////////////////////////////////////////
 
-impl serde::Serialize for Decimal {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        #[derive(Serialize)]
-        struct _ExtStruct((i8, serde_bytes::ByteBuf));
-
-        let data = unsafe {
+impl crate::msgpack::Ext for Decimal {
+    const TYPE: i8 = ffi::MP_DECIMAL;
+    const NAME: &'static str = "Decimal";
+
+    fn to_ext_data(&self) -> Vec<u8> {
+        unsafe {
             let len = ffi::decimal_len(&self.inner) as usize;
             let mut data = Vec::<u8>::with_capacity(len);
             ffi::decimal_pack(data.as_mut_ptr() as _, &self.inner);
             data.set_len(len);
             data
-        };
-        _ExtStruct((ffi::MP_DECIMAL, serde_bytes::ByteBuf::from(data)))
-            .serialize(serializer)
+        }
     }
-}
 
-impl<'de> serde::Deserialize<'de> for Decimal {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        #[derive(Deserialize)]
-        struct _ExtStruct((i8, serde_bytes::ByteBuf));
-
-        match serde::Deserialize::deserialize(deserializer)? {
-            _ExtStruct((ffi::MP_DECIMAL, bytes)) => {
-                let data = bytes.into_vec();
-                let data_p = &mut data.as_ptr().cast::<i8>();
-                let mut dec = std::mem::MaybeUninit::uninit();
-                let res = unsafe {
-                    ffi::decimal_unpack(data_p, data.len() as _, dec.as_mut_ptr())
-                };
-                if res.is_null() {
-                    Err(serde::de::Error::custom("Decimal out of range or corrupt"))
-                } else {
-                    unsafe { Ok(Self::from_raw(dec.assume_init())) }
-                }
-            }
-            _ExtStruct((kind, _)) => {
-                Err(serde::de::Error::custom(
-                    format!("Expected Decimal, found msgpack ext #{}", kind)
-                ))
-            }
+    fn from_ext_data(data: &[u8]) -> Result<Self, String> {
+        let data_p = &mut data.as_ptr().cast();
+        let mut dec = std::mem::MaybeUninit::uninit();
+        let res = unsafe {
+            ffi::decimal_unpack(data_p, data.len() as _, dec.as_mut_ptr())
+        };
+        if res.is_null() {
+            Err("Decimal out of range or corrupt".into())
+        } else {
+            unsafe { Ok(Self::from_raw(dec.assume_init())) }
         }
     }
 }
 
+crate::msgpack::impl_serde_for_ext!(Decimal);
+
 ////////////////////////////////////////////////////////////////////////////////
 /// decimal!
 ////////////////////////////////////////////////////////////////////////////////
@@ -69,6 +69,9 @@ pub enum Error {
     #[error("Transaction issue: {0}")]
     Transaction(TransactionError),
 
+    #[error("Incompatible space format: {0}")]
+    IncompatibleFormat(crate::space::FormatError),
+
     #[cfg(feature = "net_box")]
     #[error("Sever respond with error: {0}")]
     Remote(crate::net_box::ResponseError),
@@ -171,6 +174,12 @@ impl From<TransactionError> for Error {
     }
 }
 
+impl From<crate::space::FormatError> for Error {
+    fn from(error: crate::space::FormatError) -> Self {
+        Error::IncompatibleFormat(error)
+    }
+}
+
 /// Settable by Tarantool error type
 #[derive(Derivative)]
 #[derivative(Debug)]
@@ -237,6 +246,131 @@ impl From<TarantoolError> for Error {
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// ErrorInfo
+////////////////////////////////////////////////////////////////////////////////
+
+/// A tarantool error encoded as a MsgPack extension, e.g. a `box.error`
+/// object stored in a tuple or returned from a remote call.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorInfo {
+    /// Error type, e.g. "ClientError".
+    pub error_type: String,
+    pub message: String,
+    /// Source file where the error was created.
+    pub file: String,
+    /// Line in the source file where the error was created.
+    pub line: u32,
+    pub errno: u32,
+    /// Error code, see [TarantoolErrorCode](enum.TarantoolErrorCode.html).
+    pub code: u32,
+    /// The error which caused this one.
+    pub cause: Option<Box<ErrorInfo>>,
+}
+
+impl Display for ErrorInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.error_type, self.message)
+    }
+}
+
+impl std::error::Error for ErrorInfo {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause.as_deref().map(|e| e as _)
+    }
+}
+
+// keys of the error extension payload
+const MP_ERROR_STACK: u8 = 0x00;
+const MP_ERROR_TYPE: u8 = 0x00;
+const MP_ERROR_FILE: u8 = 0x01;
+const MP_ERROR_LINE: u8 = 0x02;
+const MP_ERROR_MESSAGE: u8 = 0x03;
+const MP_ERROR_ERRNO: u8 = 0x04;
+const MP_ERROR_CODE: u8 = 0x05;
+
+impl crate::msgpack::Ext for ErrorInfo {
+    const TYPE: i8 = crate::msgpack::MP_ERROR;
+    const NAME: &'static str = "Error";
+
+    fn to_ext_data(&self) -> Vec<u8> {
+        use rmp::encode::{write_array_len, write_map_len, write_str, write_uint};
+
+        let mut stack = vec![];
+        let mut next = Some(self);
+        while let Some(e) = next {
+            stack.push(e);
+            next = e.cause.as_deref();
+        }
+
+        // writing to a vec never fails
+        let mut data = vec![];
+        write_map_len(&mut data, 1).unwrap();
+        write_uint(&mut data, MP_ERROR_STACK as _).unwrap();
+        write_array_len(&mut data, stack.len() as _).unwrap();
+        for e in stack {
+            write_map_len(&mut data, 6).unwrap();
+            write_uint(&mut data, MP_ERROR_TYPE as _).unwrap();
+            write_str(&mut data, &e.error_type).unwrap();
+            write_uint(&mut data, MP_ERROR_FILE as _).unwrap();
+            write_str(&mut data, &e.file).unwrap();
+            write_uint(&mut data, MP_ERROR_LINE as _).unwrap();
+            write_uint(&mut data, e.line as _).unwrap();
+            write_uint(&mut data, MP_ERROR_MESSAGE as _).unwrap();
+            write_str(&mut data, &e.message).unwrap();
+            write_uint(&mut data, MP_ERROR_ERRNO as _).unwrap();
+            write_uint(&mut data, e.errno as _).unwrap();
+            write_uint(&mut data, MP_ERROR_CODE as _).unwrap();
+            write_uint(&mut data, e.code as _).unwrap();
+        }
+        data
+    }
+
+    fn from_ext_data(data: &[u8]) -> std::result::Result<Self, String> {
+        use std::collections::BTreeMap;
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            Str(String),
+            UInt(u64),
+            // e.g. the error's custom fields
+            Other(serde::de::IgnoredAny),
+        }
+
+        let mut payload: BTreeMap<u8, Vec<BTreeMap<u8, Value>>> = rmp_serde::from_read_ref(data)
+            .map_err(|e| format!("Invalid Error data: {}", e))?;
+        let stack = payload.remove(&MP_ERROR_STACK)
+            .ok_or("Invalid Error data: missing error stack")?;
+
+        let mut res = None;
+        for mut fields in stack.into_iter().rev() {
+            let mut take_str = |key| match fields.remove(&key) {
+                Some(Value::Str(s)) => s,
+                _ => String::new(),
+            };
+            let mut e = ErrorInfo {
+                error_type: take_str(MP_ERROR_TYPE),
+                file: take_str(MP_ERROR_FILE),
+                message: take_str(MP_ERROR_MESSAGE),
+                cause: res.take().map(Box::new),
+                ..Default::default()
+            };
+            let mut take_u32 = |key| match fields.remove(&key) {
+                Some(Value::UInt(v)) => v as u32,
+                _ => 0,
+            };
+            e.line = take_u32(MP_ERROR_LINE);
+            e.errno = take_u32(MP_ERROR_ERRNO);
+            e.code = take_u32(MP_ERROR_CODE);
+            res = Some(e);
+        }
+        res.ok_or_else(|| "Invalid Error data: empty error stack".into())
+    }
+}
+
+crate::msgpack::impl_serde_for_ext!(ErrorInfo);
+
 /// Codes of Tarantool errors
 #[repr(u32)]
 #[derive(Debug, Clone, PartialEq, ToPrimitive, FromPrimitive)]
@@ -155,6 +155,9 @@ extern "C" {
         f: FiberFunc,
     ) -> *mut Fiber;
 
+    /// Return the current fiber.
+    pub fn fiber_self() -> *mut Fiber;
+
     /// Return control to another fiber and wait until it'll be woken.
     ///
     /// See also: [fiber_wakeup](#fn.fiber_wakeup)
@@ -627,12 +630,19 @@ extern "C" {
 }
 
 // Transaction.
+#[repr(C)]
+pub struct BoxTxnSavepoint {
+    _unused: [u8; 0],
+}
+
 extern "C" {
     pub fn box_txn() -> bool;
     pub fn box_txn_begin() -> c_int;
     pub fn box_txn_commit() -> c_int;
     pub fn box_txn_rollback() -> c_int;
     pub fn box_txn_alloc(size: usize) -> *mut c_void;
+    pub fn box_txn_savepoint() -> *mut BoxTxnSavepoint;
+    pub fn box_txn_rollback_to_savepoint(savepoint: *mut BoxTxnSavepoint) -> c_int;
 }
 
 // Indexes, spaces and tuples.
@@ -684,6 +694,9 @@ extern "C" {
 extern "C" {
     pub fn box_index_id_by_name(space_id: u32, name: *const c_char, len: u32) -> u32;
     pub fn box_space_id_by_name(name: *const c_char, len: u32) -> u32;
+    /// Returns the current schema version, which changes on every DDL
+    /// operation.
+    pub fn box_schema_version() -> u64;
     pub fn box_index_len(space_id: u32, index_id: u32) -> isize;
     pub fn box_index_bsize(space_id: u32, index_id: u32) -> isize;
     pub fn box_index_random(
@@ -770,7 +783,16 @@ extern "C" {
     pub fn box_tuple_to_buf(tuple: *const BoxTuple, buf: *mut c_char, size: usize) -> isize;
     pub fn box_tuple_format_default() -> *mut BoxTupleFormat;
     pub fn box_tuple_format(tuple: *const BoxTuple) -> *mut BoxTupleFormat;
+    pub fn box_tuple_format_new(keys: *mut *mut BoxKeyDef, key_count: u16) -> *mut BoxTupleFormat;
+    pub fn box_tuple_format_ref(format: *mut BoxTupleFormat);
+    pub fn box_tuple_format_unref(format: *mut BoxTupleFormat);
     pub fn box_tuple_field(tuple: *const BoxTuple, fieldno: u32) -> *const c_char;
+    pub fn box_tuple_field_by_path(
+        tuple: *const BoxTuple,
+        path: *const c_char,
+        path_len: u32,
+        index_base: c_int,
+    ) -> *const c_char;
     pub fn box_tuple_compare(
         tuple_a: *mut BoxTuple,
         tuple_b: *mut BoxTuple,
@@ -803,9 +825,57 @@ pub struct BoxKeyDef {
     _unused: [u8; 0],
 }
 
+/// Key part definition used by [`box_key_def_new_v2`].
+///
+/// It's a packed union with 64 bytes of padding in the C API, which only
+/// makes a difference for the size of the struct.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BoxKeyPartDef {
+    pub fieldno: u32,
+    pub flags: u32,
+    pub field_type: *const c_char,
+    pub collation: *const c_char,
+    pub path: *const c_char,
+    _padding: [u8; 64 - 8 - 3 * std::mem::size_of::<*const c_char>()],
+}
+
+pub const BOX_KEY_PART_DEF_IS_NULLABLE: u32 = 1 << 0;
+
 extern "C" {
     pub fn box_key_def_new(fields: *mut u32, types: *mut u32, part_count: u32) -> *mut BoxKeyDef;
     pub fn box_key_def_delete(key_def: *mut BoxKeyDef);
+    pub fn box_key_part_def_create(part: *mut BoxKeyPartDef);
+    pub fn box_key_def_new_v2(parts: *mut BoxKeyPartDef, part_count: u32) -> *mut BoxKeyDef;
+    pub fn box_key_def_dup(key_def: *const BoxKeyDef) -> *mut BoxKeyDef;
+    /// Allocates the result on the box region.
+    pub fn box_key_def_dump_parts(
+        key_def: *const BoxKeyDef,
+        part_count_ptr: *mut u32,
+    ) -> *mut BoxKeyPartDef;
+    /// Allocates the result on the box region.
+    pub fn box_key_def_extract_key(
+        key_def: *mut BoxKeyDef,
+        tuple: *mut BoxTuple,
+        multikey_idx: c_int,
+        key_size_ptr: *mut u32,
+    ) -> *mut c_char;
+    pub fn box_index_key_def(space_id: u32, index_id: u32) -> *const BoxKeyDef;
+    /// Checks the types of the key parts, a partial key is valid.
+    pub fn box_key_def_validate_key(
+        key_def: *const BoxKeyDef,
+        key: *const c_char,
+        key_size_ptr: *mut u32,
+    ) -> c_int;
+}
+
+/// `multikey_idx` value for the non multikey indexes.
+pub const MULTIKEY_NONE: c_int = -1;
+
+// Region
+extern "C" {
+    pub fn box_region_used() -> usize;
+    pub fn box_region_truncate(size: usize);
 }
 
 #[repr(C)]
@@ -826,6 +896,7 @@ use crate::ffi::lua::lua_State;
 extern "C" {
     pub fn luaT_state() -> *mut lua_State;
     pub fn luaT_call(l: *mut lua_State, nargs: c_int, nreturns: c_int) -> isize;
+    pub fn luaT_error(l: *mut lua_State) -> c_int;
     pub fn luaT_istuple(l: *mut lua_State, index: i32) -> *mut BoxTuple;
     pub fn luaT_pushtuple(l: *mut lua_State, tuple: *mut BoxTuple);
 }
@@ -3,18 +3,24 @@
 //! With the fiber module, you can:
 //! - create, run and manage [fibers](struct.Fiber.html),
 //! - use a synchronization mechanism for fibers, similar to “condition variables” and similar to operating-system
-//! functions such as `pthread_cond_wait()` plus `pthread_cond_signal()`.
+//! functions such as `pthread_cond_wait()` plus `pthread_cond_signal()`,
+//! - wait on several [channels](Channel) and [conds](Cond) at once using [`Select`],
+//! - run [`Future`](std::future::Future)s on fibers using [`block_on`] and [`spawn_async`],
+//! - run delayed and periodic background jobs using [`Timer`] and [`Interval`],
+//! - spawn fibers borrowing data from the parent fiber using [`scope`].
 //!
 //! See also:
 //! - [Threads, fibers and yields](https://www.tarantool.io/en/doc/latest/book/box/atomic/#threads-fibers-and-yields)
 //! - [Lua reference: Module fiber](https://www.tarantool.io/en/doc/latest/reference/reference_lua/fiber/)
 //! - [C API reference: Module fiber](https://www.tarantool.io/en/doc/latest/dev_guide/reference_capi/fiber/)
-use std::cell::UnsafeCell;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
 use std::ffi::CString;
 use std::marker::PhantomData;
 use std::os::raw::c_void;
 use std::ptr::NonNull;
-use std::time::Duration;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use crate::tlua::{self as tlua, AsLua, lua_error};
 use va_list::VaList;
@@ -25,10 +31,20 @@ use crate::ffi::{tarantool as ffi, lua};
 use crate::Result;
 
 pub mod channel;
+pub mod executor;
+pub mod panic;
+pub mod scope;
+pub mod select;
+pub mod timer;
 
 pub use channel::{
     Channel, SendTimeout, RecvTimeout, SendError, RecvError, TrySendError, TryRecvError,
 };
+pub use executor::{block_on, spawn_async};
+pub use panic::{FiberPanic, set_panic_hook, take_panic_hook};
+pub use scope::{scope, Scope, ScopedJoinHandle};
+pub use select::{Select, SelectedOperation, SelectError};
+pub use timer::{Timer, Interval};
 
 /// *OBSOLETE*: This struct is being deprecated in favour of [`Immediate`],
 /// [`Deferred`], etc. due to them being more efficient and idiomatic.
@@ -237,7 +253,7 @@ impl Builder<NoFunc> {
             attr: self.attr,
             f: FiberFunc {
                 f: Box::new(f),
-                result: Default::default(),
+                state: Default::default(),
             },
         }
     }
@@ -251,7 +267,10 @@ impl Builder<NoFunc> {
         Builder {
             name: self.name,
             attr: self.attr,
-            f: FiberProc { f: Box::new(f) },
+            f: FiberProc {
+                f: Box::new(f),
+                state: Default::default(),
+            },
         }
     }
 }
@@ -487,8 +506,8 @@ where
                 lua_error!(l, "rust FnOnce callback was called more than once")
             );
 
-        // call f and drop it afterwards
-        let res = f();
+        // call f and drop it afterwards, the panic must not unwind into lua
+        let res = panic::catch(f);
 
         // return results to lua
         C::save_result(l, res)
@@ -509,7 +528,17 @@ impl<T> LuaJoinHandle<T> {
         Self { fiber_ref: Some(fiber_ref), marker: PhantomData }
     }
 
-    pub fn join(mut self) -> T {
+    /// Block until the fiber's termination and return it's result value.
+    ///
+    /// If the fiber function panicked, the panic is propagated to the current
+    /// fiber.
+    pub fn join(self) -> T {
+        self.try_join().unwrap_or_else(|p| p.resume())
+    }
+
+    /// Block until the fiber's termination and return it's result value or
+    /// the panic the fiber function raised.
+    pub fn try_join(mut self) -> std::result::Result<T, FiberPanic> {
         // It's safe to unwrap fiber_ref here because join will only be called
         // once after the join handle creation
         let fiber_ref = self.fiber_ref.take().unwrap();
@@ -518,7 +547,7 @@ impl<T> LuaJoinHandle<T> {
                 .map_err(|e| panic!("Unrecoverable lua failure: {}", e))
                 .unwrap();
             let ud_ptr = lua::lua_touserdata(guard.as_lua(), -1);
-            let res = (ud_ptr as *mut Option<T>).as_mut()
+            let res = (ud_ptr as *mut Option<std::result::Result<T, FiberPanic>>).as_mut()
                 .expect("fiber:join must return correct userdata")
                 .take()
                 .expect("data can only be taken once from the UDBox");
@@ -548,11 +577,31 @@ impl LuaUnitJoinHandle {
         Self { fiber_ref: Some(fiber_ref) }
     }
 
-    pub fn join(mut self) {
+    /// Block until the fiber's termination.
+    ///
+    /// If the fiber function panicked, the panic is propagated to the current
+    /// fiber.
+    pub fn join(self) {
+        self.try_join().unwrap_or_else(|p| p.resume())
+    }
+
+    /// Block until the fiber's termination and return the panic the fiber
+    /// function raised if any.
+    pub fn try_join(mut self) -> std::result::Result<(), FiberPanic> {
         let fiber_ref = self.fiber_ref.take().unwrap();
-        match unsafe { impl_details::lua_fiber_join(fiber_ref) } {
-            Ok(_pushguard) => (),
-            Err(e) => panic!("Unrecoverable lua failure: {}", e),
+        unsafe {
+            let guard = match impl_details::lua_fiber_join(fiber_ref) {
+                Ok(guard) => guard,
+                Err(e) => panic!("Unrecoverable lua failure: {}", e),
+            };
+            // the fiber function only returns a value if it panicked
+            let ud_ptr = lua::lua_touserdata(guard.as_lua(), -1);
+            match (ud_ptr as *mut Option<FiberPanic>).as_mut() {
+                None => Ok(()),
+                Some(ud) => Err(
+                    ud.take().expect("data can only be taken once from the UDBox")
+                ),
+            }
         }
     }
 }
@@ -670,11 +719,14 @@ pub trait LuaCallee {
     fn join_handle(fiber_ref: i32) -> Self::JoinHandle;
 
     /// This function is called within `LuaFiber::trampoline` to save the
-    /// return value after the callee's invocation
+    /// return value (or the caught panic) after the callee's invocation
     ///
     /// This function is unsafe, because it is very easy to mess things up
     /// when preparing arugments.
-    unsafe fn save_result(l: *mut lua::lua_State, res: Self::Output) -> i32;
+    unsafe fn save_result(
+        l: *mut lua::lua_State,
+        res: std::result::Result<Self::Output, FiberPanic>,
+    ) -> i32;
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -700,7 +752,10 @@ where
         LuaJoinHandle::new(fiber_ref)
     }
 
-    unsafe fn save_result(l: *mut lua::lua_State, res: T) -> i32 {
+    unsafe fn save_result(
+        l: *mut lua::lua_State,
+        res: std::result::Result<T, FiberPanic>,
+    ) -> i32 {
         tlua::push_some_userdata(l, res);
         1
     }
@@ -729,8 +784,17 @@ where
         self.0
     }
 
-    unsafe fn save_result(_: *mut lua::lua_State, _: ()) -> i32 {
-        0
+    unsafe fn save_result(
+        l: *mut lua::lua_State,
+        res: std::result::Result<(), FiberPanic>,
+    ) -> i32 {
+        match res {
+            Ok(()) => 0,
+            Err(p) => {
+                tlua::push_some_userdata(l, p);
+                1
+            }
+        }
     }
 }
 
@@ -787,7 +851,7 @@ where
     F: FnOnce() -> T,
 {
     f: Box<F>,
-    result: Box<UnsafeCell<Option<T>>>,
+    state: Rc<JoinState<T>>,
 }
 
 impl<F, T> Callee for FiberFunc<F, T>
@@ -795,22 +859,24 @@ where
     F: FnOnce() -> T,
 {
     type JoinHandle = JoinHandle<T>;
-    type Args = (Box<F>, *mut Option<T>);
+    type Args = (Box<F>, Rc<JoinState<T>>);
 
     unsafe fn start_fiber(self, inner: NonNull<ffi::Fiber>) -> Self::JoinHandle {
-        let (f, result): Self::Args = (self.f, self.result.get());
-        ffi::fiber_start(inner.as_ptr(), Box::into_raw(f), result);
-        JoinHandle::new(inner, self.result)
+        // The fiber holds it's own reference to the state, because it can
+        // outlive the join handle if it's detached
+        let state = Rc::into_raw(self.state.clone());
+        ffi::fiber_start(inner.as_ptr(), Box::into_raw(self.f), state);
+        JoinHandle::new(inner, self.state)
     }
 
     unsafe fn parse_args(mut args: VaList) -> Self::Args {
         let f = args.get_boxed::<F>();
-        let result = args.get_ptr::<Option<T>>();
-        (f, result)
+        let state = Rc::from_raw(args.get_ptr::<JoinState<T>>());
+        (f, state)
     }
 
-    unsafe fn invoke((f, result): Self::Args) {
-        std::ptr::write(result, Some(f()))
+    unsafe fn invoke((f, state): Self::Args) {
+        state.finish(panic::catch(f))
     }
 }
 
@@ -821,6 +887,7 @@ where
     F: FnOnce(),
 {
     f: Box<F>,
+    state: Rc<JoinState<()>>,
 }
 
 impl<F> Callee for FiberProc<F>
@@ -828,20 +895,22 @@ where
     F: FnOnce(),
 {
     type JoinHandle = UnitJoinHandle;
-    type Args = Box<F>;
+    type Args = (Box<F>, Rc<JoinState<()>>);
 
     unsafe fn start_fiber(self, inner: NonNull<ffi::Fiber>) -> Self::JoinHandle {
-        let f: Self::Args = self.f;
-        ffi::fiber_start(inner.as_ptr(), Box::into_raw(f));
-        UnitJoinHandle::new(inner)
+        let state = Rc::into_raw(self.state.clone());
+        ffi::fiber_start(inner.as_ptr(), Box::into_raw(self.f), state);
+        UnitJoinHandle { inner: JoinHandle::new(inner, self.state) }
     }
 
     unsafe fn parse_args(mut args: VaList) -> Self::Args {
-        args.get_boxed::<F>()
+        let f = args.get_boxed::<F>();
+        let state = Rc::from_raw(args.get_ptr::<JoinState<()>>());
+        (f, state)
     }
 
-    unsafe fn invoke(f: Self::Args) {
-        f()
+    unsafe fn invoke((f, state): Self::Args) {
+        state.finish(panic::catch(f))
     }
 }
 
@@ -899,22 +968,95 @@ impl Invocation for Deferred {
 /// An owned permission to join on an immediate fiber (block on its termination).
 pub struct JoinHandle<T> {
     inner: Option<NonNull<ffi::Fiber>>,
-    result: Box<UnsafeCell<Option<T>>>,
+    state: Rc<JoinState<T>>,
 }
 
 impl<T> JoinHandle<T> {
-    fn new(inner: NonNull<ffi::Fiber>, result: Box<UnsafeCell<Option<T>>>) -> Self {
-        Self { inner: Some(inner), result }
+    fn new(inner: NonNull<ffi::Fiber>, state: Rc<JoinState<T>>) -> Self {
+        Self { inner: Some(inner), state }
     }
 
     /// Block until the fiber's termination and return it's result value.
-    pub fn join(mut self) -> T {
+    ///
+    /// If the fiber function panicked, the panic is propagated to the current
+    /// fiber.
+    pub fn join(self) -> T {
+        self.try_join().unwrap_or_else(|p| p.resume())
+    }
+
+    /// Block until the fiber's termination and return it's result value or
+    /// the panic the fiber function raised.
+    pub fn try_join(mut self) -> std::result::Result<T, FiberPanic> {
         // It's safe to unwrap because join will only be called once after the
         // join handle was created
         let inner_raw = self.inner.take().unwrap().as_ptr();
         // TODO: add error handling
         let _code = unsafe { ffi::fiber_join(inner_raw) };
-        self.result.get_mut().take().unwrap()
+        self.state.result.take().unwrap()
+    }
+
+    /// Block until the fiber's termination or until the `timeout` expires.
+    ///
+    /// Returns the fiber's result value or the join handle itself in case of a
+    /// timeout, so that the fiber can be joined (or cancelled) later.
+    ///
+    /// If the fiber function panicked, the panic is propagated to the current
+    /// fiber.
+    pub fn join_timeout(self, timeout: Duration) -> std::result::Result<T, Self> {
+        let deadline = Instant::now() + timeout;
+        while !self.is_finished() {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(self)
+            }
+            self.state.wait_timeout(deadline - now);
+        }
+        Ok(self.join())
+    }
+
+    /// Returns `true` if the fiber function has returned (or panicked). The
+    /// fiber still has to be joined.
+    pub fn is_finished(&self) -> bool {
+        self.state.is_finished.get()
+    }
+
+    /// Cancel the fiber.
+    ///
+    /// The fiber is woken up if it's blocked in a cancellable call (e.g.
+    /// [`sleep`] or [`Cond::wait`]) and [`is_cancelled`] starts returning
+    /// `true` in it. The fiber function must check it and return as soon as
+    /// possible. The fiber still has to be joined.
+    ///
+    /// Does nothing if the fiber has already finished.
+    pub fn cancel(&self) {
+        self.state.is_cancelled.set(true);
+        if let (Some(inner), false) = (self.inner, self.is_finished()) {
+            unsafe { ffi::fiber_cancel(inner.as_ptr()) }
+        }
+    }
+
+    /// Returns `true` if [`cancel`](Self::cancel) was called for the fiber.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.is_cancelled.get()
+    }
+
+    /// Interrupt a synchronous wait of the fiber.
+    ///
+    /// Does nothing if the fiber has already finished.
+    pub fn wakeup(&self) {
+        if let (Some(inner), false) = (self.inner, self.is_finished()) {
+            unsafe { ffi::fiber_wakeup(inner.as_ptr()) }
+        }
+    }
+
+    /// Make the fiber non-joinable, so that it's recycled as soon as it
+    /// finishes.
+    ///
+    /// **NOTE**: the fiber must not have finished yet, otherwise it will never
+    /// be recycled.
+    pub(crate) fn detach(mut self) {
+        let inner = self.inner.take().unwrap();
+        unsafe { ffi::fiber_set_joinable(inner.as_ptr(), false) }
     }
 }
 
@@ -926,36 +1068,105 @@ impl<T> Drop for JoinHandle<T> {
     }
 }
 
+/// The state shared between a fiber and it's join handle.
+pub struct JoinState<T> {
+    result: Cell<Option<std::result::Result<T, FiberPanic>>>,
+    is_finished: Cell<bool>,
+    is_cancelled: Cell<bool>,
+    /// Created by the first [`JoinHandle::join_timeout`] call, the plain join
+    /// uses `fiber_join`.
+    done: RefCell<Option<Cond>>,
+}
+
+impl<T> Default for JoinState<T> {
+    fn default() -> Self {
+        Self {
+            result: Cell::new(None),
+            is_finished: Cell::new(false),
+            is_cancelled: Cell::new(false),
+            done: RefCell::new(None),
+        }
+    }
+}
+
+impl<T> JoinState<T> {
+    fn finish(&self, result: std::result::Result<T, FiberPanic>) {
+        self.result.set(Some(result));
+        self.is_finished.set(true);
+        if let Some(done) = &*self.done.borrow() {
+            done.broadcast()
+        }
+    }
+
+    fn wait_timeout(&self, timeout: Duration) {
+        if self.done.borrow().is_none() {
+            *self.done.borrow_mut() = Some(Cond::new());
+        }
+        if let Some(done) = &*self.done.borrow() {
+            done.wait_timeout(timeout);
+        }
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // UnitJoinHandle
 ////////////////////////////////////////////////////////////////////////////////
 
 /// An owned permission to join on an immediate fiber (block on its termination).
 ///
-/// This is an optimized case of [`JoinHandle`]`<()>`.
+/// This is the [`JoinHandle`] of a fiber procedure, see [`JoinHandle`] for
+/// details.
 pub struct UnitJoinHandle {
-    inner: Option<NonNull<ffi::Fiber>>,
+    inner: JoinHandle<()>,
 }
 
 impl UnitJoinHandle {
-    fn new(inner: NonNull<ffi::Fiber>) -> Self {
-        Self { inner: Some(inner) }
+    /// Block until the fiber's termination.
+    ///
+    /// If the fiber function panicked, the panic is propagated to the current
+    /// fiber.
+    pub fn join(self) {
+        self.inner.join()
     }
 
-    /// Block until the fiber's termination.
-    pub fn join(mut self) {
-        // It's safe to unwrap because join will only be called once after the
-        // join handle was created
-        let inner_raw = self.inner.take().unwrap().as_ptr();
-        let _code = unsafe { ffi::fiber_join(inner_raw) };
+    /// Block until the fiber's termination and return the panic the fiber
+    /// function raised if any.
+    pub fn try_join(self) -> std::result::Result<(), FiberPanic> {
+        self.inner.try_join()
     }
-}
 
-impl Drop for UnitJoinHandle {
-    fn drop(&mut self) {
-        if self.inner.is_some() {
-            panic!("UnitJoinHandle dropped before being joined")
-        }
+    /// Block until the fiber's termination or until the `timeout` expires.
+    ///
+    /// Returns the join handle itself in case of a timeout.
+    ///
+    /// If the fiber function panicked, the panic is propagated to the current
+    /// fiber.
+    pub fn join_timeout(self, timeout: Duration) -> std::result::Result<(), Self> {
+        self.inner.join_timeout(timeout).map_err(|inner| Self { inner })
+    }
+
+    /// See [`JoinHandle::is_finished`].
+    pub fn is_finished(&self) -> bool {
+        self.inner.is_finished()
+    }
+
+    /// See [`JoinHandle::cancel`].
+    pub fn cancel(&self) {
+        self.inner.cancel()
+    }
+
+    /// See [`JoinHandle::is_cancelled`].
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+
+    /// See [`JoinHandle::wakeup`].
+    pub fn wakeup(&self) {
+        self.inner.wakeup()
+    }
+
+    pub(crate) fn detach(self) {
+        self.inner.detach()
     }
 }
 
@@ -1207,6 +1418,11 @@ impl Drop for FiberAttr {
 /// Unlike `pthread_cond`, [Cond]() doesn't require mutex/latch wrapping.
 pub struct Cond {
     inner: *mut ffi::FiberCond,
+    /// Tickets of the fibers blocked in [`Cond::wait_timeout`] which weren't
+    /// signalled yet, in the order of the `fiber_cond` queue.
+    waiters: RefCell<VecDeque<u64>>,
+    next_ticket: Cell<u64>,
+    watchers: select::Watchers,
 }
 
 /// - call [Cond::new()](#method.new) to create a named condition variable, which will be called `cond` for examples in this section.
@@ -1218,19 +1434,31 @@ impl Cond {
     pub fn new() -> Self {
         Cond {
             inner: unsafe { ffi::fiber_cond_new() },
+            waiters: Default::default(),
+            next_ticket: Cell::new(0),
+            watchers: Default::default(),
         }
     }
 
     /// Wake one fiber waiting for the cond.
     /// Does nothing if no one is waiting. Does not yield.
+    ///
+    /// The fibers waiting in [wait()](#method.wait) are woken up before the
+    /// ones waiting for the cond in a [`Select`].
     pub fn signal(&self) {
-        unsafe { ffi::fiber_cond_signal(self.inner) }
+        if self.waiters.borrow_mut().pop_front().is_some() {
+            unsafe { ffi::fiber_cond_signal(self.inner) }
+        } else {
+            self.watchers.fire_one();
+        }
     }
 
     /// Wake up all fibers waiting for the cond.
     /// Does not yield.
     pub fn broadcast(&self) {
+        self.waiters.borrow_mut().clear();
         unsafe { ffi::fiber_cond_broadcast(self.inner) }
+        self.watchers.fire_all()
     }
 
     /// Suspend the execution of the current fiber (i.e. yield) until [signal()](#method.signal) is called.
@@ -1246,12 +1474,22 @@ impl Cond {
     /// - `true` on [signal()](#method.signal) call or a spurious wake up.
     /// - `false` on timeout, diag is set to `TimedOut`
     pub fn wait_timeout(&self, timeout: Duration) -> bool {
-        !(unsafe { ffi::fiber_cond_wait_timeout(self.inner, timeout.as_secs_f64()) } < 0)
+        self.wait_impl(|| unsafe { ffi::fiber_cond_wait_timeout(self.inner, timeout.as_secs_f64()) })
     }
 
     /// Shortcut for [wait_timeout()](#method.wait_timeout).
     pub fn wait(&self) -> bool {
-        !(unsafe { ffi::fiber_cond_wait(self.inner) } < 0)
+        self.wait_impl(|| unsafe { ffi::fiber_cond_wait(self.inner) })
+    }
+
+    fn wait_impl(&self, wait: impl FnOnce() -> i32) -> bool {
+        let ticket = self.next_ticket.get();
+        self.next_ticket.set(ticket + 1);
+        self.waiters.borrow_mut().push_back(ticket);
+        let rc = wait();
+        // still in the queue if woken up by a timeout or spuriously
+        self.waiters.borrow_mut().retain(|&t| t != ticket);
+        !(rc < 0)
     }
 }
 
@@ -1326,7 +1564,9 @@ where
     {
         let closure: &mut F = &mut *(args.get::<*const c_void>() as *mut F);
         let boxed_arg = Box::from_raw(args.get::<*const c_void>() as *mut T);
-        (*closure)(boxed_arg)
+        // the panic must not unwind into the C code, it's reported to the
+        // panic hook instead
+        panic::catch(|| (*closure)(boxed_arg)).unwrap_or(-1)
     }
     (callback as *mut F as *mut c_void, Some(trampoline::<F, T>))
 }
@@ -11,6 +11,8 @@ use crate::{
     ffi::tarantool as ffi,
 };
 
+use super::select::{ChannelRef, Watchers};
+
 ////////////////////////////////////////////////////////////////////////////////
 // Channel
 ////////////////////////////////////////////////////////////////////////////////
@@ -23,15 +25,24 @@ impl<T> Channel<T> {
         let inner_raw = unsafe { ffi::fiber_channel_new(size) };
         let inner = NonNull::new(inner_raw)
             .expect("Memory allocation failure when creating fiber::Channel");
-        Self(Rc::new(ChannelBox { inner, marker: PhantomData }))
+        Self(Rc::new(ChannelBox {
+            inner,
+            watchers: Watchers::default(),
+            marker: PhantomData,
+        }))
     }
 
     fn as_ptr(&self) -> *mut ffi::fiber_channel {
         self.0.inner.as_ptr()
     }
 
+    pub(crate) fn select_ref(&self) -> ChannelRef<'_> {
+        ChannelRef { ptr: self.as_ptr(), watchers: &self.0.watchers }
+    }
+
     pub fn close(self) {
         unsafe { ffi::fiber_channel_close(self.as_ptr()) }
+        self.0.watchers.notify()
     }
 
     pub fn is_closed(&self) -> bool {
@@ -61,6 +72,9 @@ impl<T> Channel<T> {
 
 impl<T> SendTimeout<T> for Channel<T> {
     fn send_maybe_timeout(&self, t: T, timeout: Option<Duration>) -> Result<(), SendError<T>> {
+        // Let the fibers blocked in `Select` know that either a message or a
+        // sender is about to appear in the channel
+        self.0.watchers.notify();
         unsafe {
             let ipc_value_ptr = ffi::ipc_value_new();
             let ipc_value = &mut *ipc_value_ptr;
@@ -99,6 +113,9 @@ impl<T> SendTimeout<T> for Channel<T> {
 
 impl<T> RecvTimeout<T> for Channel<T> {
     fn recv_maybe_timeout(&self, timeout: Option<Duration>) -> Result<T, RecvError> {
+        // Let the fibers blocked in `Select` know that either a free slot or a
+        // receiver is about to appear in the channel
+        self.0.watchers.notify();
         unsafe {
             let mut ipc_msg_ptr_uninit = MaybeUninit::uninit();
             let ret_code = ffi::fiber_channel_get_msg_timeout(
@@ -329,6 +346,7 @@ impl<T> IntoIterator for Channel<T> {
 
 struct ChannelBox<T> {
     inner: NonNull<ffi::fiber_channel>,
+    watchers: Watchers,
     marker: PhantomData<T>,
 }
 
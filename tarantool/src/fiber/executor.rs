@@ -0,0 +1,143 @@
+//! Running [`Future`]s on fibers.
+//!
+//! A future is driven by polling it on the current fiber. When the future
+//! returns [`Poll::Pending`] the fiber is parked on a [`Cond`] until the
+//! future's [`Waker`] is woken, which lets other fibers run in the meantime.
+//!
+//! The wakers created here are bound to the thread of the fiber which created
+//! them. Using them from any other thread results in a panic.
+use std::{
+    cell::Cell,
+    future::Future,
+    mem::ManuallyDrop,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    thread::{self, ThreadId},
+};
+
+use super::{Cond, JoinHandle};
+
+/// Runs the future `f` to completion on the current fiber and returns its
+/// result.
+///
+/// The current fiber **yields** every time the future is not ready and is
+/// resumed once the future's waker is woken.
+///
+/// Example:
+/// ```no_run
+/// use tarantool::fiber;
+///
+/// let res = fiber::block_on(async { 69 });
+/// assert_eq!(res, 69);
+/// ```
+pub fn block_on<F: Future>(f: F) -> F::Output {
+    let mut f = f;
+    // Safety: `f` is shadowed and can never be moved after this point
+    let mut f = unsafe { Pin::new_unchecked(&mut f) };
+
+    let notify = Rc::new(Notify::new());
+    let waker = notify.clone().into_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(t) = f.as_mut().poll(&mut cx) {
+            return t
+        }
+        notify.park()
+    }
+}
+
+/// Creates a new fiber which runs the future `f` to completion and **yields**
+/// execution to it immediately, returning a [`JoinHandle`] for the new fiber.
+///
+/// The result of the future can be acquired using [`JoinHandle::join`].
+///
+/// See also: [`block_on`]
+pub fn spawn_async<F>(f: F) -> JoinHandle<F::Output>
+where
+    F: Future,
+    F: 'static,
+{
+    super::start(move || block_on(f))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Notify
+////////////////////////////////////////////////////////////////////////////////
+
+/// The state shared between the executor and the wakers of a single future.
+struct Notify {
+    cond: Cond,
+    woken: Cell<bool>,
+    thread: ThreadId,
+}
+
+impl Notify {
+    fn new() -> Self {
+        Self {
+            cond: Cond::new(),
+            woken: Cell::new(false),
+            thread: thread::current().id(),
+        }
+    }
+
+    /// Blocks the current fiber until the waker is woken. Returns immediately
+    /// if it was woken since the last call.
+    ///
+    /// Spurious wakeups (e.g. fiber cancellation) are possible, in which case
+    /// the future is just polled once more.
+    fn park(&self) {
+        if !self.woken.replace(false) {
+            self.cond.wait();
+            self.woken.set(false);
+        }
+    }
+
+    fn wake(&self) {
+        self.woken.set(true);
+        self.cond.signal()
+    }
+
+    fn into_waker(self: Rc<Self>) -> Waker {
+        let raw = RawWaker::new(Rc::into_raw(self).cast(), &VTABLE);
+        // Safety: the vtable functions check that the waker is used on the
+        // thread it was created on before touching the reference counter
+        unsafe { Waker::from_raw(raw) }
+    }
+
+    /// Reconstructs the `Rc` from the waker data without taking ownership.
+    ///
+    /// Panics if called not from the thread the waker was created on.
+    unsafe fn from_waker_data(data: *const ()) -> ManuallyDrop<Rc<Self>> {
+        let notify = ManuallyDrop::new(Rc::from_raw(data.cast::<Self>()));
+        assert!(
+            notify.thread == thread::current().id(),
+            "fiber waker used from a thread it wasn't created on"
+        );
+        notify
+    }
+}
+
+static VTABLE: RawWakerVTable = RawWakerVTable::new(
+    waker_clone, waker_wake, waker_wake_by_ref, waker_drop,
+);
+
+unsafe fn waker_clone(data: *const ()) -> RawWaker {
+    let notify = Notify::from_waker_data(data);
+    let clone = Rc::clone(&notify);
+    RawWaker::new(Rc::into_raw(clone).cast(), &VTABLE)
+}
+
+unsafe fn waker_wake(data: *const ()) {
+    let notify = ManuallyDrop::into_inner(Notify::from_waker_data(data));
+    notify.wake()
+}
+
+unsafe fn waker_wake_by_ref(data: *const ()) {
+    Notify::from_waker_data(data).wake()
+}
+
+unsafe fn waker_drop(data: *const ()) {
+    drop(ManuallyDrop::into_inner(Notify::from_waker_data(data)))
+}
@@ -0,0 +1,140 @@
+//! Catching panics at the fiber boundary.
+//!
+//! A panic unwinding out of a fiber function would cross the C trampoline
+//! which is undefined behavior, so every fiber function is run inside
+//! [`std::panic::catch_unwind`]. The caught panic is reported to the
+//! [panic hook](set_panic_hook) and then handed over to the join handle, so
+//! that it can be inspected via `try_join` or propagated to the joining fiber
+//! via `join`.
+use std::{
+    any::Any,
+    cell::RefCell,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::log::{self, SayLevel};
+
+////////////////////////////////////////////////////////////////////////////////
+// FiberPanic
+////////////////////////////////////////////////////////////////////////////////
+
+/// A panic caught in a fiber function.
+pub struct FiberPanic {
+    payload: Box<dyn Any + Send + 'static>,
+}
+
+impl FiberPanic {
+    fn new(payload: Box<dyn Any + Send + 'static>) -> Self {
+        Self { payload }
+    }
+
+    /// Returns the panic message if the panic was raised with a string
+    /// argument (which is the case for `panic!`, `assert!`, `unwrap`, etc.).
+    pub fn message(&self) -> Option<&str> {
+        if let Some(s) = self.payload.downcast_ref::<&'static str>() {
+            Some(s)
+        } else if let Some(s) = self.payload.downcast_ref::<String>() {
+            Some(s)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value the fiber function panicked with.
+    pub fn into_payload(self) -> Box<dyn Any + Send + 'static> {
+        self.payload
+    }
+
+    /// Continues unwinding the panic in the current fiber.
+    pub fn resume(self) -> ! {
+        panic::resume_unwind(self.payload)
+    }
+}
+
+impl fmt::Debug for FiberPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FiberPanic")
+            .field("message", &self.message())
+            .finish()
+    }
+}
+
+impl fmt::Display for FiberPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.message() {
+            Some(msg) => write!(f, "fiber panicked: {}", msg),
+            None => f.write_str("fiber panicked"),
+        }
+    }
+}
+
+impl std::error::Error for FiberPanic {}
+
+////////////////////////////////////////////////////////////////////////////////
+// panic hook
+////////////////////////////////////////////////////////////////////////////////
+
+type PanicHook = Box<dyn Fn(&FiberPanic)>;
+
+thread_local! {
+    static PANIC_HOOK: RefCell<Option<PanicHook>> = RefCell::new(None);
+}
+
+/// Registers a custom hook which is called every time a fiber function panics,
+/// replacing the previously registered one.
+///
+/// The hook is called in the panicked fiber before the panic is passed to the
+/// join handle. The default hook writes the panic message to the tarantool log
+/// with [`log::say`].
+///
+/// The hook is registered for the current thread only, which for most
+/// applications is the tx thread where all the fibers are running.
+pub fn set_panic_hook<F>(hook: F)
+where
+    F: Fn(&FiberPanic),
+    F: 'static,
+{
+    PANIC_HOOK.with(|h| h.replace(Some(Box::new(hook))));
+}
+
+/// Unregisters the current panic hook and returns it, restoring the default
+/// one.
+pub fn take_panic_hook() -> Box<dyn Fn(&FiberPanic)> {
+    PANIC_HOOK.with(|h| h.borrow_mut().take())
+        .unwrap_or_else(|| Box::new(default_hook))
+}
+
+fn default_hook(p: &FiberPanic) {
+    log::say(SayLevel::Error, file!(), line!() as _, None, &p.to_string())
+}
+
+/// Calls `f` catching any panics and reporting them to the panic hook.
+pub(crate) fn catch<F, T>(f: F) -> Result<T, FiberPanic>
+where
+    F: FnOnce() -> T,
+{
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let p = FiberPanic::new(payload);
+        // The hook is taken out while it's running, so that a panic in the
+        // hook (or a recursive call from it) doesn't find the cell borrowed
+        let hook = PANIC_HOOK.with(|h| h.borrow_mut().take());
+        let res = panic::catch_unwind(AssertUnwindSafe(|| match &hook {
+            Some(hook) => hook(&p),
+            None => default_hook(&p),
+        }));
+        if let Some(hook) = hook {
+            PANIC_HOOK.with(|h| {
+                let mut h = h.borrow_mut();
+                // the hook could have been replaced while running
+                if h.is_none() {
+                    *h = Some(hook)
+                }
+            })
+        }
+        if res.is_err() {
+            log::say(SayLevel::Error, file!(), line!() as _, None, "fiber panic hook panicked")
+        }
+        p
+    })
+}
@@ -0,0 +1,192 @@
+//! Scoped fibers which can borrow data from the parent fiber.
+use std::{
+    cell::RefCell,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+    time::Duration,
+};
+
+use super::{Builder, FiberFunc, FiberPanic, JoinHandle};
+
+/// Creates a scope for spawning fibers.
+///
+/// The function `f` is called with a [`Scope`] which can be used to spawn
+/// fibers. Unlike the fibers created with [`start`](super::start), the scoped
+/// fibers may borrow non-`'static` data from the outside of the scope.
+///
+/// When `f` returns (or panics) all the fibers spawned in the scope which
+/// haven't been joined yet are **cancelled** and then joined, so that none of
+/// them outlive the scope. Fibers must check [`is_cancelled`](super::is_cancelled)
+/// to stop in time. Panics of such fibers are only reported to the
+/// [panic hook](super::set_panic_hook).
+///
+/// Example:
+/// ```no_run
+/// use tarantool::fiber;
+///
+/// let mut data = vec![1, 2, 3];
+/// let mut count = 0;
+///
+/// fiber::scope(|s| {
+///     s.spawn(|| println!("data: {:?}", data));
+///     s.spawn(|| count += 1);
+/// });
+///
+/// data.push(4);
+/// assert_eq!(count, 1);
+/// ```
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+{
+    let scope = Scope {
+        children: RefCell::new(ManuallyDrop::new(vec![])),
+        scope: PhantomData,
+        env: PhantomData,
+    };
+    let res = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+    scope.cancel_and_join();
+    match res {
+        Ok(t) => t,
+        Err(payload) => panic::resume_unwind(payload),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Scope
+////////////////////////////////////////////////////////////////////////////////
+
+/// A scope to spawn scoped fibers in. See [`scope`] for details.
+pub struct Scope<'scope, 'env: 'scope> {
+    // The children are always joined and removed before the scope is dropped,
+    // so there's nothing to drop here. `ManuallyDrop` is needed to convince
+    // the drop checker that the scope doesn't outlive the borrowed data.
+    children: RefCell<ManuallyDrop<Vec<Rc<dyn Child + 'scope>>>>,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Creates a new fiber within the scope and **yields** execution to it
+    /// immediately, returning a [`ScopedJoinHandle`] for it.
+    ///
+    /// The fiber is joined automatically at the end of the scope, if it wasn't
+    /// joined explicitly.
+    pub fn spawn<F, T>(&'scope self, f: F) -> ScopedJoinHandle<'scope, T>
+    where
+        F: FnOnce() -> T,
+        F: 'scope,
+        T: 'scope,
+    {
+        let f = FiberFunc { f: Box::new(f), state: Default::default() };
+        let jh = Builder { name: None, attr: None, f }.start().unwrap();
+        let inner = Rc::new(RefCell::new(Some(jh)));
+        self.children.borrow_mut().push(inner.clone());
+        ScopedJoinHandle { inner, marker: PhantomData }
+    }
+
+    fn cancel_and_join(&self) {
+        // Children can spawn new fibers into the scope while they're being
+        // joined, so repeat until there's none left
+        loop {
+            let children = std::mem::take(&mut **self.children.borrow_mut());
+            if children.is_empty() {
+                break
+            }
+            for child in &children {
+                child.cancel()
+            }
+            for child in &children {
+                child.join()
+            }
+        }
+    }
+}
+
+trait Child {
+    fn cancel(&self);
+    fn join(&self);
+}
+
+impl<T> Child for RefCell<Option<JoinHandle<T>>> {
+    fn cancel(&self) {
+        if let Some(jh) = &*self.borrow() {
+            jh.cancel()
+        }
+    }
+
+    fn join(&self) {
+        let jh = self.borrow_mut().take();
+        if let Some(jh) = jh {
+            // The panic has already been reported to the panic hook
+            let _ = jh.try_join();
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// ScopedJoinHandle
+////////////////////////////////////////////////////////////////////////////////
+
+/// A permission to join on a scoped fiber. Unlike [`JoinHandle`] it can be
+/// dropped without joining, in which case the fiber is joined at the end of
+/// the scope.
+pub struct ScopedJoinHandle<'scope, T> {
+    inner: Rc<RefCell<Option<JoinHandle<T>>>>,
+    marker: PhantomData<&'scope ()>,
+}
+
+impl<T> ScopedJoinHandle<'_, T> {
+    fn take(&self) -> JoinHandle<T> {
+        self.inner.borrow_mut()
+            .take()
+            .expect("scoped fiber can only be joined once")
+    }
+
+    /// See [`JoinHandle::join`].
+    pub fn join(self) -> T {
+        self.take().join()
+    }
+
+    /// See [`JoinHandle::try_join`].
+    pub fn try_join(self) -> Result<T, FiberPanic> {
+        self.take().try_join()
+    }
+
+    /// See [`JoinHandle::join_timeout`].
+    pub fn join_timeout(self, timeout: Duration) -> Result<T, Self> {
+        match self.take().join_timeout(timeout) {
+            Ok(t) => Ok(t),
+            Err(jh) => {
+                self.inner.replace(Some(jh));
+                Err(self)
+            }
+        }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&JoinHandle<T>) -> R) -> R {
+        f(self.inner.borrow().as_ref().expect("scoped fiber can only be joined once"))
+    }
+
+    /// See [`JoinHandle::is_finished`].
+    pub fn is_finished(&self) -> bool {
+        self.with(JoinHandle::is_finished)
+    }
+
+    /// See [`JoinHandle::cancel`].
+    pub fn cancel(&self) {
+        self.with(JoinHandle::cancel)
+    }
+
+    /// See [`JoinHandle::is_cancelled`].
+    pub fn is_cancelled(&self) -> bool {
+        self.with(JoinHandle::is_cancelled)
+    }
+
+    /// See [`JoinHandle::wakeup`].
+    pub fn wakeup(&self) {
+        self.with(JoinHandle::wakeup)
+    }
+}
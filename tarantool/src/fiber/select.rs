@@ -0,0 +1,387 @@
+use std::{
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+    os::raw::c_void,
+    rc::{Rc, Weak},
+    time::{Duration, Instant},
+};
+
+use crate::ffi::tarantool as ffi;
+
+use super::{Channel, Cond};
+
+////////////////////////////////////////////////////////////////////////////////
+// Select
+////////////////////////////////////////////////////////////////////////////////
+
+/// Waits on a set of channel operations and [`Cond`]s at once.
+///
+/// Operations are registered with [`recv`], [`send`] and [`cond`] methods,
+/// each of which returns the index of the operation. Then one of the `select*`
+/// methods is used to block the current fiber until one of the operations
+/// becomes ready. The returned [`SelectedOperation`] reports the index of
+/// the operation which is ready and is used to complete it.
+///
+/// If several operations are ready at the same time, the one registered first
+/// is selected. The exception is a cond whose signal was delivered to the
+/// select, it's always selected so that the signal isn't lost.
+///
+/// Example:
+/// ```no_run
+/// use tarantool::fiber::{Channel, Cond, Select};
+///
+/// let work: Channel<u32> = Channel::new(16);
+/// let shutdown = Cond::new();
+///
+/// loop {
+///     let mut sel = Select::new();
+///     let work_op = sel.recv(&work);
+///     let shutdown_op = sel.cond(&shutdown);
+///     let oper = sel.select().unwrap();
+///     match oper.index() {
+///         i if i == work_op => {
+///             let job = oper.recv(&work);
+///             // process the job
+///         }
+///         i if i == shutdown_op => break,
+///         _ => unreachable!(),
+///     }
+/// }
+/// ```
+///
+/// [`recv`]: Select::recv
+/// [`send`]: Select::send
+/// [`cond`]: Select::cond
+#[derive(Default)]
+pub struct Select<'a> {
+    handles: Vec<Handle<'a>>,
+}
+
+enum Handle<'a> {
+    Recv(ChannelRef<'a>),
+    Send(ChannelRef<'a>),
+    Cond(&'a Cond),
+}
+
+impl<'a> Select<'a> {
+    /// Creates an empty list of operations to select from.
+    pub fn new() -> Self {
+        Self { handles: vec![] }
+    }
+
+    /// Adds a receive operation on the channel `ch`.
+    ///
+    /// The operation is ready when there is a message in the channel buffer,
+    /// a sender is blocked on the channel or the channel is closed.
+    ///
+    /// Returns the index of the added operation.
+    pub fn recv<T>(&mut self, ch: &'a Channel<T>) -> usize {
+        self.push(Handle::Recv(ch.select_ref()))
+    }
+
+    /// Adds a send operation on the channel `ch`.
+    ///
+    /// The operation is ready when there is free space in the channel buffer,
+    /// a receiver is blocked on the channel or the channel is closed.
+    ///
+    /// Returns the index of the added operation.
+    pub fn send<T>(&mut self, ch: &'a Channel<T>) -> usize {
+        self.push(Handle::Send(ch.select_ref()))
+    }
+
+    /// Adds a wait operation on the cond `cond`.
+    ///
+    /// The operation is ready when [`Cond::signal`] or [`Cond::broadcast`] is
+    /// called while the current fiber is blocked in one of the `select*`
+    /// methods. Signals sent before the call are not remembered.
+    ///
+    /// Returns the index of the added operation.
+    pub fn cond(&mut self, cond: &'a Cond) -> usize {
+        self.push(Handle::Cond(cond))
+    }
+
+    fn push(&mut self, handle: Handle<'a>) -> usize {
+        self.handles.push(handle);
+        self.handles.len() - 1
+    }
+
+    /// Blocks until one of the operations becomes ready.
+    ///
+    /// Returns `None` if the current fiber was cancelled while waiting.
+    ///
+    /// This function may perform a **yield** in case none of the operations is
+    /// ready.
+    pub fn select(&self) -> Option<SelectedOperation<'a>> {
+        match self.select_maybe_timeout(None) {
+            Err(SelectError::Timeout) => {
+                unreachable!("100 years have passed, wake up!")
+            }
+            res => res.ok(),
+        }
+    }
+
+    /// Blocks until one of the operations becomes ready or the `timeout`
+    /// expires.
+    pub fn select_timeout(&self, timeout: Duration) -> Result<SelectedOperation<'a>, SelectError> {
+        self.select_maybe_timeout(Some(timeout))
+    }
+
+    /// Checks if any of the operations is ready without blocking.
+    ///
+    /// Operations on conds are never ready when using this method.
+    pub fn try_select(&self) -> Option<SelectedOperation<'a>> {
+        self.ready_index(None).map(|index| self.selected(index))
+    }
+
+    /// Blocks until one of the operations becomes ready or the `timeout`
+    /// expires. If `timeout` is `None` waits indefinitely.
+    ///
+    /// Returns [`SelectError::Cancelled`] if the current fiber was cancelled
+    /// while waiting.
+    ///
+    /// This function may perform a **yield** in case none of the operations is
+    /// ready.
+    pub fn select_maybe_timeout(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<SelectedOperation<'a>, SelectError> {
+        if let Some(index) = self.ready_index(None) {
+            return Ok(self.selected(index))
+        }
+
+        let deadline = timeout.map(|t| Instant::now() + t);
+        let waker = Rc::new(Waker::new());
+        let _guard = self.watch(&waker);
+
+        loop {
+            let timeout = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(SelectError::Timeout)
+                    }
+                    deadline - now
+                }
+                None => Duration::from_secs_f64(unsafe { ffi::TIMEOUT_INFINITY }),
+            };
+
+            waker.cond.wait_timeout(timeout);
+
+            if let Some(index) = self.ready_index(Some(&waker)) {
+                return Ok(self.selected(index))
+            }
+
+            if super::is_cancelled() {
+                return Err(SelectError::Cancelled)
+            }
+        }
+    }
+
+    fn ready_index(&self, waker: Option<&Waker>) -> Option<usize> {
+        let fired = waker.and_then(|w| w.fired.get());
+        if fired.is_some() {
+            // the signal of the cond is consumed
+            return fired
+        }
+        self.handles.iter()
+            .position(|handle| match handle {
+                Handle::Recv(ch) => ch.is_recv_ready(),
+                Handle::Send(ch) => ch.is_send_ready(),
+                Handle::Cond(_) => false,
+            })
+    }
+
+    fn selected(&self, index: usize) -> SelectedOperation<'a> {
+        let ptr = match &self.handles[index] {
+            Handle::Recv(ch) | Handle::Send(ch) => ch.ptr.cast(),
+            Handle::Cond(cond) => cond.inner.cast(),
+        };
+        SelectedOperation { index, ptr, marker: PhantomData }
+    }
+
+    fn watch<'s>(&'s self, waker: &Rc<Waker>) -> WatchGuard<'s, 'a> {
+        for (index, handle) in self.handles.iter().enumerate() {
+            handle.watchers().add(waker, index)
+        }
+        WatchGuard { select: self, waker: waker.clone() }
+    }
+}
+
+impl Handle<'_> {
+    fn watchers(&self) -> &Watchers {
+        match self {
+            Self::Recv(ch) | Self::Send(ch) => ch.watchers,
+            Self::Cond(cond) => &cond.watchers,
+        }
+    }
+}
+
+struct WatchGuard<'s, 'a> {
+    select: &'s Select<'a>,
+    waker: Rc<Waker>,
+}
+
+impl Drop for WatchGuard<'_, '_> {
+    fn drop(&mut self) {
+        for handle in &self.select.handles {
+            handle.watchers().remove(&self.waker)
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// SelectedOperation
+////////////////////////////////////////////////////////////////////////////////
+
+/// An operation which is ready, returned by one of the [`Select`] methods.
+///
+/// The operation is guaranteed to complete without blocking as long as the
+/// current fiber doesn't **yield** between the selection and the completion.
+pub struct SelectedOperation<'a> {
+    index: usize,
+    ptr: *const c_void,
+    marker: PhantomData<&'a ()>,
+}
+
+impl SelectedOperation<'_> {
+    /// Returns the index of the selected operation.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Completes the receive operation on the channel `ch`.
+    ///
+    /// Returns `None` if the channel was closed.
+    ///
+    /// Panics if `ch` is not the channel passed to [`Select::recv`] for the
+    /// selected operation.
+    pub fn recv<T>(self, ch: &Channel<T>) -> Option<T> {
+        assert!(
+            self.ptr == ch.select_ref().ptr.cast(),
+            "passed a channel that doesn't match the selected operation"
+        );
+        ch.try_recv().ok()
+    }
+
+    /// Completes the send operation on the channel `ch`.
+    ///
+    /// In case the channel was closed the original message is returned back.
+    ///
+    /// Panics if `ch` is not the channel passed to [`Select::send`] for the
+    /// selected operation.
+    pub fn send<T>(self, ch: &Channel<T>, t: T) -> Result<(), T> {
+        assert!(
+            self.ptr == ch.select_ref().ptr.cast(),
+            "passed a channel that doesn't match the selected operation"
+        );
+        ch.try_send(t).map_err(|e| e.into_inner())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SelectError {
+    Timeout,
+    Cancelled,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// ChannelRef
+////////////////////////////////////////////////////////////////////////////////
+
+/// Type erased reference to a [`Channel`] used for checking if it's ready for
+/// sending or receiving.
+pub(crate) struct ChannelRef<'a> {
+    pub(crate) ptr: *mut ffi::fiber_channel,
+    pub(crate) watchers: &'a Watchers,
+}
+
+impl ChannelRef<'_> {
+    fn is_recv_ready(&self) -> bool {
+        unsafe {
+            ffi::fiber_channel_is_closed(self.ptr)
+                || !ffi::fiber_channel_is_empty(self.ptr)
+                || ffi::fiber_channel_has_writers(self.ptr)
+        }
+    }
+
+    fn is_send_ready(&self) -> bool {
+        unsafe {
+            ffi::fiber_channel_is_closed(self.ptr)
+                || ffi::fiber_channel_count(self.ptr) < ffi::fiber_channel_size(self.ptr)
+                || ffi::fiber_channel_has_readers(self.ptr)
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Watchers
+////////////////////////////////////////////////////////////////////////////////
+
+/// Fibers blocked in [`Select`] calls which are interested in the state
+/// changes of a channel or a cond.
+#[derive(Default)]
+pub(crate) struct Watchers(RefCell<Vec<(Weak<Waker>, usize)>>);
+
+impl Watchers {
+    fn add(&self, waker: &Rc<Waker>, index: usize) {
+        self.0.borrow_mut().push((Rc::downgrade(waker), index))
+    }
+
+    fn remove(&self, waker: &Rc<Waker>) {
+        let waker = Rc::downgrade(waker);
+        self.0.borrow_mut()
+            .retain(|(w, _)| w.strong_count() > 0 && !w.ptr_eq(&waker))
+    }
+
+    /// Wake up all the watchers so that they recheck the state of the object.
+    /// Does not yield.
+    pub(crate) fn notify(&self) {
+        for (waker, _) in self.0.borrow().iter() {
+            if let Some(waker) = waker.upgrade() {
+                waker.cond.signal()
+            }
+        }
+    }
+
+    /// Mark the operation as ready for the first of the watchers which
+    /// wasn't fired yet and wake it up. Does not yield.
+    pub(crate) fn fire_one(&self) {
+        let mut watchers = self.0.borrow_mut();
+        let first = watchers.iter()
+            .position(|(w, _)| w.upgrade().map_or(false, |w| w.fired.get().is_none()));
+        if let Some(i) = first {
+            let (waker, index) = watchers.remove(i);
+            if let Some(waker) = waker.upgrade() {
+                waker.fire(index)
+            }
+        }
+    }
+
+    /// Mark the operation as ready for all of the watchers and wake them up.
+    /// Does not yield.
+    pub(crate) fn fire_all(&self) {
+        for (waker, index) in self.0.borrow_mut().drain(..) {
+            if let Some(waker) = waker.upgrade() {
+                waker.fire(index)
+            }
+        }
+    }
+}
+
+pub(crate) struct Waker {
+    cond: Cond,
+    fired: Cell<Option<usize>>,
+}
+
+impl Waker {
+    fn new() -> Self {
+        Self { cond: Cond::new(), fired: Cell::new(None) }
+    }
+
+    fn fire(&self, index: usize) {
+        if self.fired.get().is_none() {
+            self.fired.set(Some(index))
+        }
+        self.cond.signal()
+    }
+}
@@ -0,0 +1,199 @@
+//! Delayed and periodic jobs running in background fibers.
+//!
+//! Both [`Timer`] and [`Interval`] start a fiber which manages itself: it is
+//! never joined and is recycled as soon as the job is done or cancelled.
+//! Dropping the handle does **not** cancel the job, so it can be simply
+//! ignored for jobs which should run for the whole lifetime of the instance.
+//!
+//! Example:
+//! ```no_run
+//! use std::time::Duration;
+//! use tarantool::fiber::Interval;
+//!
+//! let flush = Interval::every(Duration::from_secs(10), || {
+//!     // flush the metrics
+//! });
+//!
+//! // some time later
+//! flush.cancel();
+//! ```
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::clock;
+
+use super::{Builder, Cond};
+
+////////////////////////////////////////////////////////////////////////////////
+// Timer
+////////////////////////////////////////////////////////////////////////////////
+
+/// A handle to a function scheduled to run once after a timeout.
+///
+/// Created with [`Timer::after`].
+pub struct Timer {
+    state: Rc<State>,
+}
+
+impl Timer {
+    /// Schedules the function `f` to be called in a new fiber after `timeout`
+    /// passes.
+    ///
+    /// Returns a handle which can be used to cancel the call.
+    pub fn after<F>(timeout: Duration, f: F) -> Self
+    where
+        F: FnOnce(),
+        F: 'static,
+    {
+        let deadline = clock::monotonic() + timeout.as_secs_f64();
+        let state = Rc::new(State::new());
+        {
+            let state = state.clone();
+            spawn("timer", move || {
+                if state.wait_until(deadline) {
+                    f()
+                }
+                state.is_active.set(false)
+            })
+        }
+        Self { state }
+    }
+
+    /// Cancels the call if it hasn't happened yet. Does nothing otherwise.
+    /// Does not yield.
+    pub fn cancel(&self) {
+        self.state.cancel()
+    }
+
+    /// Returns `true` if the function is still waiting to be called or is
+    /// being called right now.
+    pub fn is_active(&self) -> bool {
+        self.state.is_active.get()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Interval
+////////////////////////////////////////////////////////////////////////////////
+
+/// A handle to a function called periodically.
+///
+/// Created with [`Interval::every`].
+pub struct Interval {
+    state: Rc<State>,
+}
+
+impl Interval {
+    /// Schedules the function `f` to be called in a new fiber every `period`,
+    /// starting one `period` from now.
+    ///
+    /// The calls are scheduled relative to the start time using
+    /// [`clock::monotonic`], so the time spent in `f` doesn't accumulate as a
+    /// drift. If a call takes longer than `period`, the missed calls are
+    /// skipped and the next one happens at the next scheduled point.
+    ///
+    /// Returns a handle which can be used to stop the calls.
+    ///
+    /// Panics if `period` is zero.
+    pub fn every<F>(period: Duration, f: F) -> Self
+    where
+        F: FnMut(),
+        F: 'static,
+    {
+        assert!(period > Duration::from_secs(0), "Interval period must be non-zero");
+        let mut f = f;
+        let period = period.as_secs_f64();
+        let start = clock::monotonic();
+        let state = Rc::new(State::new());
+        {
+            let state = state.clone();
+            spawn("interval", move || {
+                let mut ticks = 1.0;
+                while state.wait_until(start + ticks * period) {
+                    f();
+                    let elapsed = clock::monotonic() - start;
+                    ticks = (elapsed / period).floor() + 1.0;
+                }
+                state.is_active.set(false)
+            })
+        }
+        Self { state }
+    }
+
+    /// Stops the calls. If called from inside the function, the current call
+    /// is the last one. Does not yield.
+    pub fn cancel(&self) {
+        self.state.cancel()
+    }
+
+    /// Returns `true` if the calls haven't been stopped yet.
+    pub fn is_active(&self) -> bool {
+        self.state.is_active.get()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// State
+////////////////////////////////////////////////////////////////////////////////
+
+/// The state shared between the handle and the fiber of a timer.
+struct State {
+    cond: Cond,
+    is_cancelled: Cell<bool>,
+    is_active: Cell<bool>,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            cond: Cond::new(),
+            is_cancelled: Cell::new(false),
+            is_active: Cell::new(true),
+        }
+    }
+
+    fn cancel(&self) {
+        self.is_cancelled.set(true);
+        self.cond.signal()
+    }
+
+    /// Blocks the current fiber until the `deadline` (in terms of
+    /// [`clock::monotonic`]) is reached.
+    ///
+    /// Returns `false` if the timer was cancelled either via the handle or by
+    /// cancelling the fiber itself.
+    fn wait_until(&self, deadline: f64) -> bool {
+        loop {
+            if self.is_cancelled.get() || super::is_cancelled() {
+                return false
+            }
+            let now = clock::monotonic();
+            if now >= deadline {
+                return true
+            }
+            self.cond.wait_timeout(Duration::from_secs_f64(deadline - now));
+        }
+    }
+}
+
+/// Starts a fiber which is recycled as soon as `f` returns.
+fn spawn<F>(name: &str, f: F)
+where
+    F: FnOnce(),
+    F: 'static,
+{
+    Builder::new()
+        .name(name)
+        .proc(move || {
+            // The fiber must not finish before it's detached, otherwise it will
+            // never be recycled
+            super::reschedule();
+            f()
+        })
+        .start()
+        .unwrap()
+        .detach()
+}
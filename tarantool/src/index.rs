@@ -7,16 +7,22 @@
 //! See also:
 //! - [Indexes](https://www.tarantool.io/en/doc/latest/book/box/data_model/#indexes)
 //! - [Lua reference: Submodule box.index](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_index/)
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
 use std::os::raw::c_char;
 use std::ptr::null_mut;
 use std::mem::MaybeUninit;
 
 use num_traits::ToPrimitive;
+use serde::{de::DeserializeOwned, Deserialize};
 
-use crate::error::{Error, TarantoolError};
+use crate::error::{Error, TarantoolError, TarantoolErrorCode};
 use crate::ffi::tarantool as ffi;
-use crate::tuple::{AsTuple, Tuple, TupleBuffer};
+use crate::space::{with_schema_cache, Space, SystemSpace};
+use crate::tuple::{AsTuple, KeyDef, Tuple, TupleBuffer};
 use crate::tuple_from_box_api;
+use crate::util::from_schema_name;
 
 /// An index is a group of key values and pointers.
 pub struct Index {
@@ -100,8 +106,9 @@ pub struct IndexOptions {
     pub run_size_ratio: Option<f32>,
     pub sequence: Option<IndexSequenceOption>,
     pub func: Option<String>,
-    // Only for Tarantool >= 2.6
-    // pub hint: Option<bool>,
+    /// Only for memtx tree indexes. Ignored if the running tarantool doesn't
+    /// support [Feature::IndexHint](crate::version::Feature::IndexHint).
+    pub hint: Option<bool>,
 }
 
 impl Default for IndexOptions {
@@ -127,16 +134,38 @@ impl Default for IndexOptions {
             run_size_ratio: Some(3.5),
             sequence: None,
             func: None,
-            // Only for Tarantool >= 2.6
-            // hint: Some(true),
+            hint: None,
         }
     }
 }
 
+/// Options for [Index::alter](struct.Index.html#method.alter). Only the
+/// specified options are changed.
+#[derive(Default)]
+pub struct IndexAlterOptions {
+    /// New name of the index.
+    pub name: Option<String>,
+    pub index_type: Option<IndexType>,
+    pub unique: Option<bool>,
+    pub parts: Option<Vec<IndexPart>>,
+    pub dimension: Option<u32>,
+    pub distance: Option<RtreeIndexDistanceType>,
+    pub bloom_fpr: Option<f32>,
+    pub page_size: Option<u32>,
+    pub range_size: Option<u32>,
+    pub run_count_per_level: Option<u32>,
+    pub run_size_ratio: Option<f32>,
+    pub sequence: Option<IndexSequenceOption>,
+    pub func: Option<String>,
+    /// Only for memtx tree indexes. Ignored if the running tarantool doesn't
+    /// support [Feature::IndexHint](crate::version::Feature::IndexHint).
+    pub hint: Option<bool>,
+}
+
 /// Sequence option for new or updated index.
 ///
 /// For details see [specifying a sequence in create_index](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema_sequence/create_index/#box-schema-sequence-create-index).
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub enum IndexSequenceOption {
     SeqId {
         seq_id: u32,
@@ -151,7 +180,8 @@ pub enum IndexSequenceOption {
 }
 
 /// Type of index.
-#[derive(Copy, Clone, Debug, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum IndexType {
     Hash,
     Tree,
@@ -160,9 +190,12 @@ pub enum IndexType {
 }
 
 /// Type of index part.
-#[derive(Copy, Clone, Debug, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum IndexFieldType {
+    #[serde(alias = "num")]
     Unsigned,
+    #[serde(alias = "str")]
     String,
     Integer,
     Number,
@@ -176,7 +209,7 @@ pub enum IndexFieldType {
 }
 
 /// Index part.
-#[derive(Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct IndexPart {
     pub field_index: u32,
     pub field_type: IndexFieldType,
@@ -197,6 +230,115 @@ impl IndexPart {
     }
 }
 
+/// Index metadata, read from the `_vindex` system space.
+#[derive(Clone, Debug)]
+pub struct IndexMetadata {
+    pub space_id: u32,
+    pub index_id: u32,
+    pub name: String,
+    pub index_type: IndexType,
+    pub unique: bool,
+    /// Index parts. The field numbers are 1-based, the same as in
+    /// [IndexOptions](struct.IndexOptions.html).
+    pub parts: Vec<IndexPart>,
+    /// Id of the function of a functional index.
+    pub func: Option<u32>,
+}
+
+/// A tuple in `_index` system space.
+type IndexDef = (u32, u32, String, String, IndexOptsDef, Vec<IndexPartDef>);
+
+#[derive(Deserialize)]
+struct IndexOptsDef {
+    #[serde(default = "default_unique")]
+    unique: bool,
+    #[serde(default)]
+    func: Option<u32>,
+}
+
+fn default_unique() -> bool {
+    true
+}
+
+/// Index part in `_index` system space. The field numbers are 0-based.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum IndexPartDef {
+    Map {
+        field: u32,
+        #[serde(rename = "type")]
+        field_type: String,
+        #[serde(default)]
+        is_nullable: bool,
+        #[serde(default)]
+        path: Option<String>,
+        /// Id of the collation in `_collation` system space.
+        #[serde(default)]
+        collation: Option<u32>,
+    },
+    /// Used in the older versions.
+    Array(u32, String),
+}
+
+impl IndexMetadata {
+    fn fetch(space_id: u32, index_id: u32) -> Result<Self, Error> {
+        let sys_index: Space = SystemSpace::VIndex.into();
+        let tuple = match sys_index.get(&(space_id, index_id))? {
+            Some(tuple) => tuple,
+            None => {
+                set_error!(
+                    TarantoolErrorCode::NoSuchIndexID,
+                    "No index #{} is defined in space #{}", index_id, space_id
+                );
+                return Err(TarantoolError::last().into());
+            }
+        };
+        let (space_id, index_id, name, index_type, opts, parts): IndexDef = tuple.decode()?;
+        let index_type = from_schema_name(&index_type)
+            .ok_or_else(|| unknown_type_error("index type", &index_type))?;
+
+        let sys_collation: Space = SystemSpace::VCollation.into();
+        let mut res_parts = Vec::with_capacity(parts.len());
+        for part in parts {
+            let (field, field_type, is_nullable, path, collation) = match part {
+                IndexPartDef::Map { field, field_type, is_nullable, path, collation } => {
+                    (field, field_type, is_nullable, path, collation)
+                }
+                IndexPartDef::Array(field, field_type) => (field, field_type, false, None, None),
+            };
+            let collation = match collation {
+                Some(id) => sys_collation.get(&(id,))?
+                    .map(|t| t.field::<String>(1))
+                    .transpose()?
+                    .flatten(),
+                None => None,
+            };
+            res_parts.push(IndexPart {
+                field_index: field + 1,
+                field_type: from_schema_name(&field_type)
+                    .ok_or_else(|| unknown_type_error("field type", &field_type))?,
+                collation,
+                is_nullable: Some(is_nullable),
+                path,
+            });
+        }
+
+        Ok(IndexMetadata {
+            space_id,
+            index_id,
+            name,
+            index_type,
+            unique: opts.unique,
+            parts: res_parts,
+            func: opts.func,
+        })
+    }
+}
+
+fn unknown_type_error(what: &str, name: &str) -> Error {
+    rmp_serde::decode::Error::Syntax(format!("unknown {} '{}'", what, name)).into()
+}
+
 /// Type of distance for retree index.
 #[derive(Copy, Clone, Debug, Serialize)]
 pub enum RtreeIndexDistanceType {
@@ -209,12 +351,72 @@ impl Index {
         Index { space_id, index_id }
     }
 
+    /// Returns the id of the index.
+    pub fn id(&self) -> u32 {
+        self.index_id
+    }
+
+    /// Returns the id of the space the index belongs to.
+    pub fn space_id(&self) -> u32 {
+        self.space_id
+    }
+
+    /// Returns the index metadata.
+    ///
+    /// The metadata is cached until the next schema change, so only the first
+    /// call reads the `_vindex` system space.
+    pub fn meta(&self) -> Result<IndexMetadata, Error> {
+        let key = (self.space_id, self.index_id);
+        if let Some(meta) = with_schema_cache(|c| c.indexes.get(&key).cloned()) {
+            return Ok(meta);
+        }
+        let meta = IndexMetadata::fetch(self.space_id, self.index_id)?;
+        with_schema_cache(|c| c.indexes.insert(key, meta.clone()));
+        Ok(meta)
+    }
+
+    /// Returns the name of the index.
+    pub fn name(&self) -> Result<String, Error> {
+        Ok(self.meta()?.name)
+    }
+
+    /// Returns the parts of the index. The field numbers are 1-based.
+    pub fn parts(&self) -> Result<Vec<IndexPart>, Error> {
+        Ok(self.meta()?.parts)
+    }
+
+    /// Returns `true` if the index is unique.
+    pub fn unique(&self) -> Result<bool, Error> {
+        Ok(self.meta()?.unique)
+    }
+
+    /// Returns the type of the index.
+    pub fn index_type(&self) -> Result<IndexType, Error> {
+        Ok(self.meta()?.index_type)
+    }
+
     // Drops index.
     #[cfg(feature = "schema")]
     pub fn drop(&self) -> Result<(), Error> {
         crate::schema::index::drop_index(self.space_id, self.index_id)
     }
 
+    /// Alter the index, e.g. change its parts, uniqueness or type. The data is
+    /// rebuilt if needed.
+    /// (for details see [index_object:alter()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_index/alter/)).
+    ///
+    /// Only the options which are `Some` are changed.
+    #[cfg(feature = "schema")]
+    pub fn alter(&self, opts: &IndexAlterOptions) -> Result<(), Error> {
+        crate::schema::index::alter_index(self.space_id, self.index_id, opts)
+    }
+
+    /// Rename the index.
+    #[cfg(feature = "schema")]
+    pub fn rename(&self, name: &str) -> Result<(), Error> {
+        self.alter(&IndexAlterOptions { name: Some(name.into()), ..Default::default() })
+    }
+
     /// Get a tuple from index by the key.
     ///
     /// Please note that this function works much more faster than [select](#method.select)
@@ -251,6 +453,14 @@ impl Index {
         K: AsTuple,
     {
         let key_buf = key.serialize_as_tuple().unwrap();
+        self.select_by_key_data(iterator_type, key_buf)
+    }
+
+    fn select_by_key_data(
+        &self,
+        iterator_type: IteratorType,
+        key_buf: TupleBuffer,
+    ) -> Result<IndexIterator, Error> {
         let key_buf_ptr = key_buf.as_ptr() as *const c_char;
 
         let ptr = unsafe {
@@ -273,6 +483,64 @@ impl Index {
         })
     }
 
+    /// Iterate over the tuples with the keys within `range` in the index
+    /// order.
+    ///
+    /// The bounds are keys in MsgPack Array format (`[part1, part2, ...]`),
+    /// which may be partial, in which case only the specified parts are
+    /// compared, e.g. `(1,)..=(1,)` includes all the keys starting with `1`.
+    /// Returns an error if a bound doesn't match the key definition of the
+    /// index, e.g. has a key part of a wrong type.
+    ///
+    /// Use [`IndexRange::rev`] to iterate in the descending order and
+    /// [`IndexRange::decode`] to deserialize the tuples.
+    ///
+    /// Example:
+    /// ```no_run
+    /// use tarantool::space::Space;
+    ///
+    /// let space = Space::find("users").unwrap();
+    /// let index = space.index("age").unwrap();
+    /// for tuple in index.range((18,)..(65,)).unwrap() {
+    ///     println!("{:?}", tuple.field::<String>(1));
+    /// }
+    /// let oldest = index.range((65,)..).unwrap()
+    ///     .rev()
+    ///     .decode::<(u32, String, u32)>()
+    ///     .next();
+    /// ```
+    pub fn range<K, R>(&self, range: R) -> Result<IndexRange, Error>
+    where
+        K: AsTuple,
+        R: RangeBounds<K>,
+    {
+        fn encode<K: AsTuple>(key_def: &KeyDef, bound: Bound<&K>) -> Result<Bound<Vec<u8>>, Error> {
+            let encode = |key: &K| -> Result<Vec<u8>, Error> {
+                let buf = key.serialize_as_tuple()?;
+                let data = unsafe { std::slice::from_raw_parts(buf.as_ptr(), buf.len()) }.to_vec();
+                key_def.validate_key(&data)?;
+                Ok(data)
+            };
+            Ok(match bound {
+                Bound::Included(key) => Bound::Included(encode(key)?),
+                Bound::Excluded(key) => Bound::Excluded(encode(key)?),
+                Bound::Unbounded => Bound::Unbounded,
+            })
+        }
+
+        let key_def = KeyDef::from_index(self)?;
+        Ok(IndexRange {
+            index: Index::new(self.space_id, self.index_id),
+            lower: encode(&key_def, range.start_bound())?,
+            upper: encode(&key_def, range.end_bound())?,
+            key_def,
+            reverse: false,
+            is_started: false,
+            iter: None,
+            marker: PhantomData,
+        })
+    }
+
     /// Delete a tuple identified by a key.
     ///
     /// Same as [space.delete()](../space/struct.Space.html#method.delete), but key is searched in this index instead
@@ -305,14 +573,15 @@ impl Index {
     ///
     /// - `key` - encoded key in MsgPack Array format (`[part1, part2, ...]`).
     /// - `ops` - encoded operations in MsgPack array format, e.g. `[['=', field_id, value], ['!', 2, 'xxx']]`
+    ///   (see [UpdateOps](../update/struct.UpdateOps.html) for a typed way to build them)
     ///
     /// Returns a new tuple.
     ///
     /// See also: [index.upsert()](#method.upsert)
-    pub fn update<K, Op>(&mut self, key: &K, ops: &Vec<Op>) -> Result<Option<Tuple>, Error>
+    pub fn update<K, Ops>(&mut self, key: &K, ops: &Ops) -> Result<Option<Tuple>, Error>
     where
         K: AsTuple,
-        Op: AsTuple,
+        Ops: AsTuple,
     {
         let key_buf = key.serialize_as_tuple().unwrap();
         let key_buf_ptr = key_buf.as_ptr() as *const c_char;
@@ -338,14 +607,15 @@ impl Index {
     ///
     /// - `value` - encoded tuple in MsgPack Array format (`[field1, field2, ...]`)
     /// - `ops` - encoded operations in MsgPack array format, e.g. `[['=', field_id, value], ['!', 2, 'xxx']]`
+    ///   (see [UpdateOps](../update/struct.UpdateOps.html) for a typed way to build them)
     ///
     /// Returns a new tuple.
     ///
     /// See also: [index.update()](#method.update)
-    pub fn upsert<T, Op>(&mut self, value: &T, ops: &Vec<Op>) -> Result<Option<Tuple>, Error>
+    pub fn upsert<T, Ops>(&mut self, value: &T, ops: &Ops) -> Result<Option<Tuple>, Error>
     where
         T: AsTuple,
-        Op: AsTuple,
+        Ops: AsTuple,
     {
         let value_buf = value.serialize_as_tuple().unwrap();
         let value_buf_ptr = value_buf.as_ptr() as *const c_char;
@@ -515,3 +785,120 @@ impl Drop for IndexIterator {
         unsafe { ffi::box_iterator_free(self.ptr) };
     }
 }
+
+/// Iterator over a range of keys in an index. See [`Index::range`].
+///
+/// Yields [`Tuple`]s by default or `Result<T, Error>` after
+/// [`decode`](IndexRange::decode) is called.
+pub struct IndexRange<T = Tuple> {
+    index: Index,
+    key_def: KeyDef,
+    lower: Bound<Vec<u8>>,
+    upper: Bound<Vec<u8>>,
+    reverse: bool,
+    is_started: bool,
+    /// Opened on the first call to `next`, `None` after the end of the
+    /// range.
+    iter: Option<IndexIterator>,
+    marker: PhantomData<T>,
+}
+
+impl<T> IndexRange<T> {
+    /// Iterate in the descending order.
+    ///
+    /// Panics if the iteration has already started.
+    pub fn rev(mut self) -> Self {
+        assert!(
+            !self.is_started,
+            "cannot reverse an index range after the iteration has started"
+        );
+        self.reverse = !self.reverse;
+        self
+    }
+
+    /// Deserialize the tuples into `U`.
+    pub fn decode<U>(self) -> IndexRange<U>
+    where
+        U: DeserializeOwned,
+    {
+        IndexRange {
+            index: self.index,
+            key_def: self.key_def,
+            lower: self.lower,
+            upper: self.upper,
+            reverse: self.reverse,
+            is_started: self.is_started,
+            iter: self.iter,
+            marker: PhantomData,
+        }
+    }
+
+    /// Opens an iterator starting at the lower bound, or at the upper one if
+    /// `reverse` is set.
+    fn open(&self) -> Result<IndexIterator, Error> {
+        let start = if self.reverse { &self.upper } else { &self.lower };
+        let (iterator_type, key) = match (start, self.reverse) {
+            (Bound::Included(key), false) => (IteratorType::GE, key.clone()),
+            (Bound::Excluded(key), false) => (IteratorType::GT, key.clone()),
+            (Bound::Included(key), true) => (IteratorType::LE, key.clone()),
+            (Bound::Excluded(key), true) => (IteratorType::LT, key.clone()),
+            // an empty key matches all the tuples
+            (Bound::Unbounded, false) => (IteratorType::GE, rmp_serde::to_vec(&()).unwrap()),
+            (Bound::Unbounded, true) => (IteratorType::LE, rmp_serde::to_vec(&()).unwrap()),
+        };
+        self.index.select_by_key_data(iterator_type, key.into())
+    }
+
+    fn next_tuple(&mut self) -> Option<Result<Tuple, Error>> {
+        if !self.is_started {
+            self.is_started = true;
+            match self.open() {
+                Ok(iter) => self.iter = Some(iter),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        // The scan stops at the bound opposite to the one it started at
+        let end = if self.reverse { &self.lower } else { &self.upper };
+        let tuple = self.iter.as_mut().and_then(Iterator::next);
+        let is_past_end = |tuple: &Tuple| {
+            let (key, is_inclusive) = match end {
+                Bound::Included(key) => (key, true),
+                Bound::Excluded(key) => (key, false),
+                Bound::Unbounded => return false,
+            };
+            let mut ordering = self.key_def.compare_with_key_data(tuple, key.as_ptr());
+            if self.reverse {
+                ordering = ordering.reverse();
+            }
+            ordering == Ordering::Greater || !is_inclusive && ordering == Ordering::Equal
+        };
+        match tuple {
+            Some(tuple) if !is_past_end(&tuple) => Some(Ok(tuple)),
+            _ => {
+                self.iter = None;
+                None
+            }
+        }
+    }
+}
+
+impl Iterator for IndexRange<Tuple> {
+    type Item = Tuple;
+
+    /// Stops if the iterator can't be opened, e.g. if the index was
+    /// dropped. Use [`decode`](IndexRange::decode) to get the error.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_tuple()?.ok()
+    }
+}
+
+impl<T> Iterator for IndexRange<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_tuple().map(|tuple| tuple?.into_struct())
+    }
+}
@@ -1,16 +1,18 @@
 //! Tarantool C API bindings for Rust.
 //! This library contains the following Tarantool API's:
 //!
-//! - Box: [spaces](space), [indexes](index), [sequences](sequence)
+//! - Box: [spaces](space), [indexes](index), [sequences](sequence), [update operations](update)
 //! - [Fibers: fiber attributes, conditional variables, latches](fiber)
 //! - [CoIO](coio)
 //! - [Transactions](transaction)
 //! - [Schema management](schema)
 //! - [Protocol implementation](net_box) (`net.box`): CRUD, stored procedure call, triggers
 //! - [Tuple utils](mod@tuple)
-//! - [Decimal numbers](mod@decimal)
+//! - [Decimal numbers](mod@decimal), [date and time](datetime)
+//! - [MsgPack extension types](msgpack)
 //! - [Logging](log) (see <https://docs.rs/log/>)
 //! - [Error handling](error)
+//! - [Version detection](mod@version)
 //!
 //! > **Caution!** The library is currently under development.
 //! > API may be unstable until version 1.0 will be released.
@@ -172,6 +174,7 @@ extern crate serde;
 
 pub mod clock;
 pub mod coio;
+pub mod datetime;
 pub mod decimal;
 #[macro_use]
 pub mod error;
@@ -180,6 +183,7 @@ pub mod ffi;
 pub mod fiber;
 pub mod index;
 pub mod log;
+pub mod msgpack;
 pub mod net_box;
 pub mod raft;
 pub mod schema;
@@ -188,11 +192,21 @@ pub mod session;
 pub mod space;
 pub mod transaction;
 pub mod tuple;
+pub mod update;
 pub mod util;
 pub mod uuid;
+pub mod version;
 
 pub use tlua;
 
+#[doc(hidden)]
+pub mod __private {
+    // used in the code generated by `#[derive(Tuple)]`
+    pub mod serde {
+        pub use ::serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+    }
+}
+
 pub fn global_lua() -> tlua::Lua {
     unsafe {
         tlua::Lua::from_existing_state(ffi::tarantool::luaT_state(), false)
@@ -200,4 +214,5 @@ pub fn global_lua() -> tlua::Lua {
 }
 
 pub use error::Result;
+pub use version::{supports, version};
 pub type StdResult<T, E> = std::result::Result<T, E>;
@@ -0,0 +1,98 @@
+//! MsgPack extension types
+//!
+//! Tarantool specific types like [`Decimal`](crate::decimal::Decimal),
+//! [`Uuid`](crate::uuid::Uuid) or [`Datetime`](crate::datetime::Datetime) are
+//! encoded as MsgPack extensions. The types implementing [`Ext`] can be
+//! serialized with `serde` as the corresponding extension, so they can be used
+//! as tuple fields both locally and via [net_box](crate::net_box).
+//!
+//! See also:
+//! - [MessagePack extensions](https://www.tarantool.io/en/doc/latest/dev_guide/internals/msgpack_extensions/)
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Extension type of [`Decimal`](crate::decimal::Decimal).
+pub const MP_DECIMAL: i8 = crate::ffi::decimal::MP_DECIMAL;
+/// Extension type of [`Uuid`](crate::uuid::Uuid).
+pub const MP_UUID: i8 = crate::ffi::uuid::MP_UUID;
+/// Extension type of [`ErrorInfo`](crate::error::ErrorInfo).
+pub const MP_ERROR: i8 = 3;
+/// Extension type of [`Datetime`](crate::datetime::Datetime).
+pub const MP_DATETIME: i8 = 4;
+/// Extension type of [`Interval`](crate::datetime::Interval).
+pub const MP_INTERVAL: i8 = 6;
+
+/// A type encoded as a MsgPack extension.
+pub trait Ext: Sized {
+    /// Extension type id.
+    const TYPE: i8;
+
+    /// Type name used in the error messages.
+    const NAME: &'static str;
+
+    /// Returns the extension payload.
+    fn to_ext_data(&self) -> Vec<u8>;
+
+    /// Decodes the value from the extension payload.
+    fn from_ext_data(data: &[u8]) -> Result<Self, String>;
+}
+
+// rmp_serde encodes a newtype struct with this name as an extension
+#[derive(Serialize)]
+#[serde(rename = "_ExtStruct")]
+struct ExtStructRef<'a>((i8, &'a serde_bytes::Bytes));
+
+#[derive(Deserialize)]
+#[serde(rename = "_ExtStruct")]
+struct ExtStruct((i8, serde_bytes::ByteBuf));
+
+/// Serializes `value` as a MsgPack extension. Can be used in the
+/// `#[serde(serialize_with = "...")]` attribute.
+pub fn serialize_ext<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Ext,
+    S: Serializer,
+{
+    let data = value.to_ext_data();
+    ExtStructRef((T::TYPE, serde_bytes::Bytes::new(&data))).serialize(serializer)
+}
+
+/// Deserializes a value from a MsgPack extension. Can be used in the
+/// `#[serde(deserialize_with = "...")]` attribute.
+pub fn deserialize_ext<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Ext,
+    D: Deserializer<'de>,
+{
+    let ExtStruct((kind, data)) = ExtStruct::deserialize(deserializer)?;
+    if kind != T::TYPE {
+        return Err(D::Error::custom(
+            format!("Expected {}, found msgpack ext #{}", T::NAME, kind)
+        ))
+    }
+    T::from_ext_data(&data).map_err(D::Error::custom)
+}
+
+/// Implements `Serialize` and `Deserialize` for a type implementing [`Ext`].
+macro_rules! impl_serde_for_ext {
+    ($t:ty) => {
+        impl serde::Serialize for $t {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                $crate::msgpack::serialize_ext(self, serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $t {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                $crate::msgpack::deserialize_ext(deserializer)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_serde_for_ext;
@@ -79,15 +79,15 @@ impl RemoteIndex {
 
     /// The remote-call equivalent of the local call `Space::update(...)`
     /// (see [details](../index/struct.Index.html#method.update)).
-    pub fn update<K, Op>(
+    pub fn update<K, Ops>(
         &mut self,
         key: &K,
-        ops: &Vec<Op>,
+        ops: &Ops,
         options: &Options,
     ) -> Result<Option<Tuple>, Error>
     where
         K: AsTuple,
-        Op: AsTuple,
+        Ops: AsTuple,
     {
         self.conn_inner.request(
             |buf, sync| protocol::encode_update(buf, sync, self.space_id, self.index_id, key, ops),
@@ -98,15 +98,15 @@ impl RemoteIndex {
 
     /// The remote-call equivalent of the local call `Space::upsert(...)`
     /// (see [details](../index/struct.Index.html#method.upsert)).
-    pub fn upsert<T, Op>(
+    pub fn upsert<T, Ops>(
         &mut self,
         value: &T,
-        ops: &Vec<Op>,
+        ops: &Ops,
         options: &Options,
     ) -> Result<Option<Tuple>, Error>
     where
         T: AsTuple,
-        Op: AsTuple,
+        Ops: AsTuple,
     {
         self.conn_inner.request(
             |buf, sync| {
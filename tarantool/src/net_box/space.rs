@@ -88,30 +88,30 @@ impl RemoteSpace {
 
     /// The remote-call equivalent of the local call `Space::update(...)`
     /// (see [details](../space/struct.Space.html#method.update)).
-    pub fn update<K, Op>(
+    pub fn update<K, Ops>(
         &mut self,
         key: &K,
-        ops: &Vec<Op>,
+        ops: &Ops,
         options: &Options,
     ) -> Result<Option<Tuple>, Error>
     where
         K: AsTuple,
-        Op: AsTuple,
+        Ops: AsTuple,
     {
         self.primary_key().update(key, ops, options)
     }
 
     /// The remote-call equivalent of the local call `Space::upsert(...)`
     /// (see [details](../space/struct.Space.html#method.upsert)).
-    pub fn upsert<T, Op>(
+    pub fn upsert<T, Ops>(
         &mut self,
         value: &T,
-        ops: &Vec<Op>,
+        ops: &Ops,
         options: &Options,
     ) -> Result<Option<Tuple>, Error>
     where
         T: AsTuple,
-        Op: AsTuple,
+        Ops: AsTuple,
     {
         self.primary_key().upsert(value, ops, options)
     }
@@ -0,0 +1,205 @@
+//! Stored functions
+//!
+//! Functions registered in the `_func` system space can be called via
+//! [net_box](crate::net_box), granted privileges on and, if they are
+//! persistent, used in the functional indexes:
+//! ```no_run
+//! use tarantool::index::{IndexFieldType, IndexOptions, IndexPart};
+//! use tarantool::schema::func::{self, FuncCreateOptions};
+//! use tarantool::space::Space;
+//!
+//! func::create("lower_name", &FuncCreateOptions {
+//!     body: Some("function(tuple) return {tuple[2]:lower()} end".into()),
+//!     is_deterministic: true,
+//!     is_sandboxed: true,
+//!     ..Default::default()
+//! }).unwrap();
+//! Space::find("users").unwrap().create_index("name", &IndexOptions {
+//!     func: Some("lower_name".into()),
+//!     parts: Some(vec![IndexPart::new(1, IndexFieldType::String)]),
+//!     ..Default::default()
+//! }).unwrap();
+//! ```
+//!
+//! See also:
+//! - [Lua reference: box.schema.func.create()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/func_create/)
+//! - [Functional indexes](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_space/create_index/#creating-a-functional-index)
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::datetime::Datetime;
+use crate::error::{Error, TarantoolError, TarantoolErrorCode};
+use crate::schema;
+use crate::index::IteratorType;
+use crate::session;
+use crate::space::{FuncMetadata, Space, SpaceFieldType, SystemSpace};
+
+/// Language of a stored function.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FuncLanguage {
+    Lua,
+    /// A function exported from a shared library. The name is
+    /// `module.function`, e.g. `easy.easy2`.
+    C,
+}
+
+impl Default for FuncLanguage {
+    fn default() -> Self {
+        Self::Lua
+    }
+}
+
+impl FuncLanguage {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Lua => "LUA",
+            Self::C => "C",
+        }
+    }
+}
+
+/// Options of [`create`].
+#[derive(Clone, Debug)]
+pub struct FuncCreateOptions {
+    pub if_not_exists: bool,
+    /// Call the function with the privileges of its owner.
+    pub setuid: bool,
+    pub language: FuncLanguage,
+    /// Source code of a persistent Lua function, e.g.
+    /// `function(a, b) return a + b end`. If not set, the function must be
+    /// defined in the global Lua namespace (or in a shared library for C).
+    pub body: Option<String>,
+    /// The function returns the same result for the same arguments. Required
+    /// for the functional indexes.
+    pub is_deterministic: bool,
+    /// The function runs in an isolated environment without access to the
+    /// global variables. Required for the functional indexes.
+    pub is_sandboxed: bool,
+    pub returns: SpaceFieldType,
+    pub param_list: Vec<SpaceFieldType>,
+    /// Languages the function can be called from, only `LUA` by default.
+    pub exports: Vec<String>,
+    /// The function returns multiple keys for a functional index.
+    pub is_multikey: bool,
+    pub comment: String,
+}
+
+impl Default for FuncCreateOptions {
+    fn default() -> Self {
+        FuncCreateOptions {
+            if_not_exists: false,
+            setuid: false,
+            language: FuncLanguage::Lua,
+            body: None,
+            is_deterministic: false,
+            is_sandboxed: false,
+            returns: SpaceFieldType::Any,
+            param_list: vec![],
+            exports: vec!["LUA".to_string()],
+            is_multikey: false,
+            comment: String::new(),
+        }
+    }
+}
+
+/// Create a stored function.
+/// (for details see [box.schema.func.create()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/func_create/)).
+///
+/// Returns the id of the new function.
+pub fn create(name: &str, opts: &FuncCreateOptions) -> Result<u32, Error> {
+    let sys_vfunc: Space = SystemSpace::VFunc.into();
+    if let Some(t) = sys_vfunc.index("name").unwrap().get(&(name,))? {
+        return if opts.if_not_exists {
+            Ok(t.field::<u32>(0)?.unwrap())
+        } else {
+            set_error!(TarantoolErrorCode::FunctionExists, "Function '{}' already exists", name);
+            Err(TarantoolError::last().into())
+        };
+    }
+
+    let mut sys_func: Space = SystemSpace::Func.into();
+    let id = match sys_func.primary_key().max(&())? {
+        Some(t) => t.field::<u32>(0)?.unwrap() + 1,
+        None => 1,
+    };
+    let mut func_opts = Map::new();
+    if opts.is_multikey {
+        func_opts.insert("is_multikey".to_string(), Value::Bool(true));
+    }
+    let now = timestamp_now();
+    sys_func.insert(&FuncMetadata {
+        id,
+        owner: session::euid()? as u32,
+        name: name.to_string(),
+        setuid: opts.setuid as u32,
+        language: opts.language.as_str().to_string(),
+        body: opts.body.clone().unwrap_or_default(),
+        routine_type: "function".to_string(),
+        param_list: opts.param_list.iter().map(|t| Value::String(t.to_string())).collect(),
+        returns: opts.returns.to_string(),
+        aggregate: "none".to_string(),
+        sql_data_access: "none".to_string(),
+        is_deterministic: opts.is_deterministic,
+        is_sandboxed: opts.is_sandboxed,
+        is_null_call: true,
+        exports: opts.exports.clone(),
+        opts: func_opts,
+        comment: opts.comment.clone(),
+        created: now.clone(),
+        last_altered: now,
+    })?;
+    Ok(id)
+}
+
+/// Drop a stored function and revoke the privileges granted on it.
+/// (for details see [box.schema.func.drop()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/func_drop/)).
+pub fn drop(name: &str) -> Result<(), Error> {
+    let sys_vfunc: Space = SystemSpace::VFunc.into();
+    match sys_vfunc.index("name").unwrap().get(&(name,))? {
+        Some(t) => drop_func(t.field::<u32>(0)?.unwrap()),
+        None => {
+            set_error!(TarantoolErrorCode::NoSuchFunction, "Function '{}' does not exist", name);
+            Err(TarantoolError::last().into())
+        }
+    }
+}
+
+/// Drop existing function.
+///
+/// - `func_id` - ID of existing function.
+pub(crate) fn drop_func(func_id: u32) -> Result<(), Error> {
+    // tarantool doesn't drop a function with grants, so they are revoked
+    // first and restored if the function can't be dropped, e.g. it's used by
+    // a functional index
+    let sys_vpriv: Space = SystemSpace::VPriv.into();
+    let grants = sys_vpriv.index("object").unwrap()
+        .select(IteratorType::Eq, &("function", func_id))?
+        .map(|t| t.decode::<Vec<Value>>())
+        .collect::<Result<Vec<_>, _>>()?;
+    schema::revoke_object_privileges("function", func_id)?;
+
+    let mut sys_func: Space = SystemSpace::Func.into();
+    if let Err(e) = sys_func.delete(&(func_id,)) {
+        let mut sys_priv: Space = SystemSpace::Priv.into();
+        for grant in &grants {
+            sys_priv.insert(grant)?;
+        }
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Returns the current time in the format of `_func.created`, e.g.
+/// `2022-03-01 12:30:00`.
+fn timestamp_now() -> String {
+    let now = Datetime::from(SystemTime::now());
+    Datetime::from_timestamp(now.timestamp(), 0)
+        .to_string()
+        .replace('T', " ")
+        .trim_end_matches('Z')
+        .to_string()
+}
@@ -5,8 +5,10 @@ use crate::ffi::helper::new_c_str;
 use crate::ffi::lua;
 use crate::ffi::tarantool::{luaT_state, luaT_call};
 use crate::index::{
-    IndexFieldType, IndexOptions, IndexSequenceOption, IndexType, RtreeIndexDistanceType,
+    Index, IndexAlterOptions, IndexFieldType, IndexOptions, IndexSequenceOption, IndexType,
+    RtreeIndexDistanceType,
 };
+use crate::version::{self, Feature};
 
 /// Create new index for space.
 ///
@@ -16,6 +18,10 @@ use crate::index::{
 ///
 /// For details see [space_object:create_index](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_space/create_index/)
 pub fn create_index(space_id: u32, index_name: &str, opts: &IndexOptions) -> Result<(), Error> {
+    if opts.func.is_some() {
+        version::check_supported(Feature::FunctionalIndexes)?;
+    }
+
     unsafe {
         // Create new stack (just in case - in order no to mess things
         // in current stack).
@@ -58,201 +64,276 @@ pub fn create_index(space_id: u32, index_name: &str, opts: &IndexOptions) -> Res
         lua::lua_pushstring(ci_state, new_c_str(index_name).as_ptr());
 
         // options
-        lua::lua_newtable(ci_state);
-
-        // opts.index_type
-        if let Some(index_type) = opts.index_type {
-            let index_type_str = match index_type {
-                IndexType::Hash => "hash",
-                IndexType::Tree => "tree",
-                IndexType::Bitset => "bitset",
-                IndexType::Rtree => "rtree",
-            };
-            lua::lua_pushstring(ci_state, new_c_str(index_type_str).as_ptr());
-            lua::lua_setfield(ci_state, -2, new_c_str("type").as_ptr());
-        }
+        push_index_options(ci_state, opts);
 
-        // opts.id
-        if let Some(id) = opts.id {
-            lua::lua_pushinteger(ci_state, id as isize);
-            lua::lua_setfield(ci_state, -2, new_c_str("id").as_ptr());
+        // Call space_object:create_index.
+        if luaT_call(ci_state, 3, 1) == 1 {
+            return Err(TarantoolError::last().into());
         }
 
-        // opts.unique
-        if let Some(unique) = opts.unique {
-            lua::lua_pushboolean(ci_state, unique as c_int);
-            lua::lua_setfield(ci_state, -2, new_c_str("unique").as_ptr());
+        // No need to clean ci_state. It will be gc'ed.
+    }
+
+    Ok(())
+}
+
+/// Alter existing index.
+///
+/// - `space_id` - ID of existing space.
+/// - `index_id` - ID of existing index.
+/// - `opts`     - see IndexAlterOptions struct. The options which are `None` aren't changed.
+///
+/// For details see [index_object:alter](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_index/alter/)
+pub fn alter_index(
+    space_id: u32,
+    index_id: u32,
+    opts: &IndexAlterOptions,
+) -> Result<(), Error> {
+    // Fail early if there's no such index
+    Index::new(space_id, index_id).meta()?;
+
+    unsafe {
+        // Create new stack (just in case - in order no to mess things
+        // in current stack).
+        let state = luaT_state();
+        let alter_state = lua::lua_newthread(state);
+
+        // Execute the following Lua code:
+        // -- box.space[space_id].index[index_id]:alter(opts)
+        lua::lua_getglobal(alter_state, new_c_str("box").as_ptr());
+        lua::lua_getfield(alter_state, -1, new_c_str("space").as_ptr());
+        lua::lua_pushinteger(alter_state, space_id as isize);
+        lua::lua_gettable(alter_state, -2);
+        lua::lua_getfield(alter_state, -1, new_c_str("index").as_ptr());
+        lua::lua_pushinteger(alter_state, index_id as isize);
+        lua::lua_gettable(alter_state, -2);
+        lua::lua_getfield(alter_state, -1, new_c_str("alter").as_ptr());
+
+        // self
+        lua::lua_pushvalue(alter_state, -2);
+
+        // options, index_object:alter doesn't accept `id` and `if_not_exists`
+        push_index_options(alter_state, &IndexOptions {
+            index_type: opts.index_type,
+            id: None,
+            unique: opts.unique,
+            if_not_exists: None,
+            parts: opts.parts.clone(),
+            dimension: opts.dimension,
+            distance: opts.distance,
+            bloom_fpr: opts.bloom_fpr,
+            page_size: opts.page_size,
+            range_size: opts.range_size,
+            run_count_per_level: opts.run_count_per_level,
+            run_size_ratio: opts.run_size_ratio,
+            sequence: opts.sequence.clone(),
+            func: opts.func.clone(),
+            hint: opts.hint,
+        });
+        if let Some(name) = &opts.name {
+            lua::lua_pushstring(alter_state, new_c_str(name).as_ptr());
+            lua::lua_setfield(alter_state, -2, new_c_str("name").as_ptr());
         }
 
-        // opts.if_not_exists
-        if let Some(if_not_exists) = opts.if_not_exists {
-            lua::lua_pushboolean(ci_state, if_not_exists as c_int);
-            lua::lua_setfield(ci_state, -2, new_c_str("if_not_exists").as_ptr());
+        // Call index_object:alter.
+        if luaT_call(alter_state, 2, 0) == 1 {
+            return Err(TarantoolError::last().into());
         }
 
-        // opts.parts
-        if let Some(parts) = &opts.parts {
-            lua::lua_newtable(ci_state);
-
-            for (idx, p) in parts.iter().enumerate() {
-                lua::lua_pushinteger(ci_state, (idx + 1) as isize);
-                lua::lua_newtable(ci_state);
-
-                // part.field
-                lua::lua_pushinteger(ci_state, p.field_index as isize);
-                lua::lua_setfield(ci_state, -2, new_c_str("field").as_ptr());
-
-                // part.type
-                let field_type = match p.field_type {
-                    IndexFieldType::Unsigned => "unsigned",
-                    IndexFieldType::String => "string",
-                    IndexFieldType::Integer => "integer",
-                    IndexFieldType::Number => "number",
-                    IndexFieldType::Double => "double",
-                    IndexFieldType::Decimal => "decimal",
-                    IndexFieldType::Boolean => "boolean",
-                    IndexFieldType::Varbinary => "varbinary",
-                    IndexFieldType::Uuid => "uuid",
-                    IndexFieldType::Array => "array",
-                    IndexFieldType::Scalar => "scalar",
-                };
-                lua::lua_pushstring(ci_state, new_c_str(field_type).as_ptr());
-                lua::lua_setfield(ci_state, -2, new_c_str("type").as_ptr());
-
-                // part.collation
-                if let Some(collation) = &p.collation {
-                    lua::lua_pushstring(ci_state, new_c_str(collation).as_ptr());
-                    lua::lua_setfield(ci_state, -2, new_c_str("collation").as_ptr());
-                }
+        // No need to clean alter_state. It will be gc'ed.
+    }
 
-                // part.is_nullable
-                if let Some(is_nullable) = &p.is_nullable {
-                    lua::lua_pushboolean(ci_state, if *is_nullable { 1 } else { 0 });
-                    lua::lua_setfield(ci_state, -2, new_c_str("is_nullable").as_ptr());
-                }
+    Ok(())
+}
 
-                // part.path
-                if let Some(path) = &p.path {
-                    lua::lua_pushstring(ci_state, new_c_str(path).as_ptr());
-                    lua::lua_setfield(ci_state, -2, new_c_str("path").as_ptr());
-                }
+/// Pushes a table with the index options `opts` onto the Lua stack.
+unsafe fn push_index_options(state: *mut lua::lua_State, opts: &IndexOptions) {
+    lua::lua_newtable(state);
+
+    // opts.index_type
+    if let Some(index_type) = opts.index_type {
+        let index_type_str = match index_type {
+            IndexType::Hash => "hash",
+            IndexType::Tree => "tree",
+            IndexType::Bitset => "bitset",
+            IndexType::Rtree => "rtree",
+        };
+        lua::lua_pushstring(state, new_c_str(index_type_str).as_ptr());
+        lua::lua_setfield(state, -2, new_c_str("type").as_ptr());
+    }
 
-                lua::lua_settable(ci_state, -3);
-            }
+    // opts.id
+    if let Some(id) = opts.id {
+        lua::lua_pushinteger(state, id as isize);
+        lua::lua_setfield(state, -2, new_c_str("id").as_ptr());
+    }
 
-            lua::lua_setfield(ci_state, -2, new_c_str("parts").as_ptr())
-        }
+    // opts.unique
+    if let Some(unique) = opts.unique {
+        lua::lua_pushboolean(state, unique as c_int);
+        lua::lua_setfield(state, -2, new_c_str("unique").as_ptr());
+    }
 
-        // opts.dimension
-        if let Some(dimension) = opts.dimension {
-            lua::lua_pushinteger(ci_state, dimension as isize);
-            lua::lua_setfield(ci_state, -2, new_c_str("dimension").as_ptr());
-        }
+    // opts.if_not_exists
+    if let Some(if_not_exists) = opts.if_not_exists {
+        lua::lua_pushboolean(state, if_not_exists as c_int);
+        lua::lua_setfield(state, -2, new_c_str("if_not_exists").as_ptr());
+    }
 
-        // opts.distance
-        if let Some(distance) = opts.distance {
-            let distance_str = match distance {
-                RtreeIndexDistanceType::Euclid => "euclid",
-                RtreeIndexDistanceType::Manhattan => "manhattan",
+    // opts.parts
+    if let Some(parts) = &opts.parts {
+        lua::lua_newtable(state);
+
+        for (idx, p) in parts.iter().enumerate() {
+            lua::lua_pushinteger(state, (idx + 1) as isize);
+            lua::lua_newtable(state);
+
+            // part.field
+            lua::lua_pushinteger(state, p.field_index as isize);
+            lua::lua_setfield(state, -2, new_c_str("field").as_ptr());
+
+            // part.type
+            let field_type = match p.field_type {
+                IndexFieldType::Unsigned => "unsigned",
+                IndexFieldType::String => "string",
+                IndexFieldType::Integer => "integer",
+                IndexFieldType::Number => "number",
+                IndexFieldType::Double => "double",
+                IndexFieldType::Decimal => "decimal",
+                IndexFieldType::Boolean => "boolean",
+                IndexFieldType::Varbinary => "varbinary",
+                IndexFieldType::Uuid => "uuid",
+                IndexFieldType::Array => "array",
+                IndexFieldType::Scalar => "scalar",
             };
-            lua::lua_pushstring(ci_state, new_c_str(distance_str).as_ptr());
-            lua::lua_setfield(ci_state, -2, new_c_str("distance").as_ptr());
-        }
+            lua::lua_pushstring(state, new_c_str(field_type).as_ptr());
+            lua::lua_setfield(state, -2, new_c_str("type").as_ptr());
 
-        // opts.bloom_fpr
-        if let Some(bloom_fpr) = opts.bloom_fpr {
-            lua::lua_pushnumber(ci_state, bloom_fpr as f64);
-            lua::lua_setfield(ci_state, -2, new_c_str("bloom_fpr").as_ptr());
-        }
+            // part.collation
+            if let Some(collation) = &p.collation {
+                lua::lua_pushstring(state, new_c_str(collation).as_ptr());
+                lua::lua_setfield(state, -2, new_c_str("collation").as_ptr());
+            }
 
-        // opts.page_size
-        if let Some(page_size) = opts.page_size {
-            lua::lua_pushinteger(ci_state, page_size as isize);
-            lua::lua_setfield(ci_state, -2, new_c_str("page_size").as_ptr());
-        }
+            // part.is_nullable
+            if let Some(is_nullable) = &p.is_nullable {
+                lua::lua_pushboolean(state, if *is_nullable { 1 } else { 0 });
+                lua::lua_setfield(state, -2, new_c_str("is_nullable").as_ptr());
+            }
 
-        // opts.range_size
-        if let Some(range_size) = opts.range_size {
-            lua::lua_pushinteger(ci_state, range_size as isize);
-            lua::lua_setfield(ci_state, -2, new_c_str("range_size").as_ptr());
-        }
+            // part.path
+            if let Some(path) = &p.path {
+                lua::lua_pushstring(state, new_c_str(path).as_ptr());
+                lua::lua_setfield(state, -2, new_c_str("path").as_ptr());
+            }
 
-        // opts.run_count_per_level
-        if let Some(run_count_per_level) = opts.run_count_per_level {
-            lua::lua_pushinteger(ci_state, run_count_per_level as isize);
-            lua::lua_setfield(ci_state, -2, new_c_str("run_count_per_level").as_ptr());
+            lua::lua_settable(state, -3);
         }
 
-        // opts.run_size_ratio
-        if let Some(run_size_ratio) = opts.run_size_ratio {
-            lua::lua_pushnumber(ci_state, run_size_ratio as f64);
-            lua::lua_setfield(ci_state, -2, new_c_str("run_size_ratio").as_ptr());
-        }
+        lua::lua_setfield(state, -2, new_c_str("parts").as_ptr())
+    }
 
-        // opts.sequence
-        if let Some(sequence) = &opts.sequence {
-            match sequence {
-                // sequence = {id = sequence identifier , field = field number }
-                IndexSequenceOption::SeqId {
-                    seq_id,
-                    field_index,
-                } => {
-                    lua::lua_newtable(ci_state);
-                    lua::lua_pushinteger(ci_state, *seq_id as isize);
-                    lua::lua_setfield(ci_state, -2, new_c_str("id").as_ptr());
-                    if let Some(fi) = field_index {
-                        lua::lua_pushinteger(ci_state, *fi as isize);
-                        lua::lua_setfield(ci_state, -2, new_c_str("field").as_ptr());
-                    }
-                }
-                // sequence = {id = sequence name , field = field number }
-                IndexSequenceOption::SeqName {
-                    seq_name,
-                    field_index,
-                } => {
-                    lua::lua_newtable(ci_state);
-                    lua::lua_pushstring(ci_state, new_c_str(seq_name).as_ptr());
-                    lua::lua_setfield(ci_state, -2, new_c_str("id").as_ptr());
-                    if let Some(fi) = field_index {
-                        lua::lua_pushinteger(ci_state, *fi as isize);
-                        lua::lua_setfield(ci_state, -2, new_c_str("field").as_ptr());
-                    }
-                }
-                // sequence = true
-                IndexSequenceOption::True => {
-                    lua::lua_pushboolean(ci_state, true as c_int);
+    // opts.dimension
+    if let Some(dimension) = opts.dimension {
+        lua::lua_pushinteger(state, dimension as isize);
+        lua::lua_setfield(state, -2, new_c_str("dimension").as_ptr());
+    }
+
+    // opts.distance
+    if let Some(distance) = opts.distance {
+        let distance_str = match distance {
+            RtreeIndexDistanceType::Euclid => "euclid",
+            RtreeIndexDistanceType::Manhattan => "manhattan",
+        };
+        lua::lua_pushstring(state, new_c_str(distance_str).as_ptr());
+        lua::lua_setfield(state, -2, new_c_str("distance").as_ptr());
+    }
+
+    // opts.bloom_fpr
+    if let Some(bloom_fpr) = opts.bloom_fpr {
+        lua::lua_pushnumber(state, bloom_fpr as f64);
+        lua::lua_setfield(state, -2, new_c_str("bloom_fpr").as_ptr());
+    }
+
+    // opts.page_size
+    if let Some(page_size) = opts.page_size {
+        lua::lua_pushinteger(state, page_size as isize);
+        lua::lua_setfield(state, -2, new_c_str("page_size").as_ptr());
+    }
+
+    // opts.range_size
+    if let Some(range_size) = opts.range_size {
+        lua::lua_pushinteger(state, range_size as isize);
+        lua::lua_setfield(state, -2, new_c_str("range_size").as_ptr());
+    }
+
+    // opts.run_count_per_level
+    if let Some(run_count_per_level) = opts.run_count_per_level {
+        lua::lua_pushinteger(state, run_count_per_level as isize);
+        lua::lua_setfield(state, -2, new_c_str("run_count_per_level").as_ptr());
+    }
+
+    // opts.run_size_ratio
+    if let Some(run_size_ratio) = opts.run_size_ratio {
+        lua::lua_pushnumber(state, run_size_ratio as f64);
+        lua::lua_setfield(state, -2, new_c_str("run_size_ratio").as_ptr());
+    }
+
+    // opts.sequence
+    if let Some(sequence) = &opts.sequence {
+        match sequence {
+            // sequence = {id = sequence identifier , field = field number }
+            IndexSequenceOption::SeqId {
+                seq_id,
+                field_index,
+            } => {
+                lua::lua_newtable(state);
+                lua::lua_pushinteger(state, *seq_id as isize);
+                lua::lua_setfield(state, -2, new_c_str("id").as_ptr());
+                if let Some(fi) = field_index {
+                    lua::lua_pushinteger(state, *fi as isize);
+                    lua::lua_setfield(state, -2, new_c_str("field").as_ptr());
                 }
-                // sequence = {}
-                IndexSequenceOption::Empty => {
-                    lua::lua_newtable(ci_state);
+            }
+            // sequence = {id = sequence name , field = field number }
+            IndexSequenceOption::SeqName {
+                seq_name,
+                field_index,
+            } => {
+                lua::lua_newtable(state);
+                lua::lua_pushstring(state, new_c_str(seq_name).as_ptr());
+                lua::lua_setfield(state, -2, new_c_str("id").as_ptr());
+                if let Some(fi) = field_index {
+                    lua::lua_pushinteger(state, *fi as isize);
+                    lua::lua_setfield(state, -2, new_c_str("field").as_ptr());
                 }
             }
-            lua::lua_setfield(ci_state, -2, new_c_str("sequence").as_ptr());
-        }
-
-        // opts.func
-        if let Some(func) = &opts.func {
-            lua::lua_pushstring(ci_state, new_c_str(func).as_ptr());
-            lua::lua_setfield(ci_state, -2, new_c_str("func").as_ptr());
+            // sequence = true
+            IndexSequenceOption::True => {
+                lua::lua_pushboolean(state, true as c_int);
+            }
+            // sequence = {}
+            IndexSequenceOption::Empty => {
+                lua::lua_newtable(state);
+            }
         }
+        lua::lua_setfield(state, -2, new_c_str("sequence").as_ptr());
+    }
 
-        // Only for Tarantool >= 2.6
-        // opt.hint
-        /* if let Some(hint) = opts.hint {
-            ffi_lua::lua_pushboolean(ci_state, bool_as_int(hint));
-            ffi_lua::lua_setfield(ci_state, -2, new_c_str("hint").as_ptr());
-        }
-        */
+    // opts.func
+    if let Some(func) = &opts.func {
+        lua::lua_pushstring(state, new_c_str(func).as_ptr());
+        lua::lua_setfield(state, -2, new_c_str("func").as_ptr());
+    }
 
-        // Call space_object:create_index.
-        if luaT_call(ci_state, 3, 1) == 1 {
-            return Err(TarantoolError::last().into());
+    // opts.hint
+    if let Some(hint) = opts.hint {
+        // the option is only a performance hint, so it's safe to omit it
+        if version::supports(Feature::IndexHint) {
+            lua::lua_pushboolean(state, hint as c_int);
+            lua::lua_setfield(state, -2, new_c_str("hint").as_ptr());
         }
-
-        // No need to clean ci_state. It will be gc'ed.
     }
-
-    Ok(())
 }
 
 /// Drop existing index.
@@ -0,0 +1,267 @@
+//! Schema migrations
+//!
+//! A migration is a versioned step which changes the schema or the data, e.g.
+//! creates a space. The applied migrations are recorded in a dedicated space
+//! (`_migrations` by default), so that [`Migrations::apply`] runs each step
+//! only once, even if it's called on every instance startup.
+//!
+//! Example:
+//! ```no_run
+//! use tarantool::index::{IndexFieldType, IndexOptions, IndexPart};
+//! use tarantool::schema::migrations::{Ddl, Migrations};
+//! use tarantool::space::{Space, SpaceCreateOptions};
+//!
+//! let mut migrations = Migrations::new();
+//! migrations
+//!     .add(1, "create users", Ddl::CreateSpace {
+//!         name: "users".into(),
+//!         opts: SpaceCreateOptions { is_local: false, is_temporary: false, ..Default::default() },
+//!     })
+//!     .add(2, "users primary key", Ddl::CreateIndex {
+//!         space: "users".into(),
+//!         name: "pk".into(),
+//!         opts: IndexOptions {
+//!             parts: Some(vec![IndexPart::new(1, IndexFieldType::Unsigned)]),
+//!             ..Default::default()
+//!         },
+//!     })
+//!     .add_fn(3, "add admin", || {
+//!         Space::find("users").unwrap().insert(&(1, "admin"))?;
+//!         Ok(())
+//!     });
+//!
+//! for m in migrations.pending().unwrap() {
+//!     println!("pending migration #{}: {}", m.version, m.name);
+//! }
+//! migrations.apply().unwrap();
+//! ```
+use std::collections::BTreeSet;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{Error, TarantoolError, TarantoolErrorCode};
+use crate::index::{IndexFieldType, IndexOptions, IndexPart, IteratorType};
+use crate::space::{Space, SpaceAlterOptions, SpaceCreateOptions, SpaceFieldFormat, SpaceFieldType};
+use crate::transaction::start_transaction;
+
+/// Default name of the space where the applied migrations are recorded.
+pub const DEFAULT_SPACE_NAME: &str = "_migrations";
+
+/// A schema change described declaratively.
+pub enum Ddl {
+    CreateSpace { name: String, opts: SpaceCreateOptions },
+    AlterSpace { name: String, opts: SpaceAlterOptions },
+    DropSpace { name: String },
+    CreateIndex { space: String, name: String, opts: IndexOptions },
+    DropIndex { space: String, name: String },
+}
+
+impl Ddl {
+    fn execute(&self) -> Result<(), Error> {
+        match self {
+            Self::CreateSpace { name, opts } => Space::create(name, opts).map(drop),
+            Self::AlterSpace { name, opts } => find_space(name)?.alter(opts),
+            Self::DropSpace { name } => find_space(name)?.drop(),
+            Self::CreateIndex { space, name, opts } => {
+                find_space(space)?.create_index(name, opts).map(drop)
+            }
+            Self::DropIndex { space, name } => match find_space(space)?.index(name) {
+                Some(index) => index.drop(),
+                None => {
+                    set_error!(
+                        TarantoolErrorCode::NoSuchIndexName,
+                        "No index '{}' is defined in space '{}'", name, space
+                    );
+                    Err(TarantoolError::last().into())
+                }
+            },
+        }
+    }
+}
+
+impl fmt::Display for Ddl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::CreateSpace { name, .. } => write!(f, "create space '{}'", name),
+            Self::AlterSpace { name, .. } => write!(f, "alter space '{}'", name),
+            Self::DropSpace { name } => write!(f, "drop space '{}'", name),
+            Self::CreateIndex { space, name, .. } => {
+                write!(f, "create index '{}' on space '{}'", name, space)
+            }
+            Self::DropIndex { space, name } => {
+                write!(f, "drop index '{}' on space '{}'", name, space)
+            }
+        }
+    }
+}
+
+fn find_space(name: &str) -> Result<Space, Error> {
+    match Space::find(name) {
+        Some(space) => Ok(space),
+        None => {
+            set_error!(TarantoolErrorCode::NoSuchSpace, "Space '{}' does not exist", name);
+            Err(TarantoolError::last().into())
+        }
+    }
+}
+
+enum Action {
+    Ddl(Ddl),
+    Fn(Box<dyn Fn() -> Result<(), Error>>),
+}
+
+struct Step {
+    version: u32,
+    name: String,
+    action: Action,
+}
+
+/// A migration which hasn't been applied yet, see [`Migrations::pending`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingMigration {
+    pub version: u32,
+    pub name: String,
+    /// Description of the DDL operation or `None` for the closures.
+    pub ddl: Option<String>,
+}
+
+/// An ordered list of migrations.
+pub struct Migrations {
+    space_name: String,
+    steps: Vec<Step>,
+}
+
+impl Default for Migrations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Migrations {
+    /// Creates an empty list of migrations, which are recorded in the
+    /// [`DEFAULT_SPACE_NAME`] space.
+    pub fn new() -> Self {
+        Self::with_space_name(DEFAULT_SPACE_NAME)
+    }
+
+    /// Creates an empty list of migrations, which are recorded in the space
+    /// `space_name`. It's created on the first [`apply`](Self::apply).
+    pub fn with_space_name(space_name: &str) -> Self {
+        Self {
+            space_name: space_name.into(),
+            steps: vec![],
+        }
+    }
+
+    fn push(&mut self, version: u32, name: &str, action: Action) -> &mut Self {
+        if let Some(last) = self.steps.last() {
+            assert!(
+                version > last.version,
+                "migration versions must increase: {} after {}", version, last.version
+            );
+        }
+        self.steps.push(Step { version, name: name.into(), action });
+        self
+    }
+
+    /// Adds a DDL migration.
+    ///
+    /// Panics if `version` isn't greater than the version of the previous
+    /// migration.
+    pub fn add(&mut self, version: u32, name: &str, ddl: Ddl) -> &mut Self {
+        self.push(version, name, Action::Ddl(ddl))
+    }
+
+    /// Adds a migration implemented by the function `f`, e.g. a data
+    /// migration.
+    ///
+    /// Panics if `version` isn't greater than the version of the previous
+    /// migration.
+    pub fn add_fn<F>(&mut self, version: u32, name: &str, f: F) -> &mut Self
+    where
+        F: Fn() -> Result<(), Error> + 'static,
+    {
+        self.push(version, name, Action::Fn(Box::new(f)))
+    }
+
+    /// Returns the versions of the applied migrations.
+    pub fn applied(&self) -> Result<BTreeSet<u32>, Error> {
+        let space = match Space::find(&self.space_name) {
+            Some(space) => space,
+            None => return Ok(BTreeSet::new()),
+        };
+        let mut res = BTreeSet::new();
+        for t in space.primary_key().select(IteratorType::All, &())? {
+            res.insert(t.field(0)?.unwrap());
+        }
+        Ok(res)
+    }
+
+    /// Returns the migrations which haven't been applied yet without
+    /// applying them.
+    pub fn pending(&self) -> Result<Vec<PendingMigration>, Error> {
+        let applied = self.applied()?;
+        Ok(self.steps.iter()
+            .filter(|s| !applied.contains(&s.version))
+            .map(|s| PendingMigration {
+                version: s.version,
+                name: s.name.clone(),
+                ddl: match &s.action {
+                    Action::Ddl(ddl) => Some(ddl.to_string()),
+                    Action::Fn(_) => None,
+                },
+            })
+            .collect())
+    }
+
+    /// Applies the pending migrations in the order of their versions.
+    ///
+    /// Each migration is applied in a separate transaction together with
+    /// recording it as applied. If a migration fails, its changes are rolled
+    /// back, the following migrations aren't applied and the error is
+    /// returned.
+    ///
+    /// Returns the versions of the applied migrations.
+    pub fn apply(&self) -> Result<Vec<u32>, Error> {
+        let applied = self.applied()?;
+        let mut space = self.create_space()?;
+        let mut res = vec![];
+        for step in self.steps.iter().filter(|s| !applied.contains(&s.version)) {
+            start_transaction(|| -> Result<(), Error> {
+                match &step.action {
+                    Action::Ddl(ddl) => ddl.execute()?,
+                    Action::Fn(f) => f()?,
+                }
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs());
+                space.insert(&(step.version, &step.name, now))?;
+                Ok(())
+            })?;
+            res.push(step.version);
+        }
+        Ok(res)
+    }
+
+    fn create_space(&self) -> Result<Space, Error> {
+        let opts = SpaceCreateOptions {
+            if_not_exists: true,
+            is_local: false,
+            is_temporary: false,
+            format: Some(vec![
+                SpaceFieldFormat::new("version", SpaceFieldType::Unsigned),
+                SpaceFieldFormat::new("name", SpaceFieldType::String),
+                SpaceFieldFormat::new("applied_at", SpaceFieldType::Unsigned),
+            ]),
+            ..Default::default()
+        };
+        let space = Space::create(&self.space_name, &opts)?;
+        if space.index("primary").is_none() {
+            space.create_index("primary", &IndexOptions {
+                parts: Some(vec![IndexPart::new(1, IndexFieldType::Unsigned)]),
+                ..Default::default()
+            })?;
+        }
+        Ok(space)
+    }
+}
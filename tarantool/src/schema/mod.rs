@@ -1,8 +1,13 @@
 #![cfg(any(feature = "schema", doc))]
 
+pub mod func;
 pub mod index;
+pub mod migrations;
+pub mod role;
 pub mod sequence;
+pub mod snapshot;
 pub mod space;
+pub mod user;
 
 use crate::error::Error;
 use crate::index::IteratorType;
@@ -20,7 +25,7 @@ fn resolve_user_or_role(user: &str) -> Result<Option<u32>, Error> {
 
 /// Revoke all privileges associated with the given object.
 ///
-/// - `obj_type` - string representation of object's type. Can be one of the following: "space", "sequence" or "function".
+/// - `obj_type` - string representation of object's type. Can be one of the following: "space", "sequence", "function" or "role".
 /// - `obj_id` - object's ID
 fn revoke_object_privileges(obj_type: &str, obj_id: u32) -> Result<(), Error> {
     let sys_vpriv: Space = SystemSpace::VPriv.into();
@@ -33,7 +38,7 @@ fn revoke_object_privileges(obj_type: &str, obj_id: u32) -> Result<(), Error> {
 
     for t in privs {
         let uid = t.field::<u32>(1)?.unwrap();
-        sys_priv.delete(&(uid,))?;
+        sys_priv.delete(&(uid, obj_type, obj_id))?;
     }
 
     Ok(())
@@ -0,0 +1,73 @@
+//! Roles
+//!
+//! A role is a named set of privileges which can be granted to users or to
+//! other roles. Privileges are granted to a role in the same way as to a user,
+//! see [`schema::user`](super::user):
+//! ```no_run
+//! use tarantool::schema::{role, user};
+//! use tarantool::schema::role::{GrantOptions, Object, Privileges};
+//!
+//! role::create("reader", &Default::default()).unwrap();
+//! role::grant("reader", Privileges::READ, &Object::Space("users".into()), &Default::default())
+//!     .unwrap();
+//! let reader = Object::Role("reader".into());
+//! user::grant("alice", Privileges::EXECUTE, &reader, &GrantOptions::default()).unwrap();
+//! ```
+//!
+//! See also:
+//! - [Lua reference: box.schema.role](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/#box-schema-role-create)
+use serde_json::Map;
+
+use crate::error::Error;
+use crate::schema;
+use crate::schema::user::{check_exists, find_user_or_role, UserType};
+
+pub use crate::schema::user::{GrantOptions, Object, Privileges, RevokeOptions};
+
+/// Options of [`create`].
+#[derive(Clone, Debug, Default)]
+pub struct RoleCreateOptions {
+    pub if_not_exists: bool,
+}
+
+/// Create a role.
+/// (for details see [box.schema.role.create()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/role_create/)).
+///
+/// Returns the id of the new role.
+pub fn create(name: &str, opts: &RoleCreateOptions) -> Result<u32, Error> {
+    if let Some(id) = check_exists(name, UserType::Role, opts.if_not_exists)? {
+        return Ok(id);
+    }
+    schema::user::insert_user(name, UserType::Role, Map::new())
+}
+
+/// Drop a role. It's revoked from all the users and roles it was granted to.
+/// (for details see [box.schema.role.drop()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/role_drop/)).
+pub fn drop(name: &str) -> Result<(), Error> {
+    let id = find_user_or_role(name, UserType::Role)?;
+    schema::user::drop_user_or_role(id, name)
+}
+
+/// Grant `privileges` on `object` to a role.
+/// (for details see [box.schema.role.grant()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/role_grant/)).
+pub fn grant(
+    name: &str,
+    privileges: Privileges,
+    object: &Object,
+    opts: &GrantOptions,
+) -> Result<(), Error> {
+    let grantee = find_user_or_role(name, UserType::Role)?;
+    schema::user::grant_privileges(grantee, name, privileges, object, opts)
+}
+
+/// Revoke `privileges` on `object` from a role.
+/// (for details see [box.schema.role.revoke()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/role_revoke/)).
+pub fn revoke(
+    name: &str,
+    privileges: Privileges,
+    object: &Object,
+    opts: &RevokeOptions,
+) -> Result<(), Error> {
+    let grantee = find_user_or_role(name, UserType::Role)?;
+    schema::user::revoke_privileges(grantee, name, privileges, object, opts)
+}
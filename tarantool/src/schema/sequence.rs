@@ -1,6 +1,107 @@
-use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Error, TarantoolError, TarantoolErrorCode};
+use crate::index::Index;
 use crate::schema;
+use crate::sequence::{Sequence, SequenceOptions};
+use crate::session;
 use crate::space::{Space, SystemSpace};
+use crate::tuple::AsTuple;
+
+/// SequenceDef is tuple, holding sequence metadata in system `_sequence` space.
+#[derive(Serialize, Deserialize, Debug)]
+struct SequenceDef {
+    id: u32,
+    uid: u32,
+    name: String,
+    step: i64,
+    min: i64,
+    max: i64,
+    start: i64,
+    cache: u32,
+    cycle: bool,
+}
+
+impl AsTuple for SequenceDef {}
+
+impl SequenceDef {
+    fn apply(&mut self, opts: &SequenceOptions) {
+        if let Some(step) = opts.step {
+            self.step = step;
+        }
+        if let Some(min) = opts.min {
+            self.min = min;
+        }
+        if let Some(max) = opts.max {
+            self.max = max;
+        }
+        if let Some(start) = opts.start {
+            self.start = start;
+        }
+        if let Some(cache) = opts.cache {
+            self.cache = cache;
+        }
+        if let Some(cycle) = opts.cycle {
+            self.cycle = cycle;
+        }
+    }
+}
+
+/// Create a sequence.
+/// (for details see [box.schema.sequence.create()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema_sequence/create/)).
+///
+/// Returns the id of the new sequence.
+pub fn create_sequence(name: &str, opts: &SequenceOptions) -> Result<u32, Error> {
+    if let Some(seq) = Sequence::find(name)? {
+        return if opts.if_not_exists {
+            Ok(seq.id())
+        } else {
+            set_error!(TarantoolErrorCode::SequenceExists, "Sequence '{}' already exists", name);
+            Err(TarantoolError::last().into())
+        };
+    }
+
+    let step = opts.step.unwrap_or(1);
+    let (min, max) = if step > 0 { (1, i64::MAX) } else { (i64::MIN, -1) };
+    let min = opts.min.unwrap_or(min);
+    let max = opts.max.unwrap_or(max);
+    let start = opts.start.unwrap_or(if step > 0 { min } else { max });
+
+    let mut sys_sequence: Space = SystemSpace::Sequence.into();
+    let id = match sys_sequence.primary_key().max(&())? {
+        Some(t) => t.field::<u32>(0)?.unwrap() + 1,
+        None => 1,
+    };
+    sys_sequence.insert(&SequenceDef {
+        id,
+        uid: session::euid()? as u32,
+        name: name.to_string(),
+        step,
+        min,
+        max,
+        start,
+        cache: opts.cache.unwrap_or(0),
+        cycle: opts.cycle.unwrap_or(false),
+    })?;
+    Ok(id)
+}
+
+/// Alter a sequence.
+/// (for details see [sequence_object:alter()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema_sequence/alter/)).
+pub fn alter_sequence(seq_id: u32, opts: &SequenceOptions) -> Result<(), Error> {
+    let mut sys_sequence: Space = SystemSpace::Sequence.into();
+    let mut def: SequenceDef = match sys_sequence.get(&(seq_id,))? {
+        Some(t) => t.into_struct()?,
+        None => {
+            set_error!(TarantoolErrorCode::NoSuchSequence, "Sequence #{} does not exist", seq_id);
+            return Err(TarantoolError::last().into());
+        }
+    };
+    def.apply(opts);
+    sys_sequence.replace(&def)?;
+    Ok(())
+}
 
 /// Drop existing sequence.
 ///
@@ -16,3 +117,51 @@ pub fn drop_sequence(seq_id: u32) -> Result<(), Error> {
 
     Ok(())
 }
+
+/// Attach a sequence to the primary index of a space.
+///
+/// - `space_id` - ID of existing space.
+/// - `seq_id` - ID of existing sequence.
+/// - `field_index` - number of the field (1-based) which gets the sequence
+///   values, the first field of the primary key by default.
+pub fn set_space_sequence(
+    space_id: u32,
+    seq_id: u32,
+    field_index: Option<u32>,
+) -> Result<(), Error> {
+    let field_index = match field_index {
+        Some(0) => {
+            set_error!(TarantoolErrorCode::IllegalParams, "Field numbers start from 1");
+            return Err(TarantoolError::last().into());
+        }
+        Some(field_index) => field_index,
+        None => Index::new(space_id, 0).parts()?[0].field_index,
+    };
+
+    let mut sys_space_sequence: Space = SystemSpace::SpaceSequence.into();
+    // The field and the path were added to `_space_sequence` in tarantool 2.2
+    let field_count = sys_space_sequence.format()?.len().max(3);
+    let mut tuple = vec![
+        Value::from(space_id),
+        Value::from(seq_id),
+        Value::from(false),
+        Value::from(field_index - 1),
+        Value::from(""),
+    ];
+    tuple.truncate(field_count);
+    sys_space_sequence.replace(&tuple)?;
+    Ok(())
+}
+
+/// Detach the sequence from the primary index of a space. The sequence is
+/// dropped if it was generated automatically for the space.
+pub fn drop_space_sequence(space_id: u32) -> Result<(), Error> {
+    let mut sys_space_sequence: Space = SystemSpace::SpaceSequence.into();
+    if let Some(t) = sys_space_sequence.delete(&(space_id,))? {
+        let is_generated = t.field::<bool>(2)?.unwrap();
+        if is_generated {
+            drop_sequence(t.field::<u32>(1)?.unwrap())?;
+        }
+    }
+    Ok(())
+}
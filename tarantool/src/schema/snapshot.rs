@@ -0,0 +1,534 @@
+//! Schema snapshots
+//!
+//! A [`Snapshot`] describes the user defined part of the database schema:
+//! spaces with their formats and indexes, sequences, functions, users, roles
+//! and their privileges. It can be serialized with `serde`, e.g. to JSON, to
+//! be stored next to the code, compared with the schema of a running instance
+//! or used to set up a new instance:
+//! ```no_run
+//! use tarantool::schema::snapshot::Snapshot;
+//!
+//! let expected: Snapshot = serde_json::from_str(include_str!("schema.json")).unwrap();
+//! let actual = Snapshot::take().unwrap();
+//! for change in actual.diff(&expected) {
+//!     println!("schema drift: {}", change);
+//! }
+//! ```
+//!
+//! The passwords of the users are not included.
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Error, TarantoolError, TarantoolErrorCode};
+use crate::index::{Index, IndexOptions, IndexPart, IndexType, IteratorType};
+use crate::schema;
+use crate::schema::func::{FuncCreateOptions, FuncLanguage};
+use crate::schema::role::RoleCreateOptions;
+use crate::schema::user::{GrantOptions, Object, Privileges, UserCreateOptions};
+use crate::sequence::{Sequence, SequenceOptions};
+use crate::space::{
+    Space, SpaceCreateOptions, SpaceEngineType, SpaceFieldFormat, SpaceMetadata, SystemSpace,
+    SYSTEM_ID_MAX,
+};
+use crate::tuple::Tuple;
+use crate::util::from_schema_name;
+
+/// Description of the database schema. The objects are sorted by name.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub spaces: Vec<SpaceDef>,
+    pub sequences: Vec<SequenceDef>,
+    pub functions: Vec<FuncDef>,
+    /// Users and roles, including the system ones.
+    pub users: Vec<UserDef>,
+}
+
+/// Description of a space. Only the spaces created by the user are included.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpaceDef {
+    pub name: String,
+    pub engine: String,
+    pub field_count: u32,
+    pub is_local: bool,
+    pub is_temporary: bool,
+    pub is_sync: bool,
+    pub format: Vec<SpaceFieldFormat>,
+    /// Indexes in the order of their ids, i.e. the primary key is the first.
+    pub indexes: Vec<IndexDef>,
+    /// Sequence attached to the primary key.
+    pub sequence: Option<SpaceSequenceDef>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexDef {
+    pub name: String,
+    pub index_type: IndexType,
+    pub unique: bool,
+    pub parts: Vec<IndexPart>,
+    /// Name of the function of a functional index.
+    pub func: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpaceSequenceDef {
+    /// Name of the sequence.
+    pub name: String,
+    /// Number of the field (1-based) which gets the sequence values.
+    pub field_index: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SequenceDef {
+    pub name: String,
+    pub step: i64,
+    pub min: i64,
+    pub max: i64,
+    pub start: i64,
+    pub cache: u32,
+    pub cycle: bool,
+}
+
+/// Description of a function. The built-in functions aren't included.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FuncDef {
+    pub name: String,
+    pub language: FuncLanguage,
+    pub body: Option<String>,
+    pub setuid: bool,
+    pub is_deterministic: bool,
+    pub is_sandboxed: bool,
+    pub is_multikey: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UserDef {
+    pub name: String,
+    pub is_role: bool,
+    /// Privileges granted to the user or the role, including the granted
+    /// roles.
+    pub grants: Vec<Grant>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Grant {
+    pub object: Object,
+    /// Bitmask of [`Privileges`].
+    pub privileges: u32,
+}
+
+/// Kind of a schema object, see [`Change`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ObjectKind {
+    Space,
+    Index,
+    Sequence,
+    Function,
+    User,
+    Role,
+}
+
+impl fmt::Display for ObjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Space => "space",
+            Self::Index => "index",
+            Self::Sequence => "sequence",
+            Self::Function => "function",
+            Self::User => "user",
+            Self::Role => "role",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A difference between two snapshots, see [`Snapshot::diff`]. Indexes are
+/// named `space.index`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    Added { kind: ObjectKind, name: String },
+    Removed { kind: ObjectKind, name: String },
+    Changed { kind: ObjectKind, name: String },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Added { kind, name } => write!(f, "added {} '{}'", kind, name),
+            Self::Removed { kind, name } => write!(f, "removed {} '{}'", kind, name),
+            Self::Changed { kind, name } => write!(f, "changed {} '{}'", kind, name),
+        }
+    }
+}
+
+impl Snapshot {
+    /// Reads the schema of the running instance.
+    pub fn take() -> Result<Self, Error> {
+        let mut res = Snapshot {
+            spaces: vec![],
+            sequences: read_sequences()?,
+            functions: read_functions()?,
+            users: read_users()?,
+        };
+        let sys_vspace: Space = SystemSpace::VSpace.into();
+        for t in sys_vspace.select(IteratorType::GT, &(SYSTEM_ID_MAX,))? {
+            res.spaces.push(read_space(t.into_struct()?)?);
+        }
+        res.spaces.sort_by(|l, r| l.name.cmp(&r.name));
+        Ok(res)
+    }
+
+    /// Returns the changes which turn this snapshot into `other`.
+    pub fn diff(&self, other: &Snapshot) -> Vec<Change> {
+        // the indexes are compared separately
+        let without_indexes = |spaces: &[SpaceDef]| -> Vec<SpaceDef> {
+            spaces.iter().map(|s| SpaceDef { indexes: vec![], ..s.clone() }).collect()
+        };
+        let mut res = diff_by_name(
+            ObjectKind::Space,
+            &without_indexes(&self.spaces),
+            &without_indexes(&other.spaces),
+        );
+        // the indexes of the added and removed spaces aren't listed
+        for old in &self.spaces {
+            if let Some(new) = other.spaces.iter().find(|s| s.name == old.name) {
+                let changes = diff_by_name(ObjectKind::Index, &old.indexes, &new.indexes);
+                res.extend(changes.into_iter().map(|c| qualify_index(c, &old.name)));
+            }
+        }
+        res.extend(diff_by_name(ObjectKind::Sequence, &self.sequences, &other.sequences));
+        res.extend(diff_by_name(ObjectKind::Function, &self.functions, &other.functions));
+        let (old_roles, old_users): (Vec<_>, Vec<_>) =
+            self.users.iter().cloned().partition(|u| u.is_role);
+        let (new_roles, new_users): (Vec<_>, Vec<_>) =
+            other.users.iter().cloned().partition(|u| u.is_role);
+        res.extend(diff_by_name(ObjectKind::User, &old_users, &new_users));
+        res.extend(diff_by_name(ObjectKind::Role, &old_roles, &new_roles));
+        res
+    }
+
+    /// Creates the objects of the snapshot which don't exist yet. The
+    /// existing objects aren't changed, use [`diff`](Self::diff) to find out
+    /// whether they match the snapshot.
+    ///
+    /// The objects are owned by the current user. The users are created
+    /// without passwords.
+    pub fn apply(&self) -> Result<(), Error> {
+        for user in &self.users {
+            if user.is_role {
+                let opts = RoleCreateOptions { if_not_exists: true };
+                schema::role::create(&user.name, &opts)?;
+            } else {
+                let opts = UserCreateOptions { if_not_exists: true, ..Default::default() };
+                schema::user::create(&user.name, &opts)?;
+            }
+        }
+
+        for seq in &self.sequences {
+            Sequence::create(&seq.name, &SequenceOptions {
+                start: Some(seq.start),
+                min: Some(seq.min),
+                max: Some(seq.max),
+                step: Some(seq.step),
+                cycle: Some(seq.cycle),
+                cache: Some(seq.cache),
+                if_not_exists: true,
+            })?;
+        }
+
+        for func in &self.functions {
+            schema::func::create(&func.name, &FuncCreateOptions {
+                if_not_exists: true,
+                setuid: func.setuid,
+                language: func.language,
+                body: func.body.clone(),
+                is_deterministic: func.is_deterministic,
+                is_sandboxed: func.is_sandboxed,
+                is_multikey: func.is_multikey,
+                ..Default::default()
+            })?;
+        }
+
+        for space_def in &self.spaces {
+            apply_space(space_def)?;
+        }
+
+        let grant_opts = GrantOptions { if_not_exists: true, ..Default::default() };
+        for user in &self.users {
+            for grant in &user.grants {
+                let privileges = Privileges::from_bits_truncate(grant.privileges);
+                if user.is_role {
+                    schema::role::grant(&user.name, privileges, &grant.object, &grant_opts)?;
+                } else {
+                    schema::user::grant(&user.name, privileges, &grant.object, &grant_opts)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+trait Named {
+    fn name(&self) -> &str;
+}
+
+macro_rules! impl_named {
+    ($($t:ty)*) => {
+        $(
+            impl Named for $t {
+                fn name(&self) -> &str {
+                    &self.name
+                }
+            }
+        )*
+    };
+}
+
+impl_named! { SpaceDef IndexDef SequenceDef FuncDef UserDef }
+
+fn diff_by_name<T: Named + PartialEq>(kind: ObjectKind, old: &[T], new: &[T]) -> Vec<Change> {
+    let old: BTreeMap<_, _> = old.iter().map(|o| (o.name(), o)).collect();
+    let new: BTreeMap<_, _> = new.iter().map(|n| (n.name(), n)).collect();
+    let mut res = vec![];
+    for (&name, o) in &old {
+        match new.get(name) {
+            None => res.push(Change::Removed { kind, name: name.into() }),
+            Some(n) if o != n => res.push(Change::Changed { kind, name: name.into() }),
+            Some(_) => {}
+        }
+    }
+    for &name in new.keys() {
+        if !old.contains_key(name) {
+            res.push(Change::Added { kind, name: name.into() });
+        }
+    }
+    res
+}
+
+fn qualify_index(change: Change, space: &str) -> Change {
+    let qualify = |name: String| format!("{}.{}", space, name);
+    match change {
+        Change::Added { kind, name } => Change::Added { kind, name: qualify(name) },
+        Change::Removed { kind, name } => Change::Removed { kind, name: qualify(name) },
+        Change::Changed { kind, name } => Change::Changed { kind, name: qualify(name) },
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// reading
+////////////////////////////////////////////////////////////////////////////////
+
+fn read_space(meta: SpaceMetadata) -> Result<SpaceDef, Error> {
+    let flag = |name: &str| meta.flags.get(name) == Some(&Value::Bool(true));
+    let mut res = SpaceDef {
+        name: meta.name.clone(),
+        engine: meta.engine.clone(),
+        field_count: meta.field_count,
+        is_local: meta.flags.get("group_id").and_then(Value::as_u64) == Some(1),
+        is_temporary: flag("temporary"),
+        is_sync: flag("is_sync"),
+        format: meta.format.clone(),
+        indexes: vec![],
+        sequence: None,
+    };
+
+    let sys_vindex: Space = SystemSpace::VIndex.into();
+    for t in sys_vindex.select(IteratorType::Eq, &(meta.id,))? {
+        let index_meta = Index::new(meta.id, t.field::<u32>(1)?.unwrap()).meta()?;
+        let func = match index_meta.func {
+            Some(id) => Some(name_by_id(SystemSpace::VFunc, id)?),
+            None => None,
+        };
+        res.indexes.push(IndexDef {
+            name: index_meta.name,
+            index_type: index_meta.index_type,
+            unique: index_meta.unique,
+            parts: index_meta.parts,
+            func,
+        });
+    }
+
+    let sys_space_sequence: Space = SystemSpace::SpaceSequence.into();
+    if let Some(t) = sys_space_sequence.get(&(meta.id,))? {
+        let seq_id = t.field::<u32>(1)?.unwrap();
+        let field_index = match t.field::<u32>(3)? {
+            Some(field) => field + 1,
+            // the older versions only support the first field of the primary key
+            None => res.indexes[0].parts[0].field_index,
+        };
+        res.sequence = Some(SpaceSequenceDef {
+            name: name_by_id(SystemSpace::VSequence, seq_id)?,
+            field_index,
+        });
+    }
+    Ok(res)
+}
+
+fn read_sequences() -> Result<Vec<SequenceDef>, Error> {
+    let sys_vsequence: Space = SystemSpace::VSequence.into();
+    let index = sys_vsequence.index("name").unwrap();
+    let mut res = vec![];
+    for t in index.select(IteratorType::All, &())? {
+        let (_, _, name, step, min, max, start, cache, cycle): (u32, u32, _, _, _, _, _, _, _) =
+            t.decode()?;
+        res.push(SequenceDef { name, step, min, max, start, cache, cycle });
+    }
+    Ok(res)
+}
+
+/// A built-in function which isn't included into the snapshots.
+const BUILTIN_FUNCTION: &str = "box.schema.user.info";
+
+fn read_functions() -> Result<Vec<FuncDef>, Error> {
+    #[derive(Deserialize)]
+    struct FuncOpts {
+        #[serde(default)]
+        is_multikey: bool,
+    }
+
+    let sys_vfunc: Space = SystemSpace::VFunc.into();
+    let index = sys_vfunc.index("name").unwrap();
+    let mut res = vec![];
+    for t in index.select(IteratorType::All, &())? {
+        let name = t.field::<String>(2)?.unwrap();
+        let language = t.field::<String>(4)?.unwrap_or_else(|| "LUA".into());
+        // e.g. the SQL built-in functions
+        let language = match from_schema_name(&language) {
+            Some(language) if name != BUILTIN_FUNCTION => language,
+            _ => continue,
+        };
+        // the fields after `language` were added in tarantool 2.2
+        res.push(FuncDef {
+            name,
+            language,
+            body: t.field::<String>(5)?.filter(|body| !body.is_empty()),
+            setuid: t.field::<u32>(3)?.unwrap_or(0) != 0,
+            is_deterministic: t.field::<bool>(11)?.unwrap_or(false),
+            is_sandboxed: t.field::<bool>(12)?.unwrap_or(false),
+            is_multikey: t.field::<FuncOpts>(15)?.map_or(false, |o| o.is_multikey),
+        });
+    }
+    Ok(res)
+}
+
+fn read_users() -> Result<Vec<UserDef>, Error> {
+    let sys_vuser: Space = SystemSpace::VUser.into();
+    let sys_vpriv: Space = SystemSpace::VPriv.into();
+    let index = sys_vuser.index("name").unwrap();
+    let mut res = vec![];
+    for t in index.select(IteratorType::All, &())? {
+        let id = t.field::<u32>(0)?.unwrap();
+        let mut grants = vec![];
+        let privs: Vec<Tuple> = sys_vpriv.select(IteratorType::Eq, &(id,))?.collect();
+        for p in privs {
+            if let Some(object) = read_object(&p)? {
+                grants.push(Grant { object, privileges: p.field::<u32>(4)?.unwrap() });
+            }
+        }
+        // `_vpriv` is ordered by object ids, which differ between instances
+        // with the same schema.
+        grants.sort_by(|a, b| a.object.cmp(&b.object));
+        res.push(UserDef {
+            name: t.field::<String>(2)?.unwrap(),
+            is_role: t.field::<String>(3)?.as_deref() == Some("role"),
+            grants,
+        });
+    }
+    Ok(res)
+}
+
+/// Returns the object of a `_priv` tuple, or `None` if it's a grant on all the
+/// objects of some type, which isn't supported by [`Object`].
+fn read_object(t: &Tuple) -> Result<Option<Object>, Error> {
+    let object_type = t.field::<String>(2)?.unwrap();
+    let object_id = match t.field::<Value>(3)?.as_ref().and_then(Value::as_u64) {
+        Some(id) => id as u32,
+        None => return Ok(None),
+    };
+    let (sys_space, make): (_, fn(String) -> Object) = match object_type.as_str() {
+        "universe" => return Ok(Some(Object::Universe)),
+        "space" => (SystemSpace::VSpace, Object::Space),
+        "function" => (SystemSpace::VFunc, Object::Function),
+        "sequence" => (SystemSpace::VSequence, Object::Sequence),
+        "role" => (SystemSpace::VUser, Object::Role),
+        "user" => (SystemSpace::VUser, Object::User),
+        _ => return Ok(None),
+    };
+    Ok(Some(make(name_by_id(sys_space, object_id)?)))
+}
+
+/// Returns the name of an object by its id. The name is the 3rd field in all
+/// the system spaces.
+fn name_by_id(sys_space: SystemSpace, id: u32) -> Result<String, Error> {
+    let (code, kind) = match sys_space {
+        SystemSpace::VFunc => (TarantoolErrorCode::NoSuchFunction, "Function"),
+        SystemSpace::VSequence => (TarantoolErrorCode::NoSuchSequence, "Sequence"),
+        SystemSpace::VUser => (TarantoolErrorCode::NoSuchUser, "User"),
+        _ => (TarantoolErrorCode::NoSuchSpace, "Space"),
+    };
+    let space: Space = sys_space.into();
+    match space.get(&(id,))? {
+        Some(t) => Ok(t.field::<String>(2)?.unwrap()),
+        None => {
+            set_error!(code, "{} #{} referenced by the schema does not exist", kind, id);
+            Err(TarantoolError::last().into())
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// applying
+////////////////////////////////////////////////////////////////////////////////
+
+fn apply_space(def: &SpaceDef) -> Result<(), Error> {
+    let engine = match def.engine.as_str() {
+        "memtx" => SpaceEngineType::Memtx,
+        "vinyl" => SpaceEngineType::Vinyl,
+        other => {
+            set_error!(TarantoolErrorCode::NoSuchEngine, "Space engine '{}' does not exist", other);
+            return Err(TarantoolError::last().into());
+        }
+    };
+    let space = Space::create(&def.name, &SpaceCreateOptions {
+        if_not_exists: true,
+        engine: Some(engine),
+        id: None,
+        field_count: Some(def.field_count),
+        user: None,
+        is_local: def.is_local,
+        is_temporary: def.is_temporary,
+        is_sync: def.is_sync,
+        format: Some(def.format.clone()).filter(|f| !f.is_empty()),
+    })?;
+
+    for index in &def.indexes {
+        space.create_index(&index.name, &IndexOptions {
+            index_type: Some(index.index_type),
+            unique: Some(index.unique),
+            if_not_exists: Some(true),
+            parts: Some(index.parts.clone()),
+            func: index.func.clone(),
+            ..Default::default()
+        })?;
+    }
+
+    if let Some(seq) = &def.sequence {
+        let sys_space_sequence: Space = SystemSpace::SpaceSequence.into();
+        if sys_space_sequence.get(&(space.id(),))?.is_none() {
+            let sequence = match Sequence::find(&seq.name)? {
+                Some(sequence) => sequence,
+                None => {
+                    set_error!(
+                        TarantoolErrorCode::NoSuchSequence,
+                        "Sequence '{}' does not exist", seq.name
+                    );
+                    return Err(TarantoolError::last().into());
+                }
+            };
+            space.attach_sequence(&sequence, Some(seq.field_index))?;
+        }
+    }
+    Ok(())
+}
@@ -4,13 +4,15 @@ use serde::Serialize;
 use serde_json::{Map, Number, Value};
 
 use crate::error::{Error, TarantoolError, TarantoolErrorCode};
-use crate::index::IteratorType;
+use crate::index::{Index, IndexFieldType, IteratorType};
 use crate::schema;
 use crate::schema::sequence as schema_seq;
 use crate::session;
-use crate::space::{Space, SystemSpace, SYSTEM_ID_MAX};
-use crate::space::{SpaceCreateOptions, SpaceEngineType};
+use crate::space::{FormatError, Space, SpaceFieldFormat, SpaceFieldType, SystemSpace, SYSTEM_ID_MAX};
+use crate::space::{SpaceAlterOptions, SpaceCreateOptions, SpaceEngineType};
 use crate::tuple::{AsTuple, Tuple};
+use crate::update::UpdateOps;
+use crate::version::{self, Feature};
 
 /// SpaceMetadata is tuple, holding space metadata in system `_space` space.
 #[derive(Serialize, Debug)]
@@ -120,19 +122,16 @@ fn insert_new_space(
     if opts.is_temporary {
         space_opts.insert("temporary".to_string(), Value::Bool(true));
     }
-    // Only for Tarantool version >= 2.6
-    // space_opts.insert("is_sync".to_string(), Value::Bool(opts.is_sync));
+    if opts.is_sync {
+        version::check_supported(Feature::SyncReplication)?;
+        space_opts.insert("is_sync".to_string(), Value::Bool(true));
+    }
 
     // `space_format`
-    let mut space_format = Vec::<Value>::new();
-    if let Some(format) = &opts.format {
-        for ft in format {
-            let mut field_format = Map::<String, Value>::new();
-            field_format.insert("name".to_string(), Value::String(ft.name.clone()));
-            field_format.insert("type".to_string(), Value::String(ft.field_type.to_string()));
-            space_format.push(Value::Object(field_format));
-        }
-    }
+    let space_format = match &opts.format {
+        Some(format) => format_to_values(format),
+        None => Vec::new(),
+    };
 
     let new_space = SpaceMetadata {
         id: id,
@@ -151,22 +150,172 @@ fn insert_new_space(
     }
 }
 
-/// Drop a space.
-pub fn drop_space(space_id: u32) -> Result<(), Error> {
-    // Delete automatically generated sequence.
-    let mut sys_space_sequence: Space = SystemSpace::SpaceSequence.into();
-    let seq_tuple = sys_space_sequence.delete(&(space_id,))?;
-    match seq_tuple {
-        None => (),
-        Some(t) => {
-            let is_generated = t.field::<bool>(2)?.unwrap();
-            if is_generated {
-                let seq_id = t.field::<u32>(1)?.unwrap();
-                schema_seq::drop_sequence(seq_id)?;
+fn format_to_values(format: &[SpaceFieldFormat]) -> Vec<Value> {
+    let mut space_format = Vec::<Value>::new();
+    for ft in format {
+        let mut field_format = Map::<String, Value>::new();
+        field_format.insert("name".to_string(), Value::String(ft.name.clone()));
+        field_format.insert("type".to_string(), Value::String(ft.field_type.to_string()));
+        if ft.is_nullable {
+            field_format.insert("is_nullable".to_string(), Value::Bool(true));
+        }
+        space_format.push(Value::Object(field_format));
+    }
+    space_format
+}
+
+/// A tuple in `_space` system space.
+type SpaceDef = (u32, u32, String, String, u32, Map<String, Value>, Vec<Value>);
+
+/// Alter a space.
+/// (for details see [space_object:alter()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_space/alter/)).
+///
+/// - `space_id` - ID of the space to alter.
+/// - `opts` - see SpaceAlterOptions struct.
+pub fn alter_space(space_id: u32, opts: &SpaceAlterOptions) -> Result<(), Error> {
+    let mut sys_space: Space = SystemSpace::Space.into();
+    let tuple = match sys_space.get(&(space_id,))? {
+        Some(tuple) => tuple,
+        None => {
+            set_error!(TarantoolErrorCode::NoSuchSpace, "Space #{} does not exist", space_id);
+            return Err(TarantoolError::last().into());
+        }
+    };
+    let (id, mut uid, mut name, _, mut field_count, mut options, mut format): SpaceDef =
+        tuple.decode()?;
+
+    if let Some(new_name) = &opts.name {
+        name = new_name.clone();
+    }
+    if let Some(user) = &opts.user {
+        uid = match schema::resolve_user_or_role(user.as_str())? {
+            Some(uid) => uid,
+            None => {
+                set_error!(TarantoolErrorCode::NoSuchUser, "{}", user.as_str());
+                return Err(TarantoolError::last().into());
+            }
+        };
+    }
+    if let Some(count) = opts.field_count {
+        field_count = count;
+    }
+    if let Some(is_temporary) = opts.is_temporary {
+        if is_temporary {
+            options.insert("temporary".to_string(), Value::Bool(true));
+        } else {
+            options.remove("temporary");
+        }
+    }
+    if let Some(is_sync) = opts.is_sync {
+        if is_sync {
+            version::check_supported(Feature::SyncReplication)?;
+            options.insert("is_sync".to_string(), Value::Bool(true));
+        } else {
+            options.remove("is_sync");
+        }
+    }
+    if let Some(new_format) = &opts.format {
+        check_format(space_id, new_format)?;
+        format = format_to_values(new_format);
+    }
+
+    let ops = UpdateOps::new()
+        .assign(1, uid)
+        .assign(2, name)
+        .assign(4, field_count)
+        .assign(5, options)
+        .assign(6, format);
+    if let Err(e) = sys_space.update(&(id,), &ops) {
+        // tarantool checks the tuples, but doesn't tell which field is missing
+        if let Some(new_format) = &opts.format {
+            check_tuples(space_id, new_format)?;
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Check that the format is compatible with the indexes of the space.
+fn check_format(space_id: u32, format: &[SpaceFieldFormat]) -> Result<(), Error> {
+    let sys_vindex: Space = SystemSpace::VIndex.into();
+    for t in sys_vindex.select(IteratorType::Eq, &(space_id,))? {
+        let index_id = t.field::<u32>(1)?.unwrap();
+        let meta = Index::new(space_id, index_id).meta()?;
+        for part in &meta.parts {
+            let field = match format.get(part.field_index as usize - 1) {
+                Some(field) => field,
+                // the format may be shorter than the tuples
+                None => continue,
+            };
+            let part_type = index_field_type_to_space(part.field_type);
+            let types_match = field.field_type.contains(part_type)
+                || part_type.contains(field.field_type);
+            // a part with a JSON path only restricts a nested value
+            if part.path.is_none() && !types_match {
+                return Err(FormatError::IndexedFieldType {
+                    field: part.field_index,
+                    field_type: field.field_type,
+                    index: meta.name.clone(),
+                    part_type: part.field_type,
+                }.into());
+            }
+            if part.path.is_none() && field.is_nullable && part.is_nullable == Some(false) {
+                return Err(FormatError::NullableIndexedField {
+                    field: part.field_index,
+                    index: meta.name.clone(),
+                }.into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that the existing tuples contain all the non-nullable fields of the
+/// format. It's a full scan, so it's only used to explain an error.
+fn check_tuples(space_id: u32, format: &[SpaceFieldFormat]) -> Result<(), Error> {
+    let required_len = format.iter().rposition(|f| !f.is_nullable).map_or(0, |i| i + 1);
+    let has_primary_key = Index::new(space_id, 0).meta().is_ok();
+    if required_len > 0 && has_primary_key {
+        for t in Index::new(space_id, 0).select(IteratorType::All, &())? {
+            let len = t.len() as usize;
+            if len < required_len {
+                let missing = format.iter()
+                    .enumerate()
+                    .skip(len)
+                    .find(|(_, f)| !f.is_nullable)
+                    .map_or(required_len, |(i, _)| i + 1);
+                return Err(FormatError::MissingField {
+                    field: missing as u32,
+                    tuple_len: len,
+                }.into());
             }
         }
     }
 
+    Ok(())
+}
+
+fn index_field_type_to_space(field_type: IndexFieldType) -> SpaceFieldType {
+    match field_type {
+        IndexFieldType::Unsigned => SpaceFieldType::Unsigned,
+        IndexFieldType::String => SpaceFieldType::String,
+        IndexFieldType::Integer => SpaceFieldType::Integer,
+        IndexFieldType::Number => SpaceFieldType::Number,
+        IndexFieldType::Double => SpaceFieldType::Double,
+        IndexFieldType::Decimal => SpaceFieldType::Decimal,
+        IndexFieldType::Boolean => SpaceFieldType::Boolean,
+        IndexFieldType::Varbinary => SpaceFieldType::Varbinary,
+        IndexFieldType::Uuid => SpaceFieldType::Uuid,
+        IndexFieldType::Array => SpaceFieldType::Array,
+        IndexFieldType::Scalar => SpaceFieldType::Scalar,
+    }
+}
+
+/// Drop a space.
+pub fn drop_space(space_id: u32) -> Result<(), Error> {
+    // Delete automatically generated sequence.
+    schema_seq::drop_space_sequence(space_id)?;
+
     // Remove from _trigger.
     let mut sys_trigger: Space = SystemSpace::Trigger.into();
     let sys_space_idx = sys_trigger.index("space_id").unwrap();
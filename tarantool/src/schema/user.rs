@@ -0,0 +1,555 @@
+//! Users and privileges
+//!
+//! The functions of this module work directly with the `_user` and `_priv`
+//! system spaces, so that the access can be configured from the Rust code,
+//! e.g. on the instance startup:
+//! ```no_run
+//! use tarantool::schema::user::{self, GrantOptions, Object, Privileges, UserCreateOptions};
+//!
+//! user::create("alice", &UserCreateOptions {
+//!     password: Some("secret".into()),
+//!     if_not_exists: true,
+//! }).unwrap();
+//! user::grant(
+//!     "alice",
+//!     Privileges::READ | Privileges::WRITE,
+//!     &Object::Space("users".into()),
+//!     &GrantOptions { if_not_exists: true, ..Default::default() },
+//! ).unwrap();
+//! ```
+//!
+//! A role is granted to a user as the `EXECUTE` privilege on the role object:
+//! `user::grant("alice", Privileges::EXECUTE, &Object::Role("dba".into()), &opts)`.
+//!
+//! See also:
+//! - [Lua reference: box.schema.user](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/#box-schema-user-create)
+//! - [Access control](https://www.tarantool.io/en/doc/latest/book/admin/access_control/)
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha1::{Digest, Sha1};
+
+use crate::error::{Error, TarantoolError, TarantoolErrorCode};
+use crate::index::IteratorType;
+use crate::schema;
+use crate::session;
+use crate::space::{Privilege, Space, SpaceFieldType, SystemSpace};
+use crate::tuple::Tuple;
+use crate::update::UpdateOps;
+
+/// Id of the `guest` user.
+pub const GUEST_ID: u32 = 0;
+/// Id of the `admin` user.
+pub const ADMIN_ID: u32 = 1;
+/// Id of the `public` role.
+pub const PUBLIC_ID: u32 = 2;
+/// Id of the `super` role.
+pub const SUPER_ID: u32 = 31;
+/// The ids up to this one are reserved for the system users and roles.
+pub const SYSTEM_USER_ID_MAX: u32 = 31;
+
+bitflags! {
+    /// Privileges stored as a bitmask in the `_priv` system space.
+    pub struct Privileges: u32 {
+        const READ = 1;
+        const WRITE = 2;
+        const EXECUTE = 4;
+        const SESSION = 8;
+        const USAGE = 16;
+        const CREATE = 32;
+        const DROP = 64;
+        const ALTER = 128;
+        const REFERENCE = 256;
+        const TRIGGER = 512;
+        const INSERT = 1024;
+        const UPDATE = 2048;
+        const DELETE = 4096;
+        const GRANT = 8192;
+        const REVOKE = 16384;
+        /// All the privileges, including the ones added in the future
+        /// versions of tarantool.
+        const ALL = u32::MAX;
+    }
+}
+
+const PRIVILEGE_NAMES: [(Privileges, &str); 15] = [
+    (Privileges::READ, "read"),
+    (Privileges::WRITE, "write"),
+    (Privileges::EXECUTE, "execute"),
+    (Privileges::SESSION, "session"),
+    (Privileges::USAGE, "usage"),
+    (Privileges::CREATE, "create"),
+    (Privileges::DROP, "drop"),
+    (Privileges::ALTER, "alter"),
+    (Privileges::REFERENCE, "reference"),
+    (Privileges::TRIGGER, "trigger"),
+    (Privileges::INSERT, "insert"),
+    (Privileges::UPDATE, "update"),
+    (Privileges::DELETE, "delete"),
+    (Privileges::GRANT, "grant"),
+    (Privileges::REVOKE, "revoke"),
+];
+
+/// Formats the privileges the same way as tarantool does, e.g.
+/// `read,write`.
+impl fmt::Display for Privileges {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.contains(Self::ALL) {
+            return f.write_str("all")
+        }
+        let names: Vec<_> = PRIVILEGE_NAMES.iter()
+            .filter(|(p, _)| self.contains(*p))
+            .map(|(_, name)| *name)
+            .collect();
+        f.write_str(&names.join(","))
+    }
+}
+
+/// An object on which the privileges are granted.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Object {
+    Universe,
+    Space(String),
+    Function(String),
+    Sequence(String),
+    Role(String),
+    User(String),
+}
+
+impl Object {
+    /// Object type as stored in `_priv`.
+    pub fn object_type(&self) -> &'static str {
+        match self {
+            Self::Universe => "universe",
+            Self::Space(_) => "space",
+            Self::Function(_) => "function",
+            Self::Sequence(_) => "sequence",
+            Self::Role(_) => "role",
+            Self::User(_) => "user",
+        }
+    }
+
+    /// Resolves the object id by its name.
+    fn resolve_id(&self) -> Result<u32, Error> {
+        let (sys_space, name, code, what) = match self {
+            Self::Universe => return Ok(0),
+            Self::Space(name) => {
+                (SystemSpace::VSpace, name, TarantoolErrorCode::NoSuchSpace, "Space")
+            }
+            Self::Function(name) => {
+                (SystemSpace::VFunc, name, TarantoolErrorCode::NoSuchFunction, "Function")
+            }
+            Self::Sequence(name) => {
+                (SystemSpace::VSequence, name, TarantoolErrorCode::NoSuchSequence, "Sequence")
+            }
+            Self::Role(name) => return find_user_or_role(name, UserType::Role),
+            Self::User(name) => return find_user_or_role(name, UserType::User),
+        };
+        let space: Space = sys_space.into();
+        match space.index("name").unwrap().get(&(name,))? {
+            Some(t) => Ok(t.field::<u32>(0)?.unwrap()),
+            None => {
+                set_error!(code, "{} '{}' does not exist", what, name);
+                Err(TarantoolError::last().into())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Universe => f.write_str("universe"),
+            Self::Space(name)
+            | Self::Function(name)
+            | Self::Sequence(name)
+            | Self::Role(name)
+            | Self::User(name) => write!(f, "{} '{}'", self.object_type(), name),
+        }
+    }
+}
+
+/// Options of [`create`].
+#[derive(Clone, Debug, Default)]
+pub struct UserCreateOptions {
+    /// The password. If not set, the user can only connect without
+    /// authentication, i.e. in the same way as `guest`.
+    pub password: Option<String>,
+    pub if_not_exists: bool,
+}
+
+/// Options of [`grant`].
+#[derive(Clone, Debug, Default)]
+pub struct GrantOptions {
+    /// Name of the user on whose behalf the privileges are granted. The
+    /// current session's user is used by default.
+    pub grantor: Option<String>,
+    /// Don't fail if the privileges are already granted.
+    pub if_not_exists: bool,
+}
+
+/// Options of [`revoke`].
+#[derive(Clone, Debug, Default)]
+pub struct RevokeOptions {
+    /// Don't fail if the privileges aren't granted.
+    pub if_exists: bool,
+}
+
+/// Create a user.
+/// (for details see [box.schema.user.create()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/user_create/)).
+///
+/// The user is granted the `session` and `usage` privileges on the
+/// universe, so that it can connect to the instance.
+///
+/// Returns the id of the new user.
+pub fn create(name: &str, opts: &UserCreateOptions) -> Result<u32, Error> {
+    if let Some(id) = check_exists(name, UserType::User, opts.if_not_exists)? {
+        return Ok(id);
+    }
+    let auth = match &opts.password {
+        Some(password) => chap_sha1_auth(password),
+        None => Map::new(),
+    };
+    let id = insert_user(name, UserType::User, auth)?;
+    grant(
+        name,
+        Privileges::SESSION | Privileges::USAGE,
+        &Object::Universe,
+        &GrantOptions { if_not_exists: true, ..Default::default() },
+    )?;
+    Ok(id)
+}
+
+/// Drop a user together with the objects it owns and its privileges.
+/// (for details see [box.schema.user.drop()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/user_drop/)).
+pub fn drop(name: &str) -> Result<(), Error> {
+    let id = find_user_or_role(name, UserType::User)?;
+    drop_user_or_role(id, name)
+}
+
+/// Set the password of a user.
+/// (for details see [box.schema.user.passwd()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/user_passwd/)).
+pub fn passwd(name: &str, password: &str) -> Result<(), Error> {
+    let id = find_user_or_role(name, UserType::User)?;
+    if id == GUEST_ID {
+        set_error!(
+            TarantoolErrorCode::GuestUserPassword,
+            "Setting password for guest user has no effect"
+        );
+        return Err(TarantoolError::last().into());
+    }
+    let mut sys_user: Space = SystemSpace::User.into();
+    sys_user.update(&(id,), &UpdateOps::new().assign(4, chap_sha1_auth(password)))?;
+    Ok(())
+}
+
+/// Grant `privileges` on `object` to a user.
+/// (for details see [box.schema.user.grant()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/user_grant/)).
+///
+/// The privileges are added to the ones already granted on the same object.
+pub fn grant(
+    name: &str,
+    privileges: Privileges,
+    object: &Object,
+    opts: &GrantOptions,
+) -> Result<(), Error> {
+    let grantee = find_user_or_role(name, UserType::User)?;
+    grant_privileges(grantee, name, privileges, object, opts)
+}
+
+/// Revoke `privileges` on `object` from a user.
+/// (for details see [box.schema.user.revoke()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/user_revoke/)).
+pub fn revoke(
+    name: &str,
+    privileges: Privileges,
+    object: &Object,
+    opts: &RevokeOptions,
+) -> Result<(), Error> {
+    let grantee = find_user_or_role(name, UserType::User)?;
+    revoke_privileges(grantee, name, privileges, object, opts)
+}
+
+/// Returns the privileges granted to a user or a role on `object`.
+pub fn privileges(name: &str, object: &Object) -> Result<Privileges, Error> {
+    let grantee = match schema::resolve_user_or_role(name)? {
+        Some(id) => id,
+        None => {
+            set_error!(TarantoolErrorCode::NoSuchUser, "User '{}' is not found", name);
+            return Err(TarantoolError::last().into());
+        }
+    };
+    let sys_vpriv: Space = SystemSpace::VPriv.into();
+    let key = (grantee, object.object_type(), object.resolve_id()?);
+    Ok(match sys_vpriv.get(&key)? {
+        Some(t) => Privileges::from_bits_truncate(t.field::<u32>(4)?.unwrap()),
+        None => Privileges::empty(),
+    })
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// implementation shared with schema::role
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UserType {
+    User,
+    Role,
+}
+
+impl UserType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Role => "role",
+        }
+    }
+}
+
+/// Returns the id of the user or the role `name`, or the `NoSuchUser` /
+/// `NoSuchRole` error if there's none of the expected type.
+pub(crate) fn find_user_or_role(name: &str, user_type: UserType) -> Result<u32, Error> {
+    let sys_vuser: Space = SystemSpace::VUser.into();
+    if let Some(t) = sys_vuser.index("name").unwrap().get(&(name,))? {
+        if t.field::<String>(3)?.as_deref() == Some(user_type.as_str()) {
+            return Ok(t.field::<u32>(0)?.unwrap());
+        }
+    }
+    match user_type {
+        UserType::User => {
+            set_error!(TarantoolErrorCode::NoSuchUser, "User '{}' is not found", name)
+        }
+        UserType::Role => {
+            set_error!(TarantoolErrorCode::NoSuchRole, "Role '{}' is not found", name)
+        }
+    };
+    Err(TarantoolError::last().into())
+}
+
+/// Checks if a user or a role `name` exists before creating one of
+/// `user_type`. Returns the id if it exists and is of `user_type`, but only if
+/// `if_not_exists` is set, otherwise the `UserExists` / `RoleExists` error
+/// matching the type of the existing one is returned.
+pub(crate) fn check_exists(
+    name: &str,
+    user_type: UserType,
+    if_not_exists: bool,
+) -> Result<Option<u32>, Error> {
+    let sys_vuser: Space = SystemSpace::VUser.into();
+    let t = match sys_vuser.index("name").unwrap().get(&(name,))? {
+        Some(t) => t,
+        None => return Ok(None),
+    };
+    let is_role = t.field::<String>(3)?.as_deref() == Some(UserType::Role.as_str());
+    if if_not_exists && is_role == matches!(user_type, UserType::Role) {
+        return Ok(Some(t.field::<u32>(0)?.unwrap()));
+    }
+    if is_role {
+        set_error!(TarantoolErrorCode::RoleExists, "Role '{}' already exists", name);
+    } else {
+        set_error!(TarantoolErrorCode::UserExists, "User '{}' already exists", name);
+    }
+    Err(TarantoolError::last().into())
+}
+
+/// Inserts a new tuple into `_user`. Returns the id of the user or the role.
+pub(crate) fn insert_user(
+    name: &str,
+    user_type: UserType,
+    auth: Map<String, Value>,
+) -> Result<u32, Error> {
+    let mut sys_user: Space = SystemSpace::User.into();
+    let id = match sys_user.primary_key().max(&())? {
+        Some(t) => t.field::<u32>(0)?.unwrap() + 1,
+        None => SYSTEM_USER_ID_MAX + 1,
+    };
+    let owner = session::euid()? as u32;
+    let mut tuple = vec![
+        Value::from(id),
+        Value::from(owner),
+        Value::from(name),
+        Value::from(user_type.as_str()),
+        Value::Object(auth),
+    ];
+    // Newer versions of tarantool have more fields in `_user`, e.g.
+    // `auth_history` and `last_modified`, which must be set as well
+    for field in sys_user.format()?.iter().skip(tuple.len()) {
+        tuple.push(match field.field_type {
+            SpaceFieldType::Array => Value::Array(vec![]),
+            SpaceFieldType::Map => Value::Object(Map::new()),
+            SpaceFieldType::Unsigned | SpaceFieldType::Integer | SpaceFieldType::Number => {
+                Value::from(0)
+            }
+            SpaceFieldType::String => Value::from(""),
+            _ => Value::Null,
+        });
+    }
+    sys_user.insert(&tuple)?;
+    Ok(id)
+}
+
+/// Drops the user or the role `id`, the objects it owns and the privileges
+/// granted to it.
+pub(crate) fn drop_user_or_role(id: u32, name: &str) -> Result<(), Error> {
+    if id <= SYSTEM_USER_ID_MAX {
+        set_error!(
+            TarantoolErrorCode::DropUser,
+            "Failed to drop user or role '{}': the user or the role is a system", name
+        );
+        return Err(TarantoolError::last().into());
+    }
+
+    // Drop the owned objects
+    let sys_vspace: Space = SystemSpace::VSpace.into();
+    for t in owned_by(&sys_vspace, id)? {
+        schema::space::drop_space(t.field::<u32>(0)?.unwrap())?;
+    }
+    let sys_vfunc: Space = SystemSpace::VFunc.into();
+    for t in owned_by(&sys_vfunc, id)? {
+        schema::func::drop_func(t.field::<u32>(0)?.unwrap())?;
+    }
+    let sys_vsequence: Space = SystemSpace::VSequence.into();
+    for t in owned_by(&sys_vsequence, id)? {
+        schema::sequence::drop_sequence(t.field::<u32>(0)?.unwrap())?;
+    }
+
+    // If it's a role, revoke it from whoever it was granted to
+    schema::revoke_object_privileges("role", id)?;
+
+    // Revoke the privileges granted to it
+    let sys_vpriv: Space = SystemSpace::VPriv.into();
+    let mut sys_priv: Space = SystemSpace::Priv.into();
+    let privs: Vec<Tuple> = sys_vpriv.select(IteratorType::Eq, &(id,))?.collect();
+    for t in privs {
+        let object_type = t.field::<String>(2)?.unwrap();
+        let object_id = t.field::<u32>(3)?.unwrap();
+        sys_priv.delete(&(id, object_type, object_id))?;
+    }
+
+    let mut sys_user: Space = SystemSpace::User.into();
+    sys_user.delete(&(id,))?;
+    Ok(())
+}
+
+fn owned_by(sys_space: &Space, uid: u32) -> Result<Vec<Tuple>, Error> {
+    let index = sys_space.index("owner").unwrap();
+    Ok(index.select(IteratorType::Eq, &(uid,))?.collect())
+}
+
+pub(crate) fn grant_privileges(
+    grantee: u32,
+    grantee_name: &str,
+    privileges: Privileges,
+    object: &Object,
+    opts: &GrantOptions,
+) -> Result<(), Error> {
+    let grantor = match &opts.grantor {
+        Some(name) => find_user_or_role(name, UserType::User)?,
+        None => session::euid()? as u32,
+    };
+    let object_type = object.object_type();
+    let object_id = object.resolve_id()?;
+    let mut sys_priv: Space = SystemSpace::Priv.into();
+    let old = match sys_priv.get(&(grantee, object_type, object_id))? {
+        Some(t) => Privileges::from_bits_truncate(t.field::<u32>(4)?.unwrap()),
+        None => Privileges::empty(),
+    };
+    let new = old | privileges;
+    if new == old {
+        if opts.if_not_exists {
+            return Ok(());
+        }
+        if is_role_grant(object, privileges) {
+            set_error!(
+                TarantoolErrorCode::RoleGranted,
+                "User '{}' already has role '{}'", grantee_name, object_name(object)
+            );
+        } else {
+            set_error!(
+                TarantoolErrorCode::PrivGranted,
+                "User '{}' already has {} access on {}", grantee_name, privileges, object
+            );
+        }
+        return Err(TarantoolError::last().into());
+    }
+    sys_priv.replace(&Privilege {
+        grantor,
+        grantee,
+        object_type: object_type.into(),
+        object_id,
+        privilege: new.bits(),
+    })?;
+    Ok(())
+}
+
+pub(crate) fn revoke_privileges(
+    grantee: u32,
+    grantee_name: &str,
+    privileges: Privileges,
+    object: &Object,
+    opts: &RevokeOptions,
+) -> Result<(), Error> {
+    let object_type = object.object_type();
+    let object_id = object.resolve_id()?;
+    let mut sys_priv: Space = SystemSpace::Priv.into();
+    let tuple = sys_priv.get(&(grantee, object_type, object_id))?;
+    let old = match &tuple {
+        Some(t) => Privileges::from_bits_truncate(t.field::<u32>(4)?.unwrap()),
+        None => Privileges::empty(),
+    };
+    if !old.intersects(privileges) {
+        if opts.if_exists {
+            return Ok(());
+        }
+        if is_role_grant(object, privileges) {
+            set_error!(
+                TarantoolErrorCode::RoleNotGranted,
+                "User '{}' does not have role '{}'", grantee_name, object_name(object)
+            );
+        } else {
+            set_error!(
+                TarantoolErrorCode::PrivNotGranted,
+                "User '{}' does not have {} access on {}", grantee_name, privileges, object
+            );
+        }
+        return Err(TarantoolError::last().into());
+    }
+    let new = old - privileges;
+    if new.is_empty() {
+        sys_priv.delete(&(grantee, object_type, object_id))?;
+    } else {
+        let grantor = tuple.unwrap().field::<u32>(0)?.unwrap();
+        sys_priv.replace(&Privilege {
+            grantor,
+            grantee,
+            object_type: object_type.into(),
+            object_id,
+            privilege: new.bits(),
+        })?;
+    }
+    Ok(())
+}
+
+fn is_role_grant(object: &Object, privileges: Privileges) -> bool {
+    matches!(object, Object::Role(_)) && privileges == Privileges::EXECUTE
+}
+
+fn object_name(object: &Object) -> &str {
+    match object {
+        Object::Universe => "",
+        Object::Space(name)
+        | Object::Function(name)
+        | Object::Sequence(name)
+        | Object::Role(name)
+        | Object::User(name) => name,
+    }
+}
+
+/// Returns the `_user` auth field for the `chap-sha1` authentication
+/// method: `{"chap-sha1": base64(sha1(sha1(password)))}`.
+fn chap_sha1_auth(password: &str) -> Map<String, Value> {
+    let hash = Sha1::digest(&Sha1::digest(password.as_bytes()));
+    let mut auth = Map::new();
+    auth.insert("chap-sha1".into(), Value::from(base64::encode(hash)));
+    auth
+}
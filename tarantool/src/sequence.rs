@@ -1,15 +1,71 @@
 //! Box: sequences
+//!
+//! See also:
+//! - [Lua reference: Sequences](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema_sequence/)
 use crate::error::{Error, TarantoolError};
 use crate::ffi::tarantool as ffi;
 use crate::space::{Space, SystemSpace};
 use crate::tuple::AsTuple;
 
+/// Options for [Sequence::create](struct.Sequence.html#method.create) and
+/// [Sequence::alter](struct.Sequence.html#method.alter).
+/// (for details see [box.schema.sequence.create()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema_sequence/create/)).
+///
+/// When creating a sequence the options which aren't specified get the
+/// default values. When altering a sequence only the specified options are
+/// changed.
+#[derive(Clone, Debug, Default)]
+pub struct SequenceOptions {
+    /// The first value, `min` by default (or `max` for a descending sequence).
+    pub start: Option<i64>,
+    /// The minimum value, 1 by default (or `i64::MIN` for a descending
+    /// sequence).
+    pub min: Option<i64>,
+    /// The maximum value, `i64::MAX` by default (or -1 for a descending
+    /// sequence).
+    pub max: Option<i64>,
+    /// The increment, 1 by default. A negative step makes the sequence
+    /// descending.
+    pub step: Option<i64>,
+    /// Whether to start over from `min` (or `max`) after `max` (or `min`) is
+    /// reached, instead of failing. `false` by default.
+    pub cycle: Option<bool>,
+    /// Number of values to preallocate. Not used by tarantool at the moment.
+    pub cache: Option<u32>,
+    /// Don't fail if the sequence already exists. Only used by `create`.
+    pub if_not_exists: bool,
+}
+
 /// A sequence is a generator of ordered integer values.
 pub struct Sequence {
     seq_id: u32,
 }
 
 impl Sequence {
+    /// Create a sequence.
+    /// (for details see [box.schema.sequence.create()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema_sequence/create/)).
+    ///
+    /// - `name` - name of the sequence, which should conform to the rules for object names.
+    /// - `opts` - see SequenceOptions struct.
+    #[cfg(feature = "schema")]
+    pub fn create(name: &str, opts: &SequenceOptions) -> Result<Self, Error> {
+        let seq_id = crate::schema::sequence::create_sequence(name, opts)?;
+        Ok(Sequence { seq_id })
+    }
+
+    /// Change the options of the sequence. The current value isn't changed.
+    /// (for details see [sequence_object:alter()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema_sequence/alter/)).
+    #[cfg(feature = "schema")]
+    pub fn alter(&mut self, opts: &SequenceOptions) -> Result<(), Error> {
+        crate::schema::sequence::alter_sequence(self.seq_id, opts)
+    }
+
+    /// Drop the sequence.
+    #[cfg(feature = "schema")]
+    pub fn drop(&self) -> Result<(), Error> {
+        crate::schema::sequence::drop_sequence(self.seq_id)
+    }
+
     /// Find sequence by name.
     pub fn find(name: &str) -> Result<Option<Self>, Error> {
         #[derive(Serialize, Deserialize)]
@@ -30,6 +86,23 @@ impl Sequence {
         })
     }
 
+    /// Returns the id of the sequence.
+    pub fn id(&self) -> u32 {
+        self.seq_id
+    }
+
+    /// Returns the last value returned by [next](#method.next), or `None`
+    /// if the sequence hasn't been used yet or has been [reset](#method.reset).
+    ///
+    /// Unlike `next`, doesn't change the state of the sequence.
+    pub fn current(&self) -> Result<Option<i64>, Error> {
+        let sys_sequence_data: Space = SystemSpace::SequenceData.into();
+        Ok(match sys_sequence_data.get(&(self.seq_id,))? {
+            Some(t) => t.field::<i64>(1)?,
+            None => None,
+        })
+    }
+
     /// Generate the next value and return it.
     ///
     /// The generation algorithm is simple:
@@ -6,18 +6,27 @@
 //! See also:
 //! - [Lua reference: Submodule box.space](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_space/)
 //! - [C API reference: Module box](https://www.tarantool.io/en/doc/latest/dev_guide/reference_capi/box/)
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_int};
+use std::ptr::NonNull;
 
 use num_traits::ToPrimitive;
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Serialize, Serializer};
 use serde_json::{Map, Value};
 
-use crate::error::{Error, TarantoolError};
-use crate::ffi::tarantool as ffi;
-use crate::index::{Index, IndexIterator, IteratorType};
+use crate::c_ptr;
+use crate::tlua::{self as tlua, AsLua};
+use crate::error::{Error, TarantoolError, TarantoolErrorCode};
+use crate::ffi::{lua, tarantool as ffi};
+use crate::fiber::panic;
+use crate::index::{Index, IndexFieldType, IndexIterator, IteratorType};
+#[cfg(feature = "schema")]
+use crate::sequence::Sequence;
 use crate::tuple::{AsTuple, Tuple};
 use crate::tuple_from_box_api;
+use crate::util::deserialize_schema_name;
 
 /// End of the reserved range of system spaces.
 pub const SYSTEM_ID_MAX: u32 = 511;
@@ -144,11 +153,50 @@ impl Default for SpaceCreateOptions {
     }
 }
 
-#[derive(Clone, Debug, Serialize)]
+/// Options for [Space::alter](struct.Space.html#method.alter). Only the
+/// specified options are changed.
+#[derive(Clone, Debug, Default)]
+pub struct SpaceAlterOptions {
+    /// New name of the space.
+    pub name: Option<String>,
+    /// New owner of the space.
+    pub user: Option<String>,
+    pub field_count: Option<u32>,
+    pub is_temporary: Option<bool>,
+    /// Only for Tarantool >= 2.6, see
+    /// [Feature::SyncReplication](crate::version::Feature::SyncReplication).
+    pub is_sync: Option<bool>,
+    pub format: Option<Vec<SpaceFieldFormat>>,
+}
+
+/// Reasons why a space format is incompatible with the space, see
+/// [Space::alter](struct.Space.html#method.alter).
+///
+/// Field numbers are 1-based.
+#[derive(Debug, thiserror::Error)]
+pub enum FormatError {
+    #[error("field {field} has type {field_type}, which is incompatible with type {part_type:?} of index '{index}'")]
+    IndexedFieldType {
+        field: u32,
+        field_type: SpaceFieldType,
+        index: String,
+        part_type: IndexFieldType,
+    },
+
+    #[error("field {field} is nullable, but it's not nullable in index '{index}'")]
+    NullableIndexedField { field: u32, index: String },
+
+    #[error("field {field} is not nullable, but there are tuples without it (e.g. {tuple_len} fields long)")]
+    MissingField { field: u32, tuple_len: usize },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SpaceFieldFormat {
     pub name: String,
-    #[serde(alias = "type")]
+    #[serde(alias = "type", default, deserialize_with = "deserialize_schema_name")]
     pub field_type: SpaceFieldType,
+    #[serde(default)]
+    pub is_nullable: bool,
 }
 
 impl SpaceFieldFormat {
@@ -156,14 +204,18 @@ impl SpaceFieldFormat {
         return SpaceFieldFormat {
             name: name.to_string(),
             field_type: ft,
+            is_nullable: false,
         };
     }
 }
 
-#[derive(Copy, Clone, Debug, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SpaceFieldType {
     Any,
+    #[serde(alias = "num")]
     Unsigned,
+    #[serde(alias = "str")]
     String,
     Number,
     Double,
@@ -173,6 +225,16 @@ pub enum SpaceFieldType {
     Uuid,
     Array,
     Scalar,
+    Varbinary,
+    Map,
+    Datetime,
+    Interval,
+}
+
+impl Default for SpaceFieldType {
+    fn default() -> Self {
+        Self::Any
+    }
 }
 
 impl fmt::Display for SpaceFieldType {
@@ -181,6 +243,23 @@ impl fmt::Display for SpaceFieldType {
     }
 }
 
+impl SpaceFieldType {
+    /// Returns `true` if all the values of type `other` are also of this type,
+    /// e.g. `Number` contains `Unsigned`.
+    pub fn contains(self, other: Self) -> bool {
+        use SpaceFieldType::*;
+        match (self, other) {
+            _ if self == other => true,
+            (Any, _) => true,
+            (Scalar, Array) | (Scalar, Map) | (Scalar, Any) => false,
+            (Scalar, _) => true,
+            (Number, Unsigned) | (Number, Integer) | (Number, Double) | (Number, Decimal) => true,
+            (Integer, Unsigned) => true,
+            _ => false,
+        }
+    }
+}
+
 #[derive(Serialize, Debug)]
 pub struct FuncMetadata {
     pub id: u32,
@@ -217,6 +296,31 @@ pub struct Privilege {
 
 impl AsTuple for Privilege {}
 
+/// Space metadata, read from the `_vspace` system space.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SpaceMetadata {
+    pub id: u32,
+    pub owner: u32,
+    pub name: String,
+    pub engine: String,
+    pub field_count: u32,
+    pub flags: Map<String, Value>,
+    pub format: Vec<SpaceFieldFormat>,
+}
+
+impl SpaceMetadata {
+    fn fetch(id: u32) -> Result<Self, Error> {
+        let sys_space: Space = SystemSpace::VSpace.into();
+        match sys_space.get(&(id,))? {
+            Some(tuple) => tuple.into_struct(),
+            None => {
+                set_error!(TarantoolErrorCode::NoSuchSpace, "Space #{} does not exist", id);
+                Err(TarantoolError::last().into())
+            }
+        }
+    }
+}
+
 pub struct Space {
     id: u32,
 }
@@ -240,6 +344,22 @@ impl Space {
         return crate::schema::space::drop_space(self.id);
     }
 
+    /// Attach the sequence `seq` to the primary index of the space, so that
+    /// the field `field_index` (1-based, the first field of the primary key by
+    /// default) is filled with the next value of the sequence when it's `nil`
+    /// in an inserted tuple.
+    /// (for details see [specifying a sequence in create_index](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema_sequence/create_index/#box-schema-sequence-create-index)).
+    #[cfg(feature = "schema")]
+    pub fn attach_sequence(&self, seq: &Sequence, field_index: Option<u32>) -> Result<(), Error> {
+        crate::schema::sequence::set_space_sequence(self.id, seq.id(), field_index)
+    }
+
+    /// Detach the sequence from the primary index of the space.
+    #[cfg(feature = "schema")]
+    pub fn detach_sequence(&self) -> Result<(), Error> {
+        crate::schema::sequence::drop_space_sequence(self.id)
+    }
+
     /// Find space by name.
     ///
     /// This function performs SELECT request to `_vspace` system space.
@@ -249,12 +369,17 @@ impl Space {
     /// - `None` if not found
     /// - `Some(space)` otherwise
     pub fn find(name: &str) -> Option<Self> {
+        if let Some(id) = with_schema_cache(|c| c.space_ids.get(name).copied()) {
+            return Some(Self { id });
+        }
+
         let id =
             unsafe { ffi::box_space_id_by_name(name.as_ptr() as *const c_char, name.len() as u32) };
 
         if id == ffi::BOX_ID_NIL {
             None
         } else {
+            with_schema_cache(|c| c.space_ids.insert(name.into(), id));
             Some(Self { id })
         }
     }
@@ -264,6 +389,61 @@ impl Space {
         self.id
     }
 
+    /// Returns the space metadata.
+    ///
+    /// The metadata is cached until the next schema change, so only the first
+    /// call reads the `_vspace` system space.
+    pub fn meta(&self) -> Result<SpaceMetadata, Error> {
+        if let Some(meta) = with_schema_cache(|c| c.spaces.get(&self.id).cloned()) {
+            return Ok(meta);
+        }
+        let meta = SpaceMetadata::fetch(self.id)?;
+        with_schema_cache(|c| c.spaces.insert(self.id, meta.clone()));
+        Ok(meta)
+    }
+
+    /// Returns the name of the space.
+    pub fn name(&self) -> Result<String, Error> {
+        Ok(self.meta()?.name)
+    }
+
+    /// Returns the engine of the space, e.g. `"memtx"` or `"vinyl"`.
+    pub fn engine(&self) -> Result<String, Error> {
+        Ok(self.meta()?.engine)
+    }
+
+    /// Returns the format of the space.
+    pub fn format(&self) -> Result<Vec<SpaceFieldFormat>, Error> {
+        Ok(self.meta()?.format)
+    }
+
+    /// Returns the required number of fields in the tuples of the space or
+    /// 0 if any number is allowed.
+    pub fn field_count(&self) -> Result<u32, Error> {
+        Ok(self.meta()?.field_count)
+    }
+
+    /// Alter the space, e.g. rename it or change its format. Only the options
+    /// specified in `opts` are changed.
+    ///
+    /// The new format is checked against the indexes and the existing tuples
+    /// and [FormatError](enum.FormatError.html) is returned if it's
+    /// incompatible.
+    #[cfg(feature = "schema")]
+    pub fn alter(&self, opts: &SpaceAlterOptions) -> Result<(), Error> {
+        crate::schema::space::alter_space(self.id, opts)
+    }
+
+    /// Change the format of the space. Same as
+    /// [alter](#method.alter) with only the `format` option.
+    #[cfg(feature = "schema")]
+    pub fn set_format(&self, format: &[SpaceFieldFormat]) -> Result<(), Error> {
+        self.alter(&SpaceAlterOptions {
+            format: Some(format.to_vec()),
+            ..Default::default()
+        })
+    }
+
     /// Create new index.
     ///
     /// - `name` - name of index to create, which should conform to the rules for object names.
@@ -287,6 +467,11 @@ impl Space {
     /// - `None` if not found
     /// - `Some(index)` otherwise
     pub fn index(&self, name: &str) -> Option<Index> {
+        let key = (self.id, name.to_string());
+        if let Some(index_id) = with_schema_cache(|c| c.index_ids.get(&key).copied()) {
+            return Some(Index::new(self.id, index_id));
+        }
+
         let index_id = unsafe {
             ffi::box_index_id_by_name(self.id, name.as_ptr() as *const c_char, name.len() as u32)
         };
@@ -294,6 +479,7 @@ impl Space {
         if index_id == ffi::BOX_ID_NIL {
             None
         } else {
+            with_schema_cache(|c| c.index_ids.insert(key, index_id));
             Some(Index::new(self.id, index_id))
         }
     }
@@ -448,15 +634,16 @@ impl Space {
     ///
     /// - `key` - encoded key in MsgPack Array format (`[part1, part2, ...]`).
     /// - `ops` - encoded operations in MsgPack array format, e.g. `[['=', field_id, value], ['!', 2, 'xxx']]`
+    ///   (see [UpdateOps](../update/struct.UpdateOps.html) for a typed way to build them)
     ///
     /// Returns a new tuple.
     ///
     /// See also: [space.upsert()](#method.upsert)
     #[inline(always)]
-    pub fn update<K, Op>(&mut self, key: &K, ops: &Vec<Op>) -> Result<Option<Tuple>, Error>
+    pub fn update<K, Ops>(&mut self, key: &K, ops: &Ops) -> Result<Option<Tuple>, Error>
     where
         K: AsTuple,
-        Op: AsTuple,
+        Ops: AsTuple,
     {
         self.primary_key().update(key, ops)
     }
@@ -473,16 +660,248 @@ impl Space {
     ///
     /// - `value` - encoded tuple in MsgPack Array format (`[field1, field2, ...]`)
     /// - `ops` - encoded operations in MsgPack array format, e.g. `[['=', field_id, value], ['!', 2, 'xxx']]`
+    ///   (see [UpdateOps](../update/struct.UpdateOps.html) for a typed way to build them)
     ///
     /// Returns a new tuple.
     ///
     /// See also: [space.update()](#method.update)
     #[inline(always)]
-    pub fn upsert<T, Op>(&mut self, value: &T, ops: &Vec<Op>) -> Result<Option<Tuple>, Error>
+    pub fn upsert<T, Ops>(&mut self, value: &T, ops: &Ops) -> Result<Option<Tuple>, Error>
     where
         T: AsTuple,
-        Op: AsTuple,
+        Ops: AsTuple,
     {
         self.primary_key().upsert(value, ops)
     }
+
+    /// Register a trigger which is called after a tuple is inserted, replaced,
+    /// updated or deleted in the space. The trigger gets the old and the new
+    /// tuple, either of which is `None` for inserts and deletes respectively.
+    /// Returning an error rolls back the change.
+    /// (for details see [space_object:on_replace()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_space/on_replace/)).
+    ///
+    /// The trigger is removed when the returned handle is dropped.
+    pub fn on_replace<F>(&self, f: F) -> Result<TriggerHandle, Error>
+    where
+        F: FnMut(Option<Tuple>, Option<Tuple>) -> Result<(), Error>,
+        F: 'static,
+    {
+        TriggerHandle::new(self.id, TriggerKind::OnReplace, f, on_replace_trampoline::<F>)
+    }
+
+    /// Register a trigger which is called before a tuple is inserted,
+    /// replaced, updated or deleted in the space. The trigger gets the old and
+    /// the new tuple and returns the tuple to be stored instead of the new one:
+    /// - `Ok(new)` proceeds with the change as is,
+    /// - `Ok(Some(tuple))` stores `tuple` instead,
+    /// - `Ok(None)` deletes the old tuple,
+    /// - `Ok(old)` skips the change,
+    /// - `Err(_)` rejects the change.
+    ///
+    /// (for details see [space_object:before_replace()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_space/before_replace/)).
+    ///
+    /// The trigger is removed when the returned handle is dropped.
+    pub fn before_replace<F>(&self, f: F) -> Result<TriggerHandle, Error>
+    where
+        F: FnMut(Option<Tuple>, Option<Tuple>) -> Result<Option<Tuple>, Error>,
+        F: 'static,
+    {
+        TriggerHandle::new(self.id, TriggerKind::BeforeReplace, f, before_replace_trampoline::<F>)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Triggers
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Copy, Debug)]
+enum TriggerKind {
+    OnReplace,
+    BeforeReplace,
+}
+
+/// Handle of a trigger registered with [`Space::on_replace`] or
+/// [`Space::before_replace`]. The trigger is removed when the handle is
+/// dropped, unless it's [forgotten](Self::forget).
+#[must_use = "the trigger is removed when the handle is dropped"]
+#[derive(Debug)]
+pub struct TriggerHandle {
+    space_id: u32,
+    kind: TriggerKind,
+    /// Reference to the trigger function in the lua registry.
+    trigger_ref: Option<c_int>,
+}
+
+impl TriggerHandle {
+    fn new<F>(
+        space_id: u32,
+        kind: TriggerKind,
+        f: F,
+        trampoline: unsafe extern "C" fn(*mut lua::lua_State) -> c_int,
+    ) -> Result<Self, Error> {
+        let trigger_ref = unsafe {
+            let l = ffi::luaT_state();
+            tlua::push_some_userdata(l, f);
+            lua::lua_pushcclosure(l, trampoline, 1);
+            lua::luaL_ref(l, lua::LUA_REGISTRYINDEX)
+        };
+        let res = Self { space_id, kind, trigger_ref: Some(trigger_ref) };
+        set_trigger(space_id, kind, Some(trigger_ref), None)?;
+        Ok(res)
+    }
+
+    /// Keep the trigger registered for the lifetime of the space.
+    pub fn forget(mut self) {
+        if let Some(trigger_ref) = self.trigger_ref.take() {
+            unsafe { lua::luaL_unref(ffi::luaT_state(), lua::LUA_REGISTRYINDEX, trigger_ref) }
+        }
+    }
+}
+
+impl Drop for TriggerHandle {
+    fn drop(&mut self) {
+        if let Some(trigger_ref) = self.trigger_ref.take() {
+            // the space may have been dropped along with its triggers
+            let _ = set_trigger(self.space_id, self.kind, None, Some(trigger_ref));
+            unsafe { lua::luaL_unref(ffi::luaT_state(), lua::LUA_REGISTRYINDEX, trigger_ref) }
+        }
+    }
+}
+
+/// Calls `box.space[space_id]:<kind>(new, old)`, where `new` and `old` are
+/// the references to the trigger functions in the lua registry or `nil`.
+fn set_trigger(
+    space_id: u32,
+    kind: TriggerKind,
+    new: Option<c_int>,
+    old: Option<c_int>,
+) -> Result<(), Error> {
+    let rc = unsafe {
+        let l = ffi::luaT_state();
+        let top = lua::lua_gettop(l);
+        lua::lua_getglobal(l, c_ptr!("box"));
+        lua::lua_getfield(l, -1, c_ptr!("space"));
+        lua::lua_pushinteger(l, space_id as _);
+        lua::lua_gettable(l, -2);
+        let rc = if lua::lua_isnil(l, -1) {
+            None
+        } else {
+            let method = match kind {
+                TriggerKind::OnReplace => c_ptr!("on_replace"),
+                TriggerKind::BeforeReplace => c_ptr!("before_replace"),
+            };
+            lua::lua_getfield(l, -1, method);
+            lua::lua_pushvalue(l, -2);
+            for trigger_ref in [new, old] {
+                match trigger_ref {
+                    Some(trigger_ref) => lua::lua_rawgeti(l, lua::LUA_REGISTRYINDEX, trigger_ref),
+                    None => lua::lua_pushnil(l),
+                }
+            }
+            Some(ffi::luaT_call(l, 3, 0))
+        };
+        lua::lua_settop(l, top);
+        rc
+    };
+    match rc {
+        None => {
+            set_error!(TarantoolErrorCode::NoSuchSpace, "Space #{} does not exist", space_id);
+            Err(TarantoolError::last().into())
+        }
+        Some(1) => Err(TarantoolError::last().into()),
+        Some(_) => Ok(()),
+    }
+}
+
+unsafe fn trigger_args(l: *mut lua::lua_State) -> (Option<Tuple>, Option<Tuple>) {
+    let tuple = |index| NonNull::new(ffi::luaT_istuple(l, index)).map(Tuple::from_ptr);
+    (tuple(1), tuple(2))
+}
+
+/// Calls the trigger function stored in the upvalue of the current closure,
+/// converting a panic into an error.
+unsafe fn call_trigger<F, T>(l: *mut lua::lua_State) -> Result<T, Error>
+where
+    F: FnMut(Option<Tuple>, Option<Tuple>) -> Result<T, Error>,
+{
+    let f = lua::lua_touserdata(l, lua::lua_upvalueindex(1)) as *mut Option<F>;
+    let f = (*f).as_mut().expect("trigger function was dropped");
+    let (old, new) = trigger_args(l);
+    match panic::catch(|| f(old, new)) {
+        Ok(res) => res,
+        Err(p) => {
+            set_error!(
+                TarantoolErrorCode::ProcC,
+                "trigger panicked: {}", p.message().unwrap_or("<unknown>")
+            );
+            Err(TarantoolError::last().into())
+        }
+    }
+}
+
+/// Raises the error as a lua error. Must be called when there are no values
+/// left to drop on the rust side.
+unsafe fn raise_trigger_error(l: *mut lua::lua_State, e: Error) -> c_int {
+    if !matches!(e, Error::Tarantool(_)) {
+        set_error!(TarantoolErrorCode::ProcC, "{}", e);
+    }
+    drop(e);
+    ffi::luaT_error(l)
+}
+
+unsafe extern "C" fn on_replace_trampoline<F>(l: *mut lua::lua_State) -> c_int
+where
+    F: FnMut(Option<Tuple>, Option<Tuple>) -> Result<(), Error>,
+{
+    match call_trigger::<F, _>(l) {
+        Ok(()) => 0,
+        Err(e) => raise_trigger_error(l, e),
+    }
+}
+
+unsafe extern "C" fn before_replace_trampoline<F>(l: *mut lua::lua_State) -> c_int
+where
+    F: FnMut(Option<Tuple>, Option<Tuple>) -> Result<Option<Tuple>, Error>,
+{
+    match call_trigger::<F, _>(l) {
+        Ok(Some(tuple)) => l.push_one(&tuple).assert_one_and_forget(),
+        Ok(None) => {
+            lua::lua_pushnil(l);
+            1
+        }
+        Err(e) => raise_trigger_error(l, e),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// SchemaCache
+////////////////////////////////////////////////////////////////////////////////
+
+/// Cache of the space and index lookups. It's invalidated whenever the schema
+/// version changes, i.e. on any DDL operation.
+#[derive(Default)]
+pub(crate) struct SchemaCache {
+    version: u64,
+    pub space_ids: HashMap<String, u32>,
+    pub index_ids: HashMap<(u32, String), u32>,
+    pub spaces: HashMap<u32, SpaceMetadata>,
+    pub indexes: HashMap<(u32, u32), crate::index::IndexMetadata>,
+}
+
+thread_local! {
+    static SCHEMA_CACHE: RefCell<SchemaCache> = RefCell::new(SchemaCache::default());
+}
+
+/// Calls `f` with the schema cache, which is cleared first if the schema
+/// has changed since the last call. `f` must not call this function
+/// recursively.
+pub(crate) fn with_schema_cache<R>(f: impl FnOnce(&mut SchemaCache) -> R) -> R {
+    SCHEMA_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let version = unsafe { ffi::box_schema_version() };
+        if cache.version != version {
+            *cache = SchemaCache { version, ..Default::default() };
+        }
+        f(&mut cache)
+    })
 }
@@ -26,9 +26,36 @@
 //! - [Transaction control](https://www.tarantool.io/en/doc/latest/book/box/atomic/)
 //! - [Lua reference: Functions for transaction management](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_txn_management/)
 //! - [C API reference: Module txn](https://www.tarantool.io/en/doc/latest/dev_guide/reference_capi/txn/)
+//!
+//! Use [`start_transaction`] to run a function in a transaction, or
+//! [`Transaction`] for finer control, e.g. savepoints and commit hooks:
+//! ```no_run
+//! use tarantool::space::Space;
+//! use tarantool::transaction::Transaction;
+//!
+//! let mut space = Space::find("accounts").unwrap();
+//! let mut tx = Transaction::begin().unwrap();
+//! tx.on_commit(|| println!("committed"));
+//! space.insert(&(1, "alice")).unwrap();
+//! let savepoint = tx.savepoint().unwrap();
+//! space.insert(&(2, "bob")).unwrap();
+//! // only the second insert is undone
+//! tx.rollback_to(&savepoint).unwrap();
+//! tx.commit().unwrap();
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+use tlua::AsLua;
 
-use crate::error::TransactionError;
+use crate::error::{Error, TarantoolError, TransactionError};
 use crate::ffi::tarantool as ffi;
+use crate::version::{self, Feature};
 
 /// Begin a transaction in the current fiber.
 ///
@@ -39,28 +66,460 @@ use crate::ffi::tarantool as ffi;
 ///
 /// Returns result of function `f` execution. Depending on the function result:
 /// - will **commit** - if function completes successfully
-/// - will **rollback** - if function completes with any error
+/// - will **rollback** - if function completes with any error or panics
 pub fn start_transaction<T, E, F>(f: F) -> Result<T, E>
 where
     F: FnOnce() -> Result<T, E>,
     E: From<TransactionError>,
 {
-    if unsafe { ffi::box_txn_begin() } < 0 {
-        return Err(TransactionError::AlreadyStarted.into());
+    let tx = Transaction::begin()?;
+    // on panic the transaction is rolled back when `tx` is dropped
+    let result = f();
+    match &result {
+        Ok(_) => tx.commit()?,
+        Err(_) => tx.rollback()?,
     }
+    result
+}
 
+/// Like [`start_transaction`], but if the current fiber already has an
+/// active transaction, `f` is run in a nested transaction instead of failing
+/// with [`TransactionError::AlreadyStarted`]. If `f` fails only its own
+/// changes are rolled back, the outer transaction stays active.
+///
+/// This allows library code to be transactional regardless of whether it's
+/// called inside a transaction. See [`Transaction::begin_nested`].
+pub fn start_nested_transaction<T, F>(f: F) -> Result<T, Error>
+where
+    F: FnOnce() -> Result<T, Error>,
+{
+    let tx = Transaction::begin_nested()?;
+    // on panic the transaction is rolled back when `tx` is dropped
     let result = f();
     match &result {
-        Ok(_) => {
-            if unsafe { ffi::box_txn_commit() } < 0 {
-                return Err(TransactionError::FailedToCommit.into());
+        Ok(_) => tx.commit()?,
+        Err(_) => tx.rollback()?,
+    }
+    result
+}
+
+/// Returns `true` if the current fiber has an active transaction.
+pub fn is_in_transaction() -> bool {
+    unsafe { ffi::box_txn() }
+}
+
+/// Transaction isolation level, see
+/// [Transactional manager](https://www.tarantool.io/en/doc/latest/book/box/atomic/txn_mode_mvcc/).
+///
+/// The levels only make difference if the MVCC engine is enabled
+/// (`memtx_use_mvcc_engine`). They are supported since tarantool 2.10.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum IsolationLevel {
+    /// The level set by the `txn_isolation` configuration option.
+    Default,
+    /// See the changes committed by the other transactions, even if they
+    /// aren't confirmed by the replicas yet.
+    ReadCommitted,
+    /// Only see the changes which are committed and confirmed.
+    ReadConfirmed,
+    /// Read committed changes if possible, read confirmed otherwise.
+    BestEffort,
+}
+
+impl Default for IsolationLevel {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
+impl IsolationLevel {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::ReadCommitted => "read-committed",
+            Self::ReadConfirmed => "read-confirmed",
+            Self::BestEffort => "best-effort",
+        }
+    }
+}
+
+type Hook = Box<dyn FnOnce()>;
+
+/// Hooks of a transaction or of a nested transaction.
+struct Frame {
+    id: u64,
+    on_commit: Vec<Hook>,
+    on_rollback: Vec<Hook>,
+}
+
+thread_local! {
+    /// Frames of the active transaction and its nested transactions by the
+    /// address of the fiber, the innermost is the last. All the fibers run
+    /// on the same thread, but each of them has its own transaction. The
+    /// address of a dead fiber may be reused, the stale frames are told
+    /// apart by their ids.
+    static FRAMES: RefCell<HashMap<usize, Vec<Frame>>> = RefCell::new(HashMap::new());
+    static NEXT_FRAME_ID: Cell<u64> = Cell::new(0);
+}
+
+fn current_fiber() -> usize {
+    unsafe { ffi::fiber_self() as usize }
+}
+
+/// Calls `f` with the frames of the `fiber`, the entry of the fiber is
+/// removed once it has no frames.
+fn with_frames<R>(fiber: usize, f: impl FnOnce(&mut Vec<Frame>) -> R) -> R {
+    FRAMES.with(|frames| {
+        let mut frames = frames.borrow_mut();
+        let fiber_frames = frames.entry(fiber).or_default();
+        let res = f(fiber_frames);
+        if fiber_frames.is_empty() {
+            frames.remove(&fiber);
+        }
+        res
+    })
+}
+
+fn run_hooks(hooks: Vec<Hook>) {
+    for hook in hooks {
+        hook()
+    }
+}
+
+/// Runs the hooks where a panic can't be propagated.
+fn run_hooks_catching(hooks: Vec<Hook>) {
+    for hook in hooks {
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(hook));
+    }
+}
+
+/// Keeps the hooks of a committed nested transaction until the outer
+/// transaction, which wasn't started with this API, ends. Returns the frame
+/// back if the triggers can't be set.
+fn defer_to_outer(frame: Frame) -> Result<(), Frame> {
+    if frame.on_commit.is_empty() && frame.on_rollback.is_empty() {
+        return Ok(());
+    }
+    let frame = Rc::new(RefCell::new(Some(frame)));
+    let hook = {
+        let frame = frame.clone();
+        tlua::function1(move |committed: bool| {
+            // the hooks are called from a trigger, where a panic can't be
+            // propagated
+            let frame = frame.borrow_mut().take();
+            match frame {
+                Some(frame) if committed => run_hooks_catching(frame.on_commit),
+                Some(frame) => run_hooks_catching(frame.on_rollback),
+                None => {}
+            }
+        })
+    };
+    let lua = crate::global_lua();
+    let res = tlua::LuaFunction::load(
+        &lua,
+        "local hook = ...
+        box.on_commit(function() hook(true) end)
+        box.on_rollback(function() hook(false) end)",
+    )
+    .ok()
+    .and_then(|register| {
+        let hook: tlua::LuaFunction<_> = (&lua).push(hook).read().ok()?;
+        register.call_with_args::<(), _>(&hook).ok()
+    });
+    match res {
+        Some(()) => Ok(()),
+        None => Err(frame.borrow_mut().take().unwrap()),
+    }
+}
+
+/// An active transaction of the current fiber.
+///
+/// The transaction is rolled back if it's dropped without being committed,
+/// e.g. when an error is returned with `?` or on a panic.
+///
+/// A transaction started with [`begin_nested`](Self::begin_nested) inside
+/// another one is a part of it: committing it keeps the changes until the
+/// outer transaction ends, rolling it back undoes only its own changes. The
+/// nested transactions must be finished before the outer one.
+#[must_use = "the transaction is rolled back when dropped"]
+pub struct Transaction {
+    /// Address of the fiber the transaction belongs to.
+    fiber: usize,
+    /// Id of the frame of the transaction in `FRAMES`.
+    frame_id: u64,
+    depth: usize,
+    /// The savepoint the nested transaction is rolled back to.
+    savepoint: Option<NonNull<ffi::BoxTxnSavepoint>>,
+    is_finished: bool,
+}
+
+impl Transaction {
+    /// Begin a transaction in the current fiber.
+    ///
+    /// Returns [`TransactionError::AlreadyStarted`] if the fiber already has
+    /// an active transaction.
+    pub fn begin() -> Result<Self, TransactionError> {
+        if unsafe { ffi::box_txn_begin() } < 0 {
+            return Err(TransactionError::AlreadyStarted);
+        }
+        Ok(Self::push_frame(None))
+    }
+
+    /// Begin a transaction with the isolation `level`.
+    pub fn begin_with_isolation(level: IsolationLevel) -> Result<Self, Error> {
+        if level == IsolationLevel::Default {
+            return Ok(Self::begin()?);
+        }
+        version::check_supported(Feature::TxnIsolation)?;
+        if is_in_transaction() {
+            return Err(TransactionError::AlreadyStarted.into());
+        }
+        // the isolation level isn't available in the C API
+        crate::global_lua().exec(&format!("box.begin({{txn_isolation = '{}'}})", level.as_str()))?;
+        Ok(Self::push_frame(None))
+    }
+
+    /// Begin a transaction, or a nested transaction if the fiber already has
+    /// an active one. The nested transaction is implemented with a
+    /// savepoint of the outer one.
+    pub fn begin_nested() -> Result<Self, Error> {
+        if !is_in_transaction() {
+            return Ok(Self::begin()?);
+        }
+        let ptr = unsafe { ffi::box_txn_savepoint() };
+        match NonNull::new(ptr) {
+            Some(savepoint) => Ok(Self::push_frame(Some(savepoint))),
+            None => Err(TarantoolError::last().into()),
+        }
+    }
+
+    fn push_frame(savepoint: Option<NonNull<ffi::BoxTxnSavepoint>>) -> Self {
+        let fiber = current_fiber();
+        let frame_id = NEXT_FRAME_ID.with(|id| id.replace(id.get() + 1));
+        let depth = with_frames(fiber, |frames| {
+            if savepoint.is_none() {
+                // left from a transaction of this fiber finished bypassing
+                // this API
+                frames.clear();
+            }
+            frames.push(Frame { id: frame_id, on_commit: vec![], on_rollback: vec![] });
+            frames.len() - 1
+        });
+        Self { fiber, frame_id, depth, savepoint, is_finished: false }
+    }
+
+    /// Removes the frame of the transaction along with the frames of the
+    /// unfinished nested transactions, which become a part of this one.
+    /// Returns `None` if the frame was already removed by the outer
+    /// transaction.
+    fn pop_frame(&mut self) -> Option<Frame> {
+        self.is_finished = true;
+        with_frames(self.fiber, |frames| {
+            if frames.get(self.depth).map(|f| f.id) != Some(self.frame_id) {
+                return None;
             }
+            let mut inner = frames.split_off(self.depth).into_iter();
+            let mut res = inner.next().unwrap();
+            for frame in inner {
+                res.on_commit.extend(frame.on_commit);
+                res.on_rollback.extend(frame.on_rollback);
+            }
+            Some(res)
+        })
+    }
+
+    /// Returns `true` if the transaction was started inside another one.
+    pub fn is_nested(&self) -> bool {
+        self.savepoint.is_some()
+    }
+
+    /// Return a savepoint of the transaction, see [`rollback_to`](Self::rollback_to).
+    pub fn savepoint(&self) -> Result<Savepoint<'_>, Error> {
+        let ptr = unsafe { ffi::box_txn_savepoint() };
+        match NonNull::new(ptr) {
+            Some(ptr) => Ok(Savepoint { ptr, marker: PhantomData }),
+            None => Err(TarantoolError::last().into()),
         }
-        Err(_) => {
-            if unsafe { ffi::box_txn_rollback() } < 0 {
-                return Err(TransactionError::FailedToRollback.into());
+    }
+
+    /// Undo the changes made after the `savepoint` was created. The
+    /// transaction stays active.
+    pub fn rollback_to(&self, savepoint: &Savepoint) -> Result<(), Error> {
+        if unsafe { ffi::box_txn_rollback_to_savepoint(savepoint.ptr.as_ptr()) } < 0 {
+            return Err(TarantoolError::last().into());
+        }
+        Ok(())
+    }
+
+    /// Allocate a zeroed buffer of `len` bytes on the memory region of the
+    /// transaction. The memory is released at once when the outermost
+    /// transaction ends, which is cheaper than the heap allocations for the
+    /// scratch data of the transaction.
+    pub fn alloc(&self, len: usize) -> Result<TxnBuffer<'_>, Error> {
+        // the region may return NULL for an empty allocation
+        let ptr = unsafe { ffi::box_txn_alloc(len.max(1)) }.cast::<u8>();
+        match NonNull::new(ptr) {
+            Some(ptr) => {
+                unsafe { std::ptr::write_bytes(ptr.as_ptr(), 0, len) };
+                Ok(TxnBuffer { ptr, len, marker: PhantomData })
             }
+            None => Err(TarantoolError::last().into()),
         }
     }
-    result
+
+    /// Register a function which is called after the transaction is
+    /// committed. The functions are called in the order of registration.
+    ///
+    /// For a nested transaction the functions are called after the
+    /// outermost one is committed.
+    pub fn on_commit<F>(&mut self, f: F)
+    where
+        F: FnOnce() + 'static,
+    {
+        self.with_frame(|frame| frame.on_commit.push(Box::new(f)))
+    }
+
+    /// Register a function which is called after the transaction is rolled
+    /// back, including when it's dropped or fails to commit. The functions
+    /// are called in the order of registration.
+    ///
+    /// For a nested transaction the functions are also called if it's
+    /// committed, but the outer one is rolled back.
+    pub fn on_rollback<F>(&mut self, f: F)
+    where
+        F: FnOnce() + 'static,
+    {
+        self.with_frame(|frame| frame.on_rollback.push(Box::new(f)))
+    }
+
+    fn with_frame(&self, f: impl FnOnce(&mut Frame)) {
+        with_frames(self.fiber, |frames| {
+            if let Some(frame) = frames.get_mut(self.depth).filter(|f| f.id == self.frame_id) {
+                f(frame)
+            }
+        })
+    }
+
+    /// Commit the transaction. If the commit fails the transaction is
+    /// rolled back.
+    ///
+    /// Committing a nested transaction releases its savepoint, the changes
+    /// are committed with the outer transaction. If the outer transaction
+    /// wasn't started with this API, the hooks are run by its `box.on_commit`
+    /// and `box.on_rollback` triggers.
+    pub fn commit(mut self) -> Result<(), TransactionError> {
+        let frame = self.pop_frame();
+        if self.is_nested() {
+            // the outer transaction has already ended
+            let frame = frame.ok_or(TransactionError::FailedToCommit)?;
+            let frame = with_frames(self.fiber, |frames| match frames.last_mut() {
+                Some(outer) => {
+                    outer.on_commit.extend(frame.on_commit);
+                    outer.on_rollback.extend(frame.on_rollback);
+                    None
+                }
+                // the outer transaction wasn't started with this API
+                None => Some(frame),
+            });
+            if let Some(frame) = frame {
+                if let Err(frame) = defer_to_outer(frame) {
+                    self.rollback_impl();
+                    run_hooks(frame.on_rollback);
+                    return Err(TransactionError::FailedToCommit);
+                }
+            }
+            return Ok(());
+        }
+        if unsafe { ffi::box_txn_commit() } < 0 {
+            // the transaction is rolled back by the failed commit
+            if let Some(frame) = frame {
+                run_hooks(frame.on_rollback);
+            }
+            return Err(TransactionError::FailedToCommit);
+        }
+        if let Some(frame) = frame {
+            run_hooks(frame.on_commit);
+        }
+        Ok(())
+    }
+
+    /// Roll back the transaction. A nested transaction is rolled back to its
+    /// savepoint.
+    pub fn rollback(mut self) -> Result<(), TransactionError> {
+        let frame = self.pop_frame();
+        if self.is_nested() && frame.is_none() {
+            // the outer transaction has already ended
+            return Err(TransactionError::FailedToRollback);
+        }
+        if !self.rollback_impl() {
+            return Err(TransactionError::FailedToRollback);
+        }
+        if let Some(frame) = frame {
+            run_hooks(frame.on_rollback);
+        }
+        Ok(())
+    }
+
+    fn rollback_impl(&self) -> bool {
+        let rc = match self.savepoint {
+            Some(savepoint) => unsafe { ffi::box_txn_rollback_to_savepoint(savepoint.as_ptr()) },
+            None => unsafe { ffi::box_txn_rollback() },
+        };
+        rc == 0
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        if self.is_finished {
+            return;
+        }
+        let frame = self.pop_frame();
+        if self.is_nested() && frame.is_none() {
+            // the outer transaction has already ended
+            return;
+        }
+        if !self.rollback_impl() {
+            return;
+        }
+        let frame = match frame {
+            Some(frame) => frame,
+            None => return,
+        };
+        if std::thread::panicking() {
+            // a panic in a hook would abort the process while unwinding
+            run_hooks_catching(frame.on_rollback)
+        } else {
+            run_hooks(frame.on_rollback)
+        }
+    }
+}
+
+/// A buffer allocated with [`Transaction::alloc`].
+pub struct TxnBuffer<'t> {
+    ptr: NonNull<u8>,
+    len: usize,
+    marker: PhantomData<&'t Transaction>,
+}
+
+impl Deref for TxnBuffer<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for TxnBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+/// A savepoint of a [`Transaction`]. It can only be used while the
+/// transaction is active.
+#[derive(Debug)]
+pub struct Savepoint<'t> {
+    ptr: NonNull<ffi::BoxTxnSavepoint>,
+    marker: PhantomData<&'t Transaction>,
 }
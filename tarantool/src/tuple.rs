@@ -9,18 +9,27 @@
 //! - [Lua reference: Submodule box.tuple](https://www.tarantool.io/en/doc/2.2/reference/reference_lua/box_tuple/)
 //! - [C API reference: Module tuple](https://www.tarantool.io/en/doc/2.2/dev_guide/reference_capi/tuple/)
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
 use std::io::Cursor;
 use std::os::raw::{c_char, c_int};
 use std::ptr::{copy_nonoverlapping, NonNull};
+use std::rc::Rc;
 use std::slice::from_raw_parts;
 
 use num_traits::ToPrimitive;
 use rmp::Marker;
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::{DeserializeOwned, IgnoredAny}, Deserialize, Serialize};
 
 use crate::error::{Error, TarantoolError};
 use crate::ffi::tarantool as ffi;
+use crate::index::{Index, IndexPartDef, IteratorType};
+use crate::space::{Space, SpaceFieldFormat, SpaceFieldType, SystemSpace};
 use crate::tlua as tlua;
+use crate::util::from_schema_name;
+
+/// Base for the array indexes in JSON paths, the same as in Lua.
+const TUPLE_INDEX_BASE: c_int = 1;
 
 /// Tuple
 pub struct Tuple {
@@ -43,6 +52,29 @@ impl Tuple {
         }
     }
 
+    /// Creates new tuple from `value` validating it against the `format`.
+    ///
+    /// Returns an error describing the first field which doesn't match the
+    /// format.
+    ///
+    /// See also: [TupleFormat::for_space](struct.TupleFormat.html#method.for_space)
+    pub fn from_struct_with_format<T>(value: &T, format: &TupleFormat) -> Result<Self, Error>
+    where
+        T: AsTuple,
+    {
+        let buf = value.serialize_as_tuple()?;
+        unsafe { Self::from_raw_data_with_format(buf.as_ptr(), buf.len(), format) }
+    }
+
+    unsafe fn from_raw_data_with_format(
+        data: *const u8,
+        len: usize,
+        format: &TupleFormat,
+    ) -> Result<Self, Error> {
+        let tuple_ptr = ffi::box_tuple_new(format.inner, data as _, data.add(len) as _);
+        Self::try_from_ptr(tuple_ptr).ok_or_else(|| TarantoolError::last().into())
+    }
+
     pub unsafe fn from_raw_data(data: *mut c_char, len: u32) -> Self {
         let format = TupleFormat::default();
         let tuple_ptr = ffi::box_tuple_new(
@@ -83,9 +115,7 @@ impl Tuple {
 
     /// Return the associated format.
     pub fn format(&self) -> TupleFormat {
-        TupleFormat {
-            inner: unsafe { ffi::box_tuple_format(self.ptr.as_ptr()) },
-        }
+        unsafe { TupleFormat::from_ptr(ffi::box_tuple_format(self.ptr.as_ptr())) }
     }
 
     /// Allocate and initialize a new `Tuple` iterator. The `Tuple` iterator
@@ -123,7 +153,7 @@ impl Tuple {
     /// - `fieldno` - zero-based index in MsgPack array.
     ///
     /// Returns:
-    /// - `None` if `i >= box_tuple_field_count(Tuple)` or if field has a non primitive type
+    /// - `None` if `i >= box_tuple_field_count(Tuple)`
     /// - field value otherwise
     pub fn field<T>(&self, fieldno: u32) -> Result<Option<T>, Error>
     where
@@ -133,28 +163,114 @@ impl Tuple {
         field_value_from_ptr(result_ptr as *mut u8)
     }
 
+    /// Return the Tuple field specified by a JSON path, e.g.
+    /// `"name.tags[1]"` or `"[2].key"`.
+    ///
+    /// The first component of the path may be a field name from the tuple's
+    /// format. Array indexes in the path are 1-based, the same as in Lua.
+    ///
+    /// Returns:
+    /// - `None` if there's no field at `path`
+    /// - field value otherwise
+    pub fn get_by_path<T>(&self, path: &str) -> Result<Option<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let result_ptr = unsafe {
+            ffi::box_tuple_field_by_path(
+                self.ptr.as_ptr(),
+                path.as_ptr() as _,
+                path.len() as _,
+                TUPLE_INDEX_BASE,
+            )
+        };
+        field_value_from_ptr(result_ptr as *mut u8)
+    }
+
+    /// Return the Tuple field with the given `name` in the tuple's format.
+    ///
+    /// Returns:
+    /// - `None` if the format has no field named `name` or if the tuple
+    ///   doesn't have this field
+    /// - field value otherwise
+    ///
+    /// Unlike [get_by_path](#method.get_by_path) `name` is never interpreted
+    /// as a path, e.g. `"[1]"` is only found if there's a field named so.
+    pub fn get_by_name<T>(&self, name: &str) -> Result<Option<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        // The string key of the root of a path is only looked up in the
+        // format's dictionary. The path syntax has no escapes, so a name with
+        // both kinds of quotes can't be expressed
+        let quote = match (name.contains('"'), name.contains('\'')) {
+            (false, _) => '"',
+            (true, false) => '\'',
+            (true, true) => return Ok(None),
+        };
+        self.get_by_path(&format!("[{q}{}{q}]", name, q = quote))
+    }
+
     /// Deserializes tuple contents into structure of type `T`
     pub fn as_struct<T>(&self) -> Result<T, Error>
     where
         T: DeserializeOwned,
     {
-        let raw_data = self.as_buffer()?;
-        Ok(rmp_serde::from_read::<_, T>(Cursor::new(raw_data))?)
+        self.decode()
     }
 
-    #[inline]
-    pub(crate) fn as_buffer(&self) -> Result<Vec<u8>, Error> {
-        let buffer_size = self.bsize();
-        let mut buffer = Vec::<u8>::with_capacity(buffer_size);
+    /// Deserializes tuple contents into structure of type `T` borrowing
+    /// string and binary fields directly from the tuple memory, so no
+    /// allocations are made for them.
+    ///
+    /// Example:
+    /// ```no_run
+    /// use serde::Deserialize;
+    /// # let tuple: tarantool::tuple::Tuple = unimplemented!();
+    ///
+    /// #[derive(Deserialize)]
+    /// struct User<'a> {
+    ///     id: u64,
+    ///     name: &'a str,
+    /// }
+    ///
+    /// let user: User = tuple.decode().unwrap();
+    /// ```
+    pub fn decode<'a, T>(&'a self) -> Result<T, Error>
+    where
+        T: Deserialize<'a>,
+    {
+        Ok(rmp_serde::from_read_ref::<_, T>(self.data())?)
+    }
 
-        let actual_size =
-            unsafe { ffi::box_tuple_to_buf(self.ptr.as_ptr(), buffer.as_ptr() as *mut c_char, buffer_size) };
-        if actual_size < 0 {
-            return Err(TarantoolError::last().into());
+    /// Returns the raw tuple contents (MsgPack array).
+    ///
+    /// The data is not copied and is valid as long as the tuple is alive.
+    pub fn data(&self) -> &[u8] {
+        let len = self.len();
+        let first = unsafe { ffi::box_tuple_field(self.ptr.as_ptr(), 0) as *const u8 };
+        if first.is_null() {
+            // an empty tuple
+            return &[0x90]
+        }
+        // The tuple data is stored contiguously right after the array header
+        // which precedes the first field. The size of the header is not known
+        // so check all the possible encodings. They are unambiguous because
+        // the header must contain the actual field count.
+        let bsize = self.bsize();
+        unsafe {
+            let header_len = if len < 16 && *first.sub(1) == 0x90 | len as u8 {
+                1
+            } else if *first.sub(3) == 0xdc
+                && u16::from_be_bytes([*first.sub(2), *first.sub(1)]) as u32 == len
+            {
+                3
+            } else {
+                debug_assert_eq!(*first.sub(5), 0xdd);
+                5
+            };
+            from_raw_parts(first.sub(header_len), bsize)
         }
-
-        unsafe { buffer.set_len(actual_size as usize) };
-        Ok(buffer)
     }
 
     /// Deserializes tuple contents into structure of type `T`
@@ -226,6 +342,147 @@ where
 {
 }
 
+/// Derives [`AsTuple`](trait.AsTuple.html), `Serialize`, `Deserialize` and
+/// [`Record`](trait.Record.html) for a struct with named fields, which is
+/// stored as a tuple in a space.
+///
+/// By default the struct fields are mapped to the tuple fields in the order
+/// of declaration. The field attributes change that:
+/// - `#[tuple(field = N)]` puts the struct field at the (0-based) position
+///   `N` of the tuple. The following fields continue from `N + 1`. The tuple
+///   fields which aren't mapped to any struct field are encoded as `nil` and
+///   ignored when decoding.
+/// - `#[tuple(optional)]` marks an `Option` field which may be missing at the
+///   end of the tuple. It is decoded as `None` if the tuple is shorter and is
+///   omitted when encoding `None`. The fields following an optional one must be
+///   optional too.
+///
+/// Other `Option` fields are nullable: they must be present in the tuple, but
+/// may be `nil`.
+///
+/// Example:
+/// ```no_run
+/// use tarantool::space::{Space, SpaceCreateOptions};
+/// use tarantool::tuple::{Record, Tuple};
+///
+/// #[derive(Tuple)]
+/// struct User {
+///     id: u64,
+///     name: String,
+///     #[tuple(field = 3)]
+///     email: Option<String>,
+///     #[tuple(optional)]
+///     age: Option<u32>,
+/// }
+///
+/// let opts = SpaceCreateOptions {
+///     format: Some(User::format()),
+///     ..Default::default()
+/// };
+/// let mut space = Space::create("users", &opts).unwrap();
+/// space.insert(&User { id: 1, name: "Alice".into(), email: None, age: None }).unwrap();
+/// ```
+pub use tarantool_derive::Tuple;
+
+/// A type stored as a tuple in a space. Usually implemented with
+/// [`#[derive(Tuple)]`](derive.Tuple.html).
+pub trait Record: AsTuple {
+    /// Returns the space format matching the type, which can be used in
+    /// [SpaceCreateOptions](../space/struct.SpaceCreateOptions.html).
+    fn format() -> Vec<SpaceFieldFormat>;
+}
+
+/// Describes the type of a tuple field in the space format. Used by
+/// [`#[derive(Tuple)]`](derive.Tuple.html) to generate the format.
+pub trait TupleField {
+    fn field_type() -> SpaceFieldType;
+
+    fn is_nullable() -> bool {
+        false
+    }
+}
+
+macro_rules! impl_tuple_field {
+    ($field_type:ident: $($t:ty),+) => {
+        $(
+            impl TupleField for $t {
+                fn field_type() -> SpaceFieldType {
+                    SpaceFieldType::$field_type
+                }
+            }
+        )+
+    };
+}
+
+impl_tuple_field!{Unsigned: u8, u16, u32, u64, usize}
+impl_tuple_field!{Integer: i8, i16, i32, i64, isize}
+impl_tuple_field!{Double: f32, f64}
+impl_tuple_field!{Boolean: bool}
+impl_tuple_field!{String: String, str}
+impl_tuple_field!{Varbinary: serde_bytes::ByteBuf, serde_bytes::Bytes}
+impl_tuple_field!{Decimal: crate::decimal::Decimal}
+impl_tuple_field!{Uuid: crate::uuid::Uuid}
+impl_tuple_field!{Datetime: crate::datetime::Datetime}
+impl_tuple_field!{Interval: crate::datetime::Interval}
+impl_tuple_field!{Any: serde_json::Value}
+
+impl<T> TupleField for Vec<T> {
+    fn field_type() -> SpaceFieldType {
+        SpaceFieldType::Array
+    }
+}
+
+impl<K, V> TupleField for std::collections::HashMap<K, V> {
+    fn field_type() -> SpaceFieldType {
+        SpaceFieldType::Map
+    }
+}
+
+impl<K, V> TupleField for BTreeMap<K, V> {
+    fn field_type() -> SpaceFieldType {
+        SpaceFieldType::Map
+    }
+}
+
+impl<T> TupleField for Option<T>
+where
+    T: TupleField,
+{
+    fn field_type() -> SpaceFieldType {
+        T::field_type()
+    }
+
+    fn is_nullable() -> bool {
+        true
+    }
+}
+
+impl<T> TupleField for &T
+where
+    T: TupleField + ?Sized,
+{
+    fn field_type() -> SpaceFieldType {
+        T::field_type()
+    }
+
+    fn is_nullable() -> bool {
+        T::is_nullable()
+    }
+}
+
+impl<T> TupleField for Box<T>
+where
+    T: TupleField + ?Sized,
+{
+    fn field_type() -> SpaceFieldType {
+        T::field_type()
+    }
+
+    fn is_nullable() -> bool {
+        T::is_nullable()
+    }
+}
+
 /// Buffer containing tuple contents (MsgPack array)
 ///
 /// If buffer is allocated within transaction: will be disposed after transaction ended (committed or dropped).
@@ -277,11 +534,180 @@ pub struct TupleFormat {
     inner: *mut ffi::BoxTupleFormat,
 }
 
+impl TupleFormat {
+    /// Creates a format which validates tuples against the format of `space`,
+    /// so that tuples can be checked before they are inserted.
+    ///
+    /// The format checks that every non-nullable field from the space format
+    /// and every non-nullable index part is present and has the right type.
+    /// Fields of types `any`, `array` and `map` as well as JSON path index
+    /// parts aren't checked.
+    ///
+    /// Example:
+    /// ```no_run
+    /// use tarantool::space::Space;
+    /// use tarantool::tuple::{Tuple, TupleFormat};
+    ///
+    /// let space = Space::find("users").unwrap();
+    /// let format = TupleFormat::for_space(&space).unwrap();
+    /// // fails if e.g. the second field must be a string
+    /// let res = Tuple::from_struct_with_format(&(1, 2), &format);
+    /// ```
+    pub fn for_space(space: &Space) -> Result<Self, Error> {
+        let mut fields = BTreeMap::new();
+
+        let sys_space: Space = SystemSpace::Space.into();
+        if let Some(tuple) = sys_space.get(&(space.id(),))? {
+            let (_, _, _, _, _, _, format): SpaceDef = tuple.decode()?;
+            for (field_id, field) in format.into_iter().enumerate() {
+                if !field.is_nullable {
+                    fields.insert(field_id as u32, field.field_type);
+                }
+            }
+        }
+
+        let sys_index: Space = SystemSpace::Index.into();
+        for tuple in sys_index.select(IteratorType::Eq, &(space.id(),))? {
+            let (_, _, _, _, _, parts): IndexDef = tuple.decode()?;
+            for part in parts {
+                let (field_id, field_type) = match part {
+                    IndexPartDef::Array(field_id, field_type) => (field_id, Some(field_type)),
+                    IndexPartDef::Map { field, field_type, is_nullable: false, path: None, .. } => {
+                        (field, Some(field_type))
+                    }
+                    IndexPartDef::Map { .. } => continue,
+                };
+                fields.entry(field_id).or_insert(field_type);
+            }
+        }
+
+        let items: Vec<_> = fields.into_iter()
+            .filter_map(|(field_id, field_type)| {
+                let field_type = field_type.as_deref().and_then(from_schema_name)?;
+                if matches!(field_type, FieldType::Any | FieldType::Array | FieldType::Map) {
+                    return None
+                }
+                Some(KeyDefItem { field_id, field_type })
+            })
+            .collect();
+
+        let inner = if items.is_empty() {
+            unsafe { ffi::box_tuple_format_new(std::ptr::null_mut(), 0) }
+        } else {
+            let mut key_def = KeyDef::new(items);
+            unsafe { ffi::box_tuple_format_new(&mut key_def.inner, 1) }
+        };
+        if inner.is_null() {
+            return Err(TarantoolError::last().into());
+        }
+        // The new format is already referenced
+        Ok(TupleFormat { inner })
+    }
+
+    unsafe fn from_ptr(inner: *mut ffi::BoxTupleFormat) -> Self {
+        ffi::box_tuple_format_ref(inner);
+        TupleFormat { inner }
+    }
+}
+
 impl Default for TupleFormat {
     fn default() -> Self {
-        TupleFormat {
-            inner: unsafe { ffi::box_tuple_format_default() },
+        unsafe { Self::from_ptr(ffi::box_tuple_format_default()) }
+    }
+}
+
+impl Clone for TupleFormat {
+    fn clone(&self) -> Self {
+        unsafe { Self::from_ptr(self.inner) }
+    }
+}
+
+impl Drop for TupleFormat {
+    fn drop(&mut self) {
+        unsafe { ffi::box_tuple_format_unref(self.inner) }
+    }
+}
+
+/// A tuple in `_space` system space. Only the format is needed.
+type SpaceDef = (IgnoredAny, IgnoredAny, IgnoredAny, IgnoredAny, IgnoredAny, IgnoredAny, Vec<SpaceFieldDef>);
+
+#[derive(Deserialize)]
+struct SpaceFieldDef {
+    #[serde(rename = "type", default)]
+    field_type: Option<String>,
+    #[serde(default)]
+    is_nullable: bool,
+}
+
+/// A tuple in `_index` system space. Only the parts are needed.
+type IndexDef = (IgnoredAny, IgnoredAny, IgnoredAny, IgnoredAny, IgnoredAny, Vec<IndexPartDef>);
+
+////////////////////////////////////////////////////////////////////////////////
+// TupleBuilder
+////////////////////////////////////////////////////////////////////////////////
+
+/// Builds a tuple field by field, without defining an intermediate struct.
+///
+/// Example:
+/// ```no_run
+/// use tarantool::tuple::TupleBuilder;
+///
+/// let mut builder = TupleBuilder::new();
+/// builder.append(&1).unwrap();
+/// builder.append("text").unwrap();
+/// builder.append(&[1, 2, 3]).unwrap();
+/// let tuple = builder.build().unwrap();
+/// ```
+#[derive(Default)]
+pub struct TupleBuilder {
+    buf: Vec<u8>,
+    len: u32,
+}
+
+impl TupleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a field with `value` to the end of the tuple.
+    pub fn append<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let old_len = self.buf.len();
+        if let Err(e) = rmp_serde::encode::write(&mut self.buf, value) {
+            // don't leave a partially encoded value behind
+            self.buf.truncate(old_len);
+            return Err(e.into())
         }
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Return the number of fields appended so far.
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Creates a tuple with the appended fields using the default format.
+    pub fn build(&self) -> Result<Tuple, Error> {
+        self.build_with_format(&TupleFormat::default())
+    }
+
+    /// Creates a tuple with the appended fields validating it against the
+    /// `format`.
+    ///
+    /// Returns an error describing the first field which doesn't match the
+    /// format.
+    pub fn build_with_format(&self, format: &TupleFormat) -> Result<Tuple, Error> {
+        let mut data = Vec::with_capacity(5 + self.buf.len());
+        rmp::encode::write_array_len(&mut data, self.len)?;
+        data.extend_from_slice(&self.buf);
+        unsafe { Tuple::from_raw_data_with_format(data.as_ptr(), data.len(), format) }
     }
 }
 
@@ -326,7 +752,7 @@ impl TupleIterator {
     /// Return the next Tuple field from Tuple iterator.
     ///
     /// Returns:
-    /// - `None` if `i >= box_tuple_field_count(Tuple)` or if field has a non primitive type
+    /// - `None` if `i >= box_tuple_field_count(Tuple)`
     /// - field value otherwise
     ///
     /// After call:
@@ -352,10 +778,13 @@ impl Drop for TupleIterator {
 impl TupleIterator {}
 
 #[repr(u32)]
-#[derive(Debug, ToPrimitive)]
+#[derive(Debug, ToPrimitive, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum FieldType {
     Any = 0,
+    #[serde(alias = "num")]
     Unsigned,
+    #[serde(alias = "str")]
     String,
     Number,
     Double,
@@ -371,6 +800,8 @@ pub enum FieldType {
 
 pub struct KeyDef {
     inner: *mut ffi::BoxKeyDef,
+    /// For each key part whether it has a collation. Used for hashing.
+    collated: Vec<bool>,
 }
 
 pub struct KeyDefItem {
@@ -396,7 +827,119 @@ impl KeyDef {
             inner: unsafe {
                 ffi::box_key_def_new(ids.as_mut_ptr(), types.as_mut_ptr(), size as u32)
             },
+            collated: vec![false; size],
+        }
+    }
+
+    /// Create key definition of the index `index`. Unlike [`KeyDef::new`]
+    /// it respects the collations, nullability and JSON paths of the index
+    /// parts, so tuples are ordered exactly like in the index.
+    pub fn from_index(index: &Index) -> Result<Self, Error> {
+        unsafe {
+            let index_key_def = ffi::box_index_key_def(index.space_id(), index.id());
+            if index_key_def.is_null() {
+                return Err(TarantoolError::last().into());
+            }
+            let inner = ffi::box_key_def_dup(index_key_def);
+            if inner.is_null() {
+                return Err(TarantoolError::last().into());
+            }
+
+            let region_used = ffi::box_region_used();
+            let mut part_count = 0;
+            let parts = ffi::box_key_def_dump_parts(inner, &mut part_count);
+            let collated = if parts.is_null() {
+                None
+            } else {
+                let parts = from_raw_parts(parts, part_count as _);
+                Some(parts.iter().map(|p| !p.collation.is_null()).collect())
+            };
+            ffi::box_region_truncate(region_used);
+
+            match collated {
+                Some(collated) => Ok(KeyDef { inner, collated }),
+                None => {
+                    ffi::box_key_def_delete(inner);
+                    Err(TarantoolError::last().into())
+                }
+            }
+        }
+    }
+
+    /// Extract the key of `tuple` according to the key definition.
+    ///
+    /// Returns an error if the tuple doesn't match the key definition, e.g.
+    /// a non-nullable key part is missing.
+    pub fn extract_key(&self, tuple: &Tuple) -> Result<Tuple, Error> {
+        self.with_key_data(tuple, |key| unsafe {
+            Tuple::from_raw_data_with_format(key.as_ptr(), key.len(), &TupleFormat::default())
+        })?
+    }
+
+    /// Call `f` with the raw MsgPack data of the key of `tuple`. The data is
+    /// allocated on the box region and is freed after `f` returns.
+    fn with_key_data<R>(&self, tuple: &Tuple, f: impl FnOnce(&[u8]) -> R) -> Result<R, Error> {
+        unsafe {
+            let region_used = ffi::box_region_used();
+            let mut key_size = 0;
+            let key = ffi::box_key_def_extract_key(
+                self.inner,
+                tuple.ptr.as_ptr(),
+                ffi::MULTIKEY_NONE,
+                &mut key_size,
+            );
+            let res = if key.is_null() {
+                Err(TarantoolError::last().into())
+            } else {
+                Ok(f(from_raw_parts(key as *const u8, key_size as _)))
+            };
+            ffi::box_region_truncate(region_used);
+            res
+        }
+    }
+
+    /// Check that `key` (MsgPack array) matches the key definition, e.g. the
+    /// key parts have the right types. A partial key is valid.
+    pub fn validate_key(&self, key: &[u8]) -> Result<(), Error> {
+        let mut key_size = 0;
+        let rc = unsafe {
+            ffi::box_key_def_validate_key(self.inner, key.as_ptr() as _, &mut key_size)
+        };
+        if rc < 0 {
+            return Err(TarantoolError::last().into());
         }
+        Ok(())
+    }
+
+    /// Calculate the hash of the key of `tuple`.
+    ///
+    /// The hash is consistent with [`KeyDef::compare`]: the tuples which are
+    /// equal according to the key definition have the same hash. In
+    /// particular the numbers of different types (e.g. `1` and `1.0`) are
+    /// hashed as the same value, and the content of the key parts with
+    /// collations is ignored, because e.g. `"a"` and `"A"` are equal with
+    /// `unicode_ci`.
+    ///
+    /// Decimals which are equal to integers or floats are not supported,
+    /// because they're encoded differently.
+    ///
+    /// All the tuples which don't match the key definition have the same hash.
+    pub fn hash(&self, tuple: &Tuple) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        let _ = self.with_key_data(tuple, |key| {
+            // skip the array header
+            let mut data = &key[msgpack_header_len(key)..];
+            for &collated in &self.collated {
+                if data.is_empty() {
+                    break
+                }
+                // the key data was validated by tarantool
+                let len = unsafe { msgpack_value_len(data.as_ptr()) }.unwrap_or(data.len());
+                hash_key_part(&data[..len], collated, &mut hasher);
+                data = &data[len..];
+            }
+        });
+        hasher.finish()
     }
 
     /// Compare tuples using the key definition.
@@ -429,9 +972,14 @@ impl KeyDef {
         K: AsTuple,
     {
         let key_buf = key.serialize_as_tuple().unwrap();
-        let key_buf_ptr = key_buf.as_ptr() as _;
+        self.compare_with_key_data(tuple, key_buf.as_ptr())
+    }
+
+    /// Same as [`KeyDef::compare_with_key`] but accepts the already encoded
+    /// key.
+    pub(crate) fn compare_with_key_data(&self, tuple: &Tuple, key: *const u8) -> Ordering {
         unsafe {
-            ffi::box_tuple_compare_with_key(tuple.ptr.as_ptr(), key_buf_ptr, self.inner)
+            ffi::box_tuple_compare_with_key(tuple.ptr.as_ptr(), key as _, self.inner)
                 .cmp(&0)
         }
     }
@@ -443,6 +991,111 @@ impl Drop for KeyDef {
     }
 }
 
+/// Returns the length of the MsgPack array header at the start of `data`.
+fn msgpack_header_len(data: &[u8]) -> usize {
+    match Marker::from_u8(data[0]) {
+        Marker::Array16 => 3,
+        Marker::Array32 => 5,
+        _ => 1,
+    }
+}
+
+fn hash_key_part(data: &[u8], collated: bool, state: &mut impl Hasher) {
+    let mut cur = Cursor::new(data);
+    match Marker::from_u8(data[0]) {
+        Marker::FixPos(_) | Marker::FixNeg(_)
+        | Marker::U8 | Marker::U16 | Marker::U32 | Marker::U64
+        | Marker::I8 | Marker::I16 | Marker::I32 | Marker::I64 => {
+            if let Ok(v) = rmp::decode::read_int::<i128, _>(&mut cur) {
+                (0u8, v).hash(state)
+            }
+        }
+        Marker::F32 | Marker::F64 => {
+            let v = match Marker::from_u8(data[0]) {
+                Marker::F32 => rmp::decode::read_f32(&mut cur).map(f64::from).ok(),
+                _ => rmp::decode::read_f64(&mut cur).ok(),
+            };
+            if let Some(v) = v {
+                // integral floats are equal to the corresponding integers
+                if v.fract() == 0.0 && v.abs() < 2f64.powi(64) {
+                    (0u8, v as i128).hash(state)
+                } else {
+                    (1u8, v.to_bits()).hash(state)
+                }
+            }
+        }
+        Marker::FixStr(_) | Marker::Str8 | Marker::Str16 | Marker::Str32 if collated => {
+            2u8.hash(state)
+        }
+        _ => (3u8, data).hash(state),
+    }
+}
+
+/// A tuple which is compared, ordered and hashed according to a [`KeyDef`],
+/// so that it can be used as a key of `BTreeMap` or `HashMap`, or sorted the
+/// same way as in a tarantool index.
+///
+/// Example:
+/// ```no_run
+/// use std::rc::Rc;
+/// use tarantool::space::Space;
+/// use tarantool::tuple::{KeyDef, OrderedTuple};
+///
+/// let space = Space::find("users").unwrap();
+/// let index = space.primary_key();
+/// let key_def = Rc::new(KeyDef::from_index(&index).unwrap());
+/// let mut tuples: Vec<_> = index.select(Default::default(), &())
+///     .unwrap()
+///     .map(|t| OrderedTuple::new(t, key_def.clone()))
+///     .collect();
+/// tuples.sort();
+/// ```
+#[derive(Clone)]
+pub struct OrderedTuple {
+    tuple: Tuple,
+    key_def: Rc<KeyDef>,
+}
+
+impl OrderedTuple {
+    pub fn new(tuple: Tuple, key_def: Rc<KeyDef>) -> Self {
+        Self { tuple, key_def }
+    }
+
+    pub fn tuple(&self) -> &Tuple {
+        &self.tuple
+    }
+
+    pub fn into_tuple(self) -> Tuple {
+        self.tuple
+    }
+}
+
+impl PartialEq for OrderedTuple {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedTuple {}
+
+impl PartialOrd for OrderedTuple {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedTuple {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key_def.compare(&self.tuple, &other.tuple)
+    }
+}
+
+impl Hash for OrderedTuple {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.key_def.hash(&self.tuple))
+    }
+}
+
 fn field_value_from_ptr<T>(value_ptr: *mut u8) -> Result<Option<T>, Error>
 where
     T: DeserializeOwned,
@@ -451,42 +1104,73 @@ where
         return Ok(None);
     }
 
-    let marker = Marker::from_u8(unsafe { *value_ptr });
-    Ok(match marker {
-        Marker::FixStr(str_len) => {
-            let buf = unsafe { from_raw_parts(value_ptr as *mut u8, (str_len + 1) as usize) };
-            Some(rmp_serde::from_read_ref::<_, T>(buf)?)
-        }
-
-        Marker::Str8 | Marker::Str16 | Marker::Str32 => {
-            let head = unsafe { from_raw_parts(value_ptr as *mut u8, 9) };
-            let len = rmp::decode::read_str_len(&mut Cursor::new(head))?;
+    let buf = unsafe {
+        let len = msgpack_value_len(value_ptr)?;
+        from_raw_parts(value_ptr as *const u8, len)
+    };
+    Ok(Some(rmp_serde::from_read_ref::<_, T>(buf)?))
+}
 
-            let buf = unsafe { from_raw_parts(value_ptr as *mut u8, (len + 9) as usize) };
-            Some(rmp_serde::from_read_ref::<_, T>(buf)?)
+/// Returns the size in bytes of the MsgPack value starting at `ptr`
+/// including the nested values of arrays and maps.
+///
+/// Returns an error if a reserved marker is encountered.
+///
+/// # Safety
+/// `ptr` must point to a complete MsgPack value, which is always the case
+/// for the tuple fields, because tarantool validates the tuple data.
+unsafe fn msgpack_value_len(ptr: *const u8) -> Result<usize, Error> {
+    unsafe fn read_len(p: &mut *const u8, size: usize) -> usize {
+        let mut len = 0;
+        for _ in 0..size {
+            len = len << 8 | **p as usize;
+            *p = p.add(1);
         }
+        len
+    }
 
-        Marker::FixPos(_)
-        | Marker::FixNeg(_)
-        | Marker::Null
-        | Marker::True
-        | Marker::False
-        | Marker::U8
-        | Marker::U16
-        | Marker::U32
-        | Marker::U64
-        | Marker::I8
-        | Marker::I16
-        | Marker::I32
-        | Marker::I64
-        | Marker::F32
-        | Marker::F64 => {
-            let buf = unsafe { from_raw_parts(value_ptr as *mut u8, 9) };
-            Some(rmp_serde::from_read_ref::<_, T>(buf)?)
+    let mut p = ptr;
+    // number of values left to skip
+    let mut count: usize = 1;
+    while count > 0 {
+        count -= 1;
+        let marker = Marker::from_u8(*p);
+        p = p.add(1);
+        match marker {
+            Marker::FixPos(_)
+            | Marker::FixNeg(_)
+            | Marker::Null
+            | Marker::True
+            | Marker::False => {}
+            Marker::U8 | Marker::I8 => p = p.add(1),
+            Marker::U16 | Marker::I16 => p = p.add(2),
+            Marker::U32 | Marker::I32 | Marker::F32 => p = p.add(4),
+            Marker::U64 | Marker::I64 | Marker::F64 => p = p.add(8),
+            Marker::FixStr(len) => p = p.add(len as _),
+            Marker::Str8 | Marker::Bin8 => p = p.add(read_len(&mut p, 1)),
+            Marker::Str16 | Marker::Bin16 => p = p.add(read_len(&mut p, 2)),
+            Marker::Str32 | Marker::Bin32 => p = p.add(read_len(&mut p, 4)),
+            Marker::FixArray(len) => count += len as usize,
+            Marker::Array16 => count += read_len(&mut p, 2),
+            Marker::Array32 => count += read_len(&mut p, 4),
+            Marker::FixMap(len) => count += 2 * len as usize,
+            Marker::Map16 => count += 2 * read_len(&mut p, 2),
+            Marker::Map32 => count += 2 * read_len(&mut p, 4),
+            // 1 byte of ext type + data
+            Marker::FixExt1 => p = p.add(1 + 1),
+            Marker::FixExt2 => p = p.add(1 + 2),
+            Marker::FixExt4 => p = p.add(1 + 4),
+            Marker::FixExt8 => p = p.add(1 + 8),
+            Marker::FixExt16 => p = p.add(1 + 16),
+            Marker::Ext8 => p = p.add(1 + read_len(&mut p, 1)),
+            Marker::Ext16 => p = p.add(1 + read_len(&mut p, 2)),
+            Marker::Ext32 => p = p.add(1 + read_len(&mut p, 4)),
+            Marker::Reserved => {
+                return Err(rmp_serde::decode::Error::TypeMismatch(Marker::Reserved).into())
+            }
         }
-
-        _ => None,
-    })
+    }
+    Ok(p as usize - ptr as usize)
 }
 
 #[repr(C)]
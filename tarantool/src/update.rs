@@ -0,0 +1,240 @@
+//! Box: update operations
+//!
+//! [`UpdateOps`] is a builder for the list of operations accepted by
+//! [space.update()](../space/struct.Space.html#method.update),
+//! [space.upsert()](../space/struct.Space.html#method.upsert), the same methods
+//! of [Index](../index/struct.Index.html) and their
+//! [net_box](../net_box/index.html) counterparts.
+//!
+//! Fields are specified either by number (0-based, negative numbers count from
+//! the end of the tuple) or by name/JSON path, e.g. `"name"` or `"data.tags[1]"`.
+//!
+//! Example:
+//! ```no_run
+//! use tarantool::space::Space;
+//! use tarantool::update::UpdateOps;
+//!
+//! let mut space = Space::find("users").unwrap();
+//! let ops = UpdateOps::new()
+//!     .assign("name", "Alice")
+//!     .add(2, 1)
+//!     .splice(3, 0, 1, "X");
+//! space.update(&(1,), &ops).unwrap();
+//! ```
+//!
+//! See also:
+//! - [Lua reference: space_object:update()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_space/update/)
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+use crate::tuple::AsTuple;
+
+/// A reference to a tuple field in an update operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldRef {
+    /// Field number. 0 is the first field, -1 is the last one.
+    Index(i64),
+    /// Field name or JSON path.
+    Path(String),
+}
+
+impl From<u32> for FieldRef {
+    fn from(i: u32) -> Self {
+        Self::Index(i as _)
+    }
+}
+
+impl From<i32> for FieldRef {
+    fn from(i: i32) -> Self {
+        Self::Index(i as _)
+    }
+}
+
+impl From<usize> for FieldRef {
+    fn from(i: usize) -> Self {
+        Self::Index(i as _)
+    }
+}
+
+impl From<&str> for FieldRef {
+    fn from(path: &str) -> Self {
+        Self::Path(path.into())
+    }
+}
+
+impl From<String> for FieldRef {
+    fn from(path: String) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl Serialize for FieldRef {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Index(i) => serializer.serialize_i64(*i),
+            Self::Path(path) => serializer.serialize_str(path),
+        }
+    }
+}
+
+/// A list of update operations.
+///
+/// The operations are applied sequentially in the order they were added, each
+/// one to the result of the previous ones. Serializes as a MsgPack array of
+/// operations: `[['=', field, value], ['#', field, count], ...]`.
+#[derive(Default)]
+pub struct UpdateOps<'a> {
+    ops: Vec<Op<'a>>,
+}
+
+impl<'a> UpdateOps<'a> {
+    pub fn new() -> Self {
+        Self { ops: vec![] }
+    }
+
+    fn with_value<V>(mut self, op: char, field: impl Into<FieldRef>, value: V) -> Self
+    where
+        V: Serialize + 'a,
+    {
+        self.ops.push(Op { op, field: field.into(), args: Args::Value(Box::new(value)) });
+        self
+    }
+
+    /// Assign `value` to the field (`'='`).
+    pub fn assign<V>(self, field: impl Into<FieldRef>, value: V) -> Self
+    where
+        V: Serialize + 'a,
+    {
+        self.with_value('=', field, value)
+    }
+
+    /// Add `value` to the numeric field (`'+'`).
+    pub fn add<V>(self, field: impl Into<FieldRef>, value: V) -> Self
+    where
+        V: Serialize + 'a,
+    {
+        self.with_value('+', field, value)
+    }
+
+    /// Subtract `value` from the numeric field (`'-'`).
+    pub fn sub<V>(self, field: impl Into<FieldRef>, value: V) -> Self
+    where
+        V: Serialize + 'a,
+    {
+        self.with_value('-', field, value)
+    }
+
+    /// Bitwise AND of the unsigned field and `value` (`'&'`).
+    pub fn bit_and(self, field: impl Into<FieldRef>, value: u64) -> Self {
+        self.with_value('&', field, value)
+    }
+
+    /// Bitwise OR of the unsigned field and `value` (`'|'`).
+    pub fn bit_or(self, field: impl Into<FieldRef>, value: u64) -> Self {
+        self.with_value('|', field, value)
+    }
+
+    /// Bitwise XOR of the unsigned field and `value` (`'^'`).
+    pub fn bit_xor(self, field: impl Into<FieldRef>, value: u64) -> Self {
+        self.with_value('^', field, value)
+    }
+
+    /// Insert a new field with `value` before the specified one (`'!'`).
+    pub fn insert<V>(self, field: impl Into<FieldRef>, value: V) -> Self
+    where
+        V: Serialize + 'a,
+    {
+        self.with_value('!', field, value)
+    }
+
+    /// Delete `count` fields starting with the specified one (`'#'`).
+    pub fn delete(mut self, field: impl Into<FieldRef>, count: u32) -> Self {
+        self.ops.push(Op { op: '#', field: field.into(), args: Args::Count(count) });
+        self
+    }
+
+    /// Replace `count` bytes of the string field starting at position `start`
+    /// (1-based, negative positions count from the end) with `value` (`':'`).
+    pub fn splice(
+        mut self,
+        field: impl Into<FieldRef>,
+        start: i32,
+        count: u32,
+        value: impl Into<String>,
+    ) -> Self {
+        self.ops.push(Op {
+            op: ':',
+            field: field.into(),
+            args: Args::Splice { start, count, value: value.into() },
+        });
+        self
+    }
+
+    /// Returns the number of operations.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns `true` if there are no operations.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+impl Serialize for UpdateOps<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.ops.len()))?;
+        for op in &self.ops {
+            seq.serialize_element(op)?;
+        }
+        seq.end()
+    }
+}
+
+impl AsTuple for UpdateOps<'_> {}
+
+////////////////////////////////////////////////////////////////////////////////
+// Op
+////////////////////////////////////////////////////////////////////////////////
+
+struct Op<'a> {
+    op: char,
+    field: FieldRef,
+    args: Args<'a>,
+}
+
+enum Args<'a> {
+    Value(Box<dyn erased_serde::Serialize + 'a>),
+    Count(u32),
+    Splice { start: i32, count: u32, value: String },
+}
+
+impl Serialize for Op<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let len = match self.args {
+            Args::Value(_) | Args::Count(_) => 3,
+            Args::Splice { .. } => 5,
+        };
+        let mut seq = serializer.serialize_seq(Some(len))?;
+        seq.serialize_element(&self.op)?;
+        seq.serialize_element(&self.field)?;
+        match &self.args {
+            Args::Value(value) => seq.serialize_element(value)?,
+            Args::Count(count) => seq.serialize_element(count)?,
+            Args::Splice { start, count, value } => {
+                seq.serialize_element(start)?;
+                seq.serialize_element(count)?;
+                seq.serialize_element(value)?;
+            }
+        }
+        seq.end()
+    }
+}
@@ -1,3 +1,6 @@
+use serde::de::{self, DeserializeOwned, Deserializer, IntoDeserializer};
+use serde::Deserialize;
+
 pub trait IntoClones<Tuple>: Clone {
     fn into_clones(self) -> Tuple;
 }
@@ -40,3 +43,24 @@ macro_rules! tuple_from_box_api {
     }
 }
 
+/// Converts a type name used in the system spaces, e.g. `"unsigned"` or
+/// `"TREE"`, with the `Deserialize` implementation of `T`. The names are case
+/// insensitive the same as in tarantool, so the variants of `T` must be
+/// renamed to lowercase.
+pub(crate) fn from_schema_name<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let deserializer: de::value::StringDeserializer<de::value::Error> =
+        name.to_lowercase().into_deserializer();
+    T::deserialize(deserializer).ok()
+}
+
+/// Deserializes a type name used in the system spaces, see
+/// [`from_schema_name`].
+pub(crate) fn deserialize_schema_name<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let name = String::deserialize(deserializer)?;
+    from_schema_name(&name)
+        .ok_or_else(|| de::Error::custom(format!("unknown type '{}'", name)))
+}
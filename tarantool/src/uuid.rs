@@ -210,47 +210,23 @@ impl std::str::FromStr for Uuid {
 /// Tuple
 ////////////////////////////////////////////////////////////////////////////////
 
-impl serde::Serialize for Uuid {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        #[derive(Serialize)]
-        struct _ExtStruct((i8, serde_bytes::ByteBuf));
-
-        let data = self.as_bytes();
-        _ExtStruct((ffi::MP_UUID, serde_bytes::ByteBuf::from(data as &[_])))
-            .serialize(serializer)
-    }
-}
-
-impl<'de> serde::Deserialize<'de> for Uuid {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        #[derive(Deserialize)]
-        struct _ExtStruct((i8, serde_bytes::ByteBuf));
-
-        let _ExtStruct((kind, bytes)) = serde::Deserialize::deserialize(deserializer)?;
+impl crate::msgpack::Ext for Uuid {
+    const TYPE: i8 = ffi::MP_UUID;
+    const NAME: &'static str = "UUID";
 
-        if kind != ffi::MP_UUID {
-            return Err(serde::de::Error::custom(
-                format!("Expected UUID, found msgpack ext #{}", kind)
-            ))
-        }
+    fn to_ext_data(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
 
-        let data = bytes.into_vec();
-        Self::try_from_slice(&data)
-            .ok_or_else(|| serde::de::Error::custom(
-                format!(
-                    "Not enough bytes for UUID: expected 16, got {}",
-                    data.len()
-                )
-            ))
+    fn from_ext_data(data: &[u8]) -> Result<Self, String> {
+        Self::try_from_slice(data).ok_or_else(|| {
+            format!("Not enough bytes for UUID: expected 16, got {}", data.len())
+        })
     }
 }
 
+crate::msgpack::impl_serde_for_ext!(Uuid);
+
 ////////////////////////////////////////////////////////////////////////////////
 /// Lua
 ////////////////////////////////////////////////////////////////////////////////
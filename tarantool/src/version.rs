@@ -0,0 +1,197 @@
+//! Tarantool version
+//!
+//! The version of the running tarantool can be used to enable the features
+//! which aren't supported by the older versions:
+//! ```no_run
+//! use tarantool::version::Feature;
+//!
+//! if tarantool::supports(Feature::SyncReplication) {
+//!     // create a synchronous space
+//! }
+//! println!("running on tarantool {}", tarantool::version());
+//! ```
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::log::{self, SayLevel};
+
+/// A parsed tarantool version, e.g. `2.10.0-beta2-91-g08c9b4963`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    /// Pre-release label, e.g. `beta2` or `entrypoint`.
+    pub pre_release: Option<String>,
+    /// Number of commits after the tag, 0 for the releases.
+    pub commits: u32,
+}
+
+impl Version {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch, pre_release: None, commits: 0 }
+    }
+}
+
+/// Pre-releases precede the release with the same number, e.g.
+/// `2.11.0-entrypoint < 2.11.0-beta1 < 2.11.0-rc1 < 2.11.0 < 2.11.0-1-g123`.
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // `entrypoint` is the tag at the start of the development of a version.
+        // The other labels are compared by the name and then by the number,
+        // e.g. `beta2 < beta10 < rc1`
+        fn pre_release_key(v: &Version) -> (u8, &str, Option<u32>, &str) {
+            let label = match v.pre_release.as_deref() {
+                Some("entrypoint") => return (0, "", None, ""),
+                Some(label) => label,
+                None => return (2, "", None, ""),
+            };
+            let split = label.find(|c: char| c.is_ascii_digit()).unwrap_or(label.len());
+            let (name, number) = label.split_at(split);
+            (1, name, number.parse().ok(), number)
+        }
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| pre_release_key(self).cmp(&pre_release_key(other)))
+            .then_with(|| self.commits.cmp(&other.commits))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre_release) = &self.pre_release {
+            write!(f, "-{}", pre_release)?;
+        }
+        if self.commits != 0 {
+            write!(f, "-{}", self.commits)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid tarantool version: '{0}'")]
+pub struct ParseVersionError(String);
+
+/// Parses the version in the format of `_TARANTOOL` or `box.info.version`,
+/// i.e. `major.minor.patch[-pre_release][-commits][-ghash][-rrevision]`.
+impl FromStr for Version {
+    type Err = ParseVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseVersionError(s.into());
+        let mut parts = s.split('-');
+        let mut numbers = parts.next().ok_or_else(err)?.split('.');
+        let mut number = || -> Result<u32, ParseVersionError> {
+            numbers.next().and_then(|n| n.parse().ok()).ok_or_else(err)
+        };
+        let mut res = Self::new(number()?, number()?, number()?);
+        for part in parts {
+            if let Ok(commits) = part.parse() {
+                res.commits = commits;
+                // the rest is the commit hash and the package revision
+                break;
+            }
+            if res.pre_release.is_some() || part.is_empty() {
+                return Err(err());
+            }
+            res.pre_release = Some(part.into());
+        }
+        Ok(res)
+    }
+}
+
+/// Features which are only available since some version of tarantool.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Feature {
+    /// Functional indexes (`IndexOptions::func`).
+    FunctionalIndexes,
+    /// Synchronous replication (`is_sync` space option).
+    SyncReplication,
+    /// `hint` option of the memtx tree indexes.
+    IndexHint,
+    /// `datetime` and `interval` field types.
+    Datetime,
+    /// Transaction isolation levels.
+    TxnIsolation,
+}
+
+impl Feature {
+    /// Returns the first version of tarantool supporting the feature.
+    pub fn since(self) -> Version {
+        match self {
+            Self::FunctionalIndexes => Version::new(2, 2, 1),
+            Self::SyncReplication => Version::new(2, 6, 1),
+            Self::IndexHint => Version::new(2, 6, 1),
+            Self::Datetime => Version::new(2, 10, 0),
+            Self::TxnIsolation => Version::new(2, 10, 0),
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::FunctionalIndexes => "functional indexes",
+            Self::SyncReplication => "synchronous replication",
+            Self::IndexHint => "index hints",
+            Self::Datetime => "datetime",
+            Self::TxnIsolation => "transaction isolation levels",
+        };
+        f.write_str(name)
+    }
+}
+
+thread_local! {
+    static VERSION: Version = {
+        let lua = crate::global_lua();
+        let version: Option<String> = lua.get("_TARANTOOL");
+        match version.as_deref().map(str::parse) {
+            Some(Ok(version)) => version,
+            _ => {
+                let msg = format!(
+                    "unknown tarantool version {:?}, the optional features are disabled",
+                    version
+                );
+                log::say(SayLevel::Warn, file!(), line!() as _, None, &msg);
+                Version::new(0, 0, 0)
+            }
+        }
+    };
+}
+
+/// Returns the version of the running tarantool.
+///
+/// The version is read once from the `_TARANTOOL` Lua variable. If it can't
+/// be parsed, `0.0.0` is returned, so that none of the [`Feature`]s are
+/// considered supported.
+pub fn version() -> Version {
+    VERSION.with(Clone::clone)
+}
+
+/// Returns `true` if the running tarantool supports the `feature`.
+pub fn supports(feature: Feature) -> bool {
+    version() >= feature.since()
+}
+
+/// Returns the `Unsupported` error if the running tarantool doesn't support
+/// the `feature`.
+pub(crate) fn check_supported(feature: Feature) -> crate::Result<()> {
+    if supports(feature) {
+        return Ok(());
+    }
+    set_error!(
+        crate::error::TarantoolErrorCode::Unsupported,
+        "Tarantool {} does not support {} (since {})", version(), feature, feature.since()
+    );
+    Err(crate::error::TarantoolError::last().into())
+}
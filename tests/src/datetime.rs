@@ -0,0 +1,103 @@
+use tarantool::{
+    datetime::{Adjust, Datetime, Interval},
+    error::ErrorInfo,
+    tlua::LuaFunction,
+    tuple::Tuple,
+    uuid::Uuid,
+};
+
+pub fn to_tuple() {
+    let dt = Datetime::from_timestamp(1_646_137_800, 500_000_000).with_tzoffset(180);
+    let t = Tuple::from_struct(&(dt,)).unwrap();
+    let lua = tarantool::global_lua();
+    let f: LuaFunction<_> = lua.eval("return function(t) return tostring(t[1]) end").unwrap();
+    let s: String = f.call_with_args(t).unwrap();
+    assert_eq!(s, "2022-03-01T15:30:00.5+0300");
+    assert_eq!(dt.to_string(), s);
+}
+
+pub fn from_tuple() {
+    let lua = tarantool::global_lua();
+    let t: Tuple = lua.eval(
+        "return box.tuple.new(require('datetime').new{
+            year = 2022, month = 3, day = 1, hour = 12, min = 30, tzoffset = 180
+        })"
+    ).unwrap();
+    let (dt,): (Datetime,) = t.as_struct().unwrap();
+    assert_eq!(dt.timestamp(), 1_646_127_000);
+    assert_eq!(dt.nsec(), 0);
+    assert_eq!(dt.tzoffset(), 180);
+    assert_eq!(dt.to_string(), "2022-03-01T12:30:00+0300");
+}
+
+pub fn round_trip() {
+    let values = [
+        Datetime::from_timestamp(0, 0),
+        Datetime::from_timestamp(-1, 1),
+        Datetime::from_timestamp(1_646_127_000, 0).with_tzoffset(-90),
+        Datetime::now(),
+    ];
+    for dt in values.iter() {
+        let t = Tuple::from_struct(&(dt,)).unwrap();
+        let (res,): (Datetime,) = t.into_struct().unwrap();
+        assert_eq!(&res, dt);
+    }
+
+    let interval = Interval {
+        year: 1,
+        month: -2,
+        hour: 36,
+        nsec: 5,
+        adjust: Adjust::Last,
+        ..Default::default()
+    };
+    let t = Tuple::from_struct(&(interval, Interval::default())).unwrap();
+    let res: (Interval, Interval) = t.into_struct().unwrap();
+    assert_eq!(res, (interval, Interval::default()));
+}
+
+pub fn interval_from_lua() {
+    let lua = tarantool::global_lua();
+    let t: Tuple = lua.eval(
+        "return box.tuple.new(require('datetime').interval.new{
+            year = 1, week = 2, min = -3, adjust = 'excess'
+        })"
+    ).unwrap();
+    let (interval,): (Interval,) = t.as_struct().unwrap();
+    assert_eq!(interval, Interval {
+        year: 1,
+        week: 2,
+        min: -3,
+        adjust: Adjust::Excess,
+        ..Default::default()
+    });
+}
+
+pub fn error_round_trip() {
+    let error = ErrorInfo {
+        error_type: "ClientError".into(),
+        message: "Something went wrong".into(),
+        file: "file.c".into(),
+        line: 13,
+        errno: 0,
+        code: 32,
+        cause: Some(Box::new(ErrorInfo {
+            error_type: "CustomError".into(),
+            message: "Because of this".into(),
+            ..Default::default()
+        })),
+    };
+    let t = Tuple::from_struct(&(&error,)).unwrap();
+    let (res,): (ErrorInfo,) = t.into_struct().unwrap();
+    assert_eq!(res, error);
+}
+
+pub fn ext_type_mismatch() {
+    let u = Uuid::parse_str("30de7784-33e2-4393-a8cd-b67534db2432").unwrap();
+    let t = Tuple::from_struct(&(u,)).unwrap();
+    let err = t.as_struct::<(Datetime,)>().err().unwrap();
+    assert!(
+        err.to_string().contains("Expected Datetime, found msgpack ext #2"),
+        "{}", err
+    );
+}
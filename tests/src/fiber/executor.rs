@@ -0,0 +1,109 @@
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use crate::common::{check_yield, YieldResult::{Yields, DoesntYield}};
+use tarantool::fiber;
+
+/// A future which becomes ready after [`Flag::set`] is called.
+#[derive(Default)]
+struct Flag {
+    is_set: Cell<bool>,
+    waker: RefCell<Option<Waker>>,
+    polls: Cell<usize>,
+}
+
+impl Flag {
+    fn set(&self) {
+        self.is_set.set(true);
+        if let Some(waker) = self.waker.take() {
+            waker.wake()
+        }
+    }
+}
+
+struct WaitFlag(Rc<Flag>);
+
+impl Future for WaitFlag {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let flag = &self.0;
+        flag.polls.set(flag.polls.get() + 1);
+        if flag.is_set.get() {
+            Poll::Ready(())
+        } else {
+            flag.waker.replace(Some(cx.waker().clone()));
+            Poll::Pending
+        }
+    }
+}
+
+pub fn block_on_ready() {
+    assert_eq!(
+        check_yield(|| fiber::block_on(async { 69 })),
+        DoesntYield(69)
+    );
+}
+
+pub fn block_on_pending() {
+    let flag = Rc::new(Flag::default());
+
+    let f = {
+        let flag = flag.clone();
+        fiber::defer_proc(move || flag.set())
+    };
+
+    let wait = WaitFlag(flag.clone());
+    assert_eq!(
+        check_yield(|| fiber::block_on(async { wait.await; 420 })),
+        Yields(420)
+    );
+    assert_eq!(flag.polls.get(), 2);
+
+    f.join();
+}
+
+/// A future which wakes itself up and returns `Pending` on the first poll.
+struct WakeSelf(bool);
+
+impl Future for WakeSelf {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+pub fn block_on_woken_while_polled() {
+    assert_eq!(
+        check_yield(|| fiber::block_on(WakeSelf(false))),
+        DoesntYield(())
+    );
+}
+
+pub fn spawn_async() {
+    let flag = Rc::new(Flag::default());
+
+    let jh = {
+        let flag = flag.clone();
+        fiber::spawn_async(async move {
+            WaitFlag(flag).await;
+            "hello"
+        })
+    };
+
+    flag.set();
+
+    assert_eq!(jh.join(), "hello");
+}
@@ -11,6 +11,11 @@ use tarantool::util::IntoClones;
 
 pub mod old;
 pub mod channel;
+pub mod executor;
+pub mod panic;
+pub mod scope;
+pub mod select;
+pub mod timer;
 
 pub fn immediate() {
     let jh = fiber::Builder::new()
@@ -405,3 +410,63 @@ pub fn deferred_with_cond() {
     }
 }
 
+
+pub fn cancel() {
+    let jh = fiber::start(|| {
+        let mut count = 0;
+        while !fiber::is_cancelled() {
+            fiber::sleep(Duration::from_secs(100));
+            count += 1;
+        }
+        count
+    });
+    assert!(!jh.is_cancelled());
+    assert!(!jh.is_finished());
+
+    jh.cancel();
+    assert!(jh.is_cancelled());
+    assert_eq!(jh.join(), 1);
+
+    let jh = fiber::start_proc(|| ());
+    assert!(jh.is_finished());
+    // does nothing for a finished fiber
+    jh.cancel();
+    jh.join();
+}
+
+pub fn wakeup() {
+    let jh = fiber::start(|| {
+        fiber::sleep(Duration::from_secs(100));
+        "woken up"
+    });
+
+    jh.wakeup();
+    assert_eq!(jh.join(), "woken up");
+}
+
+pub fn join_timeout() {
+    let cond = Rc::new(fiber::Cond::new());
+
+    let jh = {
+        let cond = cond.clone();
+        fiber::start(move || {
+            cond.wait();
+            69
+        })
+    };
+
+    let jh = jh.join_timeout(Duration::from_millis(1)).unwrap_err();
+    assert!(!jh.is_finished());
+
+    cond.signal();
+    assert_eq!(jh.join_timeout(Duration::from_secs(1)).ok(), Some(69));
+
+    let jh = {
+        let cond = cond.clone();
+        fiber::start_proc(move || { cond.wait(); })
+    };
+
+    let jh = jh.join_timeout(Duration::from_millis(1)).unwrap_err();
+    cond.signal();
+    assert!(jh.join_timeout(Duration::from_secs(1)).is_ok());
+}
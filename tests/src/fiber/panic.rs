@@ -0,0 +1,78 @@
+use std::{
+    cell::RefCell,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use tarantool::fiber;
+
+pub fn start_try_join() {
+    let jh = fiber::start(|| -> i32 { panic!("oops") });
+    let p = jh.try_join().unwrap_err();
+    assert_eq!(p.message(), Some("oops"));
+
+    let jh = fiber::start(|| 69);
+    assert_eq!(jh.try_join().unwrap(), 69);
+}
+
+pub fn start_proc_try_join() {
+    let jh = fiber::start_proc(|| panic!("oops: {}", 420));
+    let p = jh.try_join().unwrap_err();
+    assert_eq!(p.message(), Some("oops: 420"));
+    assert_eq!(p.to_string(), "fiber panicked: oops: 420");
+
+    let jh = fiber::start_proc(|| ());
+    assert!(jh.try_join().is_ok());
+}
+
+pub fn defer_try_join() {
+    let jh = fiber::defer(|| -> i32 { panic!("oops") });
+    let p = jh.try_join().unwrap_err();
+    assert_eq!(p.message(), Some("oops"));
+
+    let jh = fiber::defer(|| 69);
+    assert_eq!(jh.try_join().unwrap(), 69);
+}
+
+pub fn defer_proc_try_join() {
+    let jh = fiber::defer_proc(|| panic!("oops"));
+    let p = jh.try_join().unwrap_err();
+    assert_eq!(p.message(), Some("oops"));
+
+    let jh = fiber::defer_proc(|| ());
+    assert!(jh.try_join().is_ok());
+}
+
+pub fn join_resumes_panic() {
+    let jh = fiber::start(|| -> i32 { panic!("oops") });
+    let payload = panic::catch_unwind(AssertUnwindSafe(|| jh.join())).unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"oops"));
+
+    let jh = fiber::defer_proc(|| panic!("oops"));
+    let payload = panic::catch_unwind(AssertUnwindSafe(|| jh.join())).unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"oops"));
+}
+
+pub fn panic_hook() {
+    let messages = Rc::new(RefCell::new(vec![]));
+    {
+        let messages = messages.clone();
+        fiber::set_panic_hook(move |p| {
+            messages.borrow_mut().push(p.message().unwrap().to_string())
+        });
+    }
+
+    let res = fiber::start_proc(|| panic!("first")).try_join();
+    assert!(res.is_err());
+    let res = fiber::defer(|| -> () { panic!("second") }).try_join();
+    assert!(res.is_err());
+    fiber::start_proc(|| ()).join();
+
+    drop(fiber::take_panic_hook());
+    assert_eq!(*messages.borrow(), vec!["first", "second"]);
+
+    // default hook is restored
+    let res = fiber::start_proc(|| panic!("third")).try_join();
+    assert!(res.is_err());
+    assert_eq!(messages.borrow().len(), 2);
+}
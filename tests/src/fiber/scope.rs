@@ -0,0 +1,91 @@
+use std::{
+    cell::Cell,
+    time::Duration,
+};
+
+use tarantool::fiber;
+
+pub fn borrow() {
+    let mut data = vec![1, 2, 3];
+    let count = Cell::new(0);
+
+    let sum = fiber::scope(|s| {
+        let jh = s.spawn(|| data.iter().sum::<i32>());
+        s.spawn(|| count.set(count.get() + 1));
+        s.spawn(|| count.set(count.get() + 1));
+        jh.join()
+    });
+
+    data.push(4);
+    assert_eq!(sum, 6);
+    assert_eq!(count.get(), 2);
+}
+
+pub fn cancel_on_exit() {
+    let iterations = Cell::new(0);
+    let finished = Cell::new(false);
+
+    fiber::scope(|s| {
+        s.spawn(|| {
+            while !fiber::is_cancelled() {
+                iterations.set(iterations.get() + 1);
+                fiber::sleep(Duration::from_secs(100));
+            }
+            finished.set(true);
+        });
+    });
+
+    assert_eq!(iterations.get(), 1);
+    assert!(finished.get());
+}
+
+pub fn joined_on_panic() {
+    let finished = Cell::new(false);
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        fiber::scope(|s| {
+            s.spawn(|| {
+                fiber::sleep(Duration::from_secs(100));
+                finished.set(true);
+            });
+            panic!("oops")
+        })
+    }));
+
+    assert!(res.is_err());
+    assert!(finished.get());
+}
+
+pub fn nested_spawn() {
+    let count = Cell::new(0);
+    let count = &count;
+
+    fiber::scope(|s| {
+        s.spawn(move || {
+            count.set(count.get() + 1);
+            s.spawn(move || {
+                fiber::sleep(Duration::ZERO);
+                count.set(count.get() + 1);
+            });
+        });
+    });
+
+    assert_eq!(count.get(), 2);
+}
+
+pub fn handle_methods() {
+    fiber::scope(|s| {
+        let jh = s.spawn(|| {
+            fiber::sleep(Duration::from_secs(100));
+            fiber::is_cancelled()
+        });
+        assert!(!jh.is_finished());
+        let jh = jh.join_timeout(Duration::from_millis(1)).unwrap_err();
+        jh.cancel();
+        assert!(jh.is_cancelled());
+        assert!(jh.join());
+
+        let jh = s.spawn(|| -> () { panic!("oops") });
+        assert_eq!(jh.try_join().unwrap_err().message(), Some("oops"));
+    });
+}
@@ -0,0 +1,199 @@
+use std::time::Duration;
+
+use crate::common::{check_yield, YieldResult::{Yields, DoesntYield}};
+use tarantool::fiber;
+use tarantool::util::IntoClones;
+
+pub fn recv_ready() {
+    let ch1 = fiber::Channel::<&str>::new(1);
+    let ch2 = fiber::Channel::new(1);
+    ch2.send("hello").unwrap();
+
+    let mut sel = fiber::Select::new();
+    let _op1 = sel.recv(&ch1);
+    let op2 = sel.recv(&ch2);
+
+    assert_eq!(
+        check_yield(|| sel.select().unwrap().index()),
+        DoesntYield(op2)
+    );
+    let oper = sel.try_select().unwrap();
+    assert_eq!(oper.index(), op2);
+    assert_eq!(oper.recv(&ch2), Some("hello"));
+}
+
+pub fn send_ready() {
+    let ch1 = fiber::Channel::<&str>::new(0);
+    let ch2 = fiber::Channel::new(1);
+
+    let mut sel = fiber::Select::new();
+    let _op1 = sel.send(&ch1);
+    let op2 = sel.send(&ch2);
+
+    let oper = sel.try_select().unwrap();
+    assert_eq!(oper.index(), op2);
+    oper.send(&ch2, "hello").unwrap();
+
+    assert_eq!(ch2.try_recv(), Ok("hello"));
+}
+
+pub fn try_select_empty() {
+    let ch = fiber::Channel::<()>::new(0);
+    let cond = fiber::Cond::new();
+
+    let mut sel = fiber::Select::new();
+    sel.recv(&ch);
+    sel.cond(&cond);
+
+    assert!(sel.try_select().is_none());
+}
+
+pub fn timeout() {
+    let ch = fiber::Channel::<()>::new(0);
+    let cond = fiber::Cond::new();
+
+    let mut sel = fiber::Select::new();
+    sel.recv(&ch);
+    sel.cond(&cond);
+
+    assert_eq!(
+        check_yield(|| sel.select_timeout(Duration::from_micros(1)).err()),
+        Yields(Some(fiber::SelectError::Timeout))
+    );
+}
+
+pub fn wakes_on_send() {
+    let (tx, rx) = fiber::Channel::new(0).into_clones();
+    let other = fiber::Channel::<()>::new(0);
+
+    let f = fiber::defer_proc(move || tx.send("hello").unwrap());
+
+    let mut sel = fiber::Select::new();
+    sel.recv(&other);
+    let op = sel.recv(&rx);
+    let oper = sel.select_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(oper.index(), op);
+    assert_eq!(oper.recv(&rx), Some("hello"));
+
+    f.join();
+}
+
+pub fn wakes_on_recv() {
+    let (tx, rx) = fiber::Channel::new(0).into_clones();
+
+    let f = fiber::defer(move || rx.recv());
+
+    let mut sel = fiber::Select::new();
+    let op = sel.send(&tx);
+    let oper = sel.select_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(oper.index(), op);
+    oper.send(&tx, 69).unwrap();
+
+    assert_eq!(f.join(), Some(69));
+}
+
+pub fn wakes_on_close() {
+    let (tx, rx) = fiber::Channel::<()>::new(0).into_clones();
+
+    let f = fiber::defer_proc(move || tx.close());
+
+    let mut sel = fiber::Select::new();
+    let op = sel.recv(&rx);
+    let oper = sel.select_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(oper.index(), op);
+    assert_eq!(oper.recv(&rx), None);
+
+    f.join();
+}
+
+pub fn wakes_on_cond() {
+    let ch = fiber::Channel::<()>::new(0);
+    let cond1 = std::rc::Rc::new(fiber::Cond::new());
+    let cond2 = std::rc::Rc::new(fiber::Cond::new());
+
+    let f = {
+        let cond2 = cond2.clone();
+        fiber::defer_proc(move || cond2.signal())
+    };
+
+    let mut sel = fiber::Select::new();
+    sel.recv(&ch);
+    sel.cond(&cond1);
+    let op = sel.cond(&cond2);
+    let oper = sel.select_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(oper.index(), op);
+
+    f.join();
+}
+
+pub fn broadcast_wakes_all() {
+    let cond = std::rc::Rc::new(fiber::Cond::new());
+
+    let fibers: Vec<_> = (0..3)
+        .map(|_| {
+            let cond = cond.clone();
+            fiber::defer(move || {
+                let mut sel = fiber::Select::new();
+                let op = sel.cond(&cond);
+                sel.select_timeout(Duration::from_secs(1))
+                    .map(|oper| oper.index() == op)
+            })
+        })
+        .collect();
+
+    // let the fibers start waiting
+    fiber::sleep(Duration::ZERO);
+    cond.broadcast();
+
+    for f in fibers {
+        assert_eq!(f.join(), Ok(true));
+    }
+}
+
+pub fn signal_wakes_one() {
+    let cond = std::rc::Rc::new(fiber::Cond::new());
+
+    let waiter = {
+        let cond = cond.clone();
+        fiber::defer(move || cond.wait_timeout(Duration::from_secs(1)))
+    };
+    let selector = {
+        let cond = cond.clone();
+        fiber::defer(move || {
+            let mut sel = fiber::Select::new();
+            sel.cond(&cond);
+            sel.select_timeout(Duration::from_millis(100)).map(|oper| oper.index())
+        })
+    };
+
+    // let the fibers start waiting
+    fiber::sleep(Duration::ZERO);
+    cond.signal();
+
+    assert!(waiter.join());
+    assert_eq!(selector.join(), Err(fiber::SelectError::Timeout));
+}
+
+pub fn signalled_cond_is_selected() {
+    let ch = std::rc::Rc::new(fiber::Channel::<()>::new(1));
+    let cond = std::rc::Rc::new(fiber::Cond::new());
+
+    let f = {
+        let (ch, cond) = (ch.clone(), cond.clone());
+        fiber::defer(move || {
+            let mut sel = fiber::Select::new();
+            sel.recv(&ch);
+            let op = sel.cond(&cond);
+            sel.select_timeout(Duration::from_secs(1))
+                .map(|oper| oper.index() == op)
+        })
+    };
+
+    // let the fiber start waiting
+    fiber::sleep(Duration::ZERO);
+    cond.signal();
+    ch.try_send(()).unwrap();
+
+    // the signal isn't lost even though the channel comes first
+    assert_eq!(f.join(), Ok(true));
+}
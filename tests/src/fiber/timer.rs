@@ -0,0 +1,107 @@
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::Duration,
+};
+
+use tarantool::fiber::{self, Interval, Timer};
+
+pub fn timer_fires() {
+    let fired = Rc::new(Cell::new(false));
+
+    let timer = {
+        let fired = fired.clone();
+        Timer::after(Duration::from_millis(10), move || fired.set(true))
+    };
+    assert!(timer.is_active());
+    assert!(!fired.get());
+
+    fiber::sleep(Duration::from_millis(50));
+    assert!(fired.get());
+    assert!(!timer.is_active());
+}
+
+pub fn timer_zero_timeout() {
+    let fired = Rc::new(Cell::new(false));
+
+    let _ = {
+        let fired = fired.clone();
+        Timer::after(Duration::ZERO, move || fired.set(true))
+    };
+    assert!(!fired.get());
+
+    fiber::sleep(Duration::from_millis(10));
+    assert!(fired.get());
+}
+
+pub fn timer_cancel() {
+    let fired = Rc::new(Cell::new(false));
+
+    let timer = {
+        let fired = fired.clone();
+        Timer::after(Duration::from_millis(10), move || fired.set(true))
+    };
+    timer.cancel();
+
+    fiber::sleep(Duration::from_millis(50));
+    assert!(!fired.get());
+    assert!(!timer.is_active());
+}
+
+pub fn interval() {
+    let count = Rc::new(Cell::new(0));
+
+    let interval = {
+        let count = count.clone();
+        Interval::every(Duration::from_millis(10), move || {
+            count.set(count.get() + 1)
+        })
+    };
+
+    fiber::sleep(Duration::from_millis(55));
+    interval.cancel();
+    let n = count.get();
+    assert!((3..=5).contains(&n), "unexpected number of calls: {}", n);
+
+    fiber::sleep(Duration::from_millis(30));
+    assert_eq!(count.get(), n);
+    assert!(!interval.is_active());
+}
+
+pub fn interval_cancel_from_callback() {
+    let count = Rc::new(Cell::new(0));
+    let handle: Rc<Cell<Option<Interval>>> = Default::default();
+
+    let interval = {
+        let count = count.clone();
+        let handle = handle.clone();
+        Interval::every(Duration::from_millis(5), move || {
+            count.set(count.get() + 1);
+            if count.get() == 2 {
+                handle.take().unwrap().cancel()
+            }
+        })
+    };
+    handle.set(Some(interval));
+
+    fiber::sleep(Duration::from_millis(50));
+    assert_eq!(count.get(), 2);
+}
+
+pub fn interval_no_drift() {
+    let count = Rc::new(Cell::new(0));
+
+    let interval = {
+        let count = count.clone();
+        Interval::every(Duration::from_millis(10), move || {
+            count.set(count.get() + 1);
+            // the time spent here must not shift the following calls
+            fiber::sleep(Duration::from_millis(4));
+        })
+    };
+
+    fiber::sleep(Duration::from_millis(105));
+    interval.cancel();
+    let n = count.get();
+    assert!((9..=10).contains(&n), "unexpected number of calls: {}", n);
+}
@@ -15,6 +15,7 @@ use tarantool::space::{Space, SpaceCreateOptions, SpaceFieldFormat, SpaceFieldTy
 
 mod bench_bulk_insert;
 mod common;
+mod datetime;
 mod decimal;
 mod test_box;
 mod test_coio;
@@ -24,11 +25,13 @@ mod test_latch;
 mod test_log;
 mod test_net_box;
 mod test_raft;
+mod test_schema;
 mod test_session;
 mod test_transaction;
 mod test_tuple;
 mod tlua;
 mod uuid;
+mod version;
 
 macro_rules! tests {
     (@should_panic should_panic) => { ShouldPanic::Yes };
@@ -182,6 +185,12 @@ fn run_tests(cfg: TestConfig) -> Result<bool, io::Error> {
             }]
         } else {
             tests![
+                datetime::to_tuple,
+                datetime::from_tuple,
+                datetime::round_trip,
+                datetime::interval_from_lua,
+                datetime::error_round_trip,
+                datetime::ext_type_mismatch,
                 decimal::from_lua,
                 decimal::to_lua,
                 decimal::from_string,
@@ -343,6 +352,9 @@ fn run_tests(cfg: TestConfig) -> Result<bool, io::Error> {
                 #[should_panic] fiber::defer_proc_dont_join,
                 fiber::immediate_with_cond,
                 fiber::deferred_with_cond,
+                fiber::cancel,
+                fiber::wakeup,
+                fiber::join_timeout,
 
                 fiber::channel::send_self,
                 fiber::channel::send_full,
@@ -361,18 +373,61 @@ fn run_tests(cfg: TestConfig) -> Result<bool, io::Error> {
                 fiber::channel::demo,
                 fiber::channel::drop_rx,
 
+                fiber::select::recv_ready,
+                fiber::select::send_ready,
+                fiber::select::try_select_empty,
+                fiber::select::timeout,
+                fiber::select::wakes_on_send,
+                fiber::select::wakes_on_recv,
+                fiber::select::wakes_on_close,
+                fiber::select::wakes_on_cond,
+                fiber::select::broadcast_wakes_all,
+                fiber::select::signal_wakes_one,
+                fiber::select::signalled_cond_is_selected,
+
+                fiber::executor::block_on_ready,
+                fiber::executor::block_on_pending,
+                fiber::executor::block_on_woken_while_polled,
+                fiber::executor::spawn_async,
+
+                fiber::panic::start_try_join,
+                fiber::panic::start_proc_try_join,
+                fiber::panic::defer_try_join,
+                fiber::panic::defer_proc_try_join,
+                fiber::panic::join_resumes_panic,
+                fiber::panic::panic_hook,
+
+                fiber::scope::borrow,
+                fiber::scope::cancel_on_exit,
+                fiber::scope::joined_on_panic,
+                fiber::scope::nested_spawn,
+                fiber::scope::handle_methods,
+
+                fiber::timer::timer_fires,
+                fiber::timer::timer_zero_timeout,
+                fiber::timer::timer_cancel,
+                fiber::timer::interval,
+                fiber::timer::interval_cancel_from_callback,
+                fiber::timer::interval_no_drift,
+
                 test_box::test_space_get_by_name,
                 test_box::test_space_get_system,
                 test_box::test_index_get_by_name,
+                test_box::test_space_meta,
+                test_box::test_index_meta,
+                test_box::test_schema_cache,
                 test_box::test_box_insert,
                 test_box::test_box_replace,
                 test_box::test_box_delete,
                 test_box::test_box_update,
                 test_box::test_box_upsert,
+                test_box::test_box_update_ops,
+                test_box::test_box_upsert_ops,
                 test_box::test_box_truncate,
                 test_box::test_box_get,
                 test_box::test_box_select,
                 test_box::test_box_select_composite_key,
+                test_box::test_box_select_range,
                 test_box::test_box_len,
                 test_box::test_box_random,
                 test_box::test_box_min_max,
@@ -381,36 +436,65 @@ fn run_tests(cfg: TestConfig) -> Result<bool, io::Error> {
                 test_box::test_box_sequence_get_by_name,
                 test_box::test_box_sequence_iterate,
                 test_box::test_box_sequence_set,
+                test_box::test_box_sequence_create,
                 test_box::test_space_create_opt_default,
+                test_box::test_space_create_is_sync,
                 test_box::test_space_create_opt_if_not_exists,
                 test_box::test_space_create_id_increment,
                 test_box::test_space_create_opt_user,
                 test_box::test_space_create_opt_id,
                 test_box::test_space_drop,
+                test_box::test_space_alter,
+                test_schema::migrations,
+                test_schema::users,
+                test_schema::roles,
+                test_schema::funcs,
+                test_schema::snapshot,
                 test_box::test_index_create_drop,
+                test_box::test_index_alter,
+                test_box::test_space_on_replace,
+                test_box::test_space_before_replace,
 
                 test_tuple::test_tuple_new_from_struct,
                 test_tuple::test_tuple_field_count,
                 test_tuple::test_tuple_size,
                 test_tuple::test_tuple_into_struct,
+                test_tuple::test_tuple_decode,
+                test_tuple::test_tuple_data,
                 test_tuple::test_tuple_clone,
                 test_tuple::test_tuple_iterator,
                 test_tuple::test_tuple_iterator_seek_rewind,
                 test_tuple::test_tuple_get_format,
                 test_tuple::test_tuple_get_field,
+                test_tuple::test_tuple_get_field_complex,
+                test_tuple::test_tuple_get_by_path,
+                test_tuple::test_tuple_get_by_name,
+                test_tuple::test_tuple_builder,
+                test_tuple::test_tuple_format_for_space,
+                test_tuple::test_tuple_derive,
+                test_tuple::test_tuple_derive_space_format,
                 test_tuple::test_tuple_compare,
                 test_tuple::test_tuple_compare_with_key,
+                test_tuple::test_key_def_from_index,
                 test_tuple::to_and_from_lua,
 
                 test_error::test_error_last,
                 test_coio::test_coio_accept,
                 test_coio::test_coio_read_write,
+                test_coio::test_coio_read_write_async,
                 test_coio::test_coio_call,
                 test_coio::test_channel,
                 test_coio::test_channel_rx_closed,
                 test_coio::test_channel_tx_closed,
                 test_transaction::test_transaction_commit,
                 test_transaction::test_transaction_rollback,
+                test_transaction::test_transaction_panic,
+                test_transaction::test_transaction_savepoint,
+                test_transaction::test_transaction_hooks,
+                test_transaction::test_transaction_isolation,
+                test_transaction::test_transaction_nested,
+                test_transaction::test_transaction_alloc,
+                test_transaction::test_transaction_fibers,
                 test_log::test_log,
                 test_latch::test_latch_lock,
                 test_latch::test_latch_try_lock,
@@ -430,6 +514,7 @@ fn run_tests(cfg: TestConfig) -> Result<bool, io::Error> {
                 test_net_box::test_replace,
                 test_net_box::test_update,
                 test_net_box::test_upsert,
+                test_net_box::test_update_ops,
                 test_net_box::test_delete,
                 test_net_box::test_cancel_recv,
                 test_net_box::test_triggers_connect,
@@ -444,6 +529,9 @@ fn run_tests(cfg: TestConfig) -> Result<bool, io::Error> {
                 uuid::from_tuple,
                 uuid::to_lua,
                 uuid::from_lua,
+                version::parse,
+                version::compare,
+                version::running,
             ]
         },
     )
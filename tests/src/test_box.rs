@@ -1,9 +1,21 @@
+use std::cell::RefCell;
+use std::ops::Bound;
+use std::rc::Rc;
+
 use rand::Rng;
 
-use tarantool::index::{IndexOptions, IteratorType};
-use tarantool::sequence::Sequence;
-use tarantool::space::{Space, SpaceCreateOptions, SystemSpace};
+use tarantool::index::{
+    IndexAlterOptions, IndexFieldType, IndexOptions, IndexPart, IndexRange, IndexType, IteratorType,
+};
+use tarantool::sequence::{Sequence, SequenceOptions};
+use tarantool::error::{Error, TarantoolErrorCode};
+use tarantool::space::{
+    FormatError, Space, SpaceAlterOptions, SpaceCreateOptions, SpaceFieldFormat, SpaceFieldType,
+    SystemSpace,
+};
 use tarantool::tuple::Tuple;
+use tarantool::update::UpdateOps;
+use tarantool::version::Feature;
 
 use crate::common::{QueryOperation, S1Record, S2Key, S2Record};
 
@@ -23,6 +35,69 @@ pub fn test_index_get_by_name() {
     assert!(space.index("idx_1_invalid").is_none());
 }
 
+pub fn test_space_meta() {
+    let space = Space::find("test_s1").unwrap();
+    assert_eq!(space.name().unwrap(), "test_s1");
+    assert_eq!(space.engine().unwrap(), "memtx");
+    assert_eq!(space.field_count().unwrap(), 0);
+    assert_eq!(
+        space.format().unwrap(),
+        vec![
+            SpaceFieldFormat::new("id", SpaceFieldType::Unsigned),
+            SpaceFieldFormat::new("text", SpaceFieldType::String),
+        ]
+    );
+
+    let space: Space = SystemSpace::Space.into();
+    assert_eq!(space.name().unwrap(), "_space");
+}
+
+pub fn test_index_meta() {
+    let space = Space::find("test_s2").unwrap();
+
+    let index = space.index("idx_1").unwrap();
+    assert_eq!(index.name().unwrap(), "idx_1");
+    assert_eq!(index.index_type().unwrap(), IndexType::Hash);
+    assert!(index.unique().unwrap());
+
+    let index = space.index("idx_2").unwrap();
+    let meta = index.meta().unwrap();
+    assert_eq!((meta.space_id, meta.index_id), (space.id(), index.id()));
+    assert_eq!(meta.index_type, IndexType::Tree);
+    assert_eq!(
+        meta.parts,
+        vec![
+            IndexPart { is_nullable: Some(false), ..IndexPart::new(1, IndexFieldType::Unsigned) },
+            IndexPart { is_nullable: Some(false), ..IndexPart::new(4, IndexFieldType::Integer) },
+            IndexPart { is_nullable: Some(false), ..IndexPart::new(5, IndexFieldType::Integer) },
+        ]
+    );
+
+    let index = space.index("idx_3").unwrap();
+    assert!(!index.unique().unwrap());
+}
+
+pub fn test_schema_cache() {
+    let space = Space::create("test_cache", &Default::default()).unwrap();
+    space.create_index("pk", &Default::default()).unwrap();
+    assert_eq!(Space::find("test_cache").unwrap().id(), space.id());
+    assert_eq!(space.name().unwrap(), "test_cache");
+    assert_eq!(space.index("pk").unwrap().name().unwrap(), "pk");
+
+    // the cache is invalidated on schema change
+    let lua = tarantool::global_lua();
+    lua.exec("box.space.test_cache:rename('test_cache_2')").unwrap();
+    lua.exec("box.space.test_cache_2.index.pk:rename('primary')").unwrap();
+    assert!(Space::find("test_cache").is_none());
+    assert_eq!(space.name().unwrap(), "test_cache_2");
+    assert!(space.index("pk").is_none());
+    assert_eq!(space.primary_key().name().unwrap(), "primary");
+
+    space.drop().unwrap();
+    assert!(Space::find("test_cache_2").is_none());
+    assert!(space.name().is_err());
+}
+
 pub fn test_box_get() {
     let space = Space::find("test_s2").unwrap();
 
@@ -151,6 +226,54 @@ pub fn test_box_select_composite_key() {
     );
 }
 
+pub fn test_box_select_range() {
+    let space = Space::find("test_s2").unwrap();
+    let ids = |range: IndexRange| -> Vec<u32> {
+        range.map(|t| t.field(0).unwrap().unwrap()).collect()
+    };
+
+    let pk = space.primary_key();
+    assert_eq!(ids(pk.range((3,)..(6,)).unwrap()), vec![3, 4, 5]);
+    assert_eq!(ids(pk.range((3,)..=(6,)).unwrap()), vec![3, 4, 5, 6]);
+    assert_eq!(ids(pk.range((18,)..).unwrap()), vec![18, 19, 20]);
+    assert_eq!(ids(pk.range(..(3,)).unwrap()), vec![1, 2]);
+    assert_eq!(ids(pk.range((Bound::Excluded((3,)), Bound::Included((5,)))).unwrap()), vec![4, 5]);
+    assert_eq!(ids(pk.range::<(u32,), _>(..).unwrap()).len(), 20);
+    assert!(ids(pk.range((6,)..(3,)).unwrap()).is_empty());
+
+    assert_eq!(ids(pk.range((3,)..(6,)).unwrap().rev()), vec![5, 4, 3]);
+    assert_eq!(ids(pk.range((3,)..=(6,)).unwrap().rev()), vec![6, 5, 4, 3]);
+    assert_eq!(ids(pk.range(..=(2,)).unwrap().rev()), vec![2, 1]);
+    assert_eq!(ids(pk.range((19,)..).unwrap().rev()), vec![20, 19]);
+
+    // non unique index, field "a"
+    let idx = space.index("idx_3").unwrap();
+    assert_eq!(ids(idx.range((1,)..=(2,)).unwrap()), vec![1, 6, 11, 16, 2, 7, 12, 17]);
+    assert_eq!(ids(idx.range((1,)..(2,)).unwrap().rev()), vec![16, 11, 6, 1]);
+
+    // partial key of the index (id, a, b)
+    let idx = space.index("idx_2").unwrap();
+    let result: Vec<S2Record> = idx.range((3,)..=(3,))
+        .unwrap()
+        .decode::<S2Record>()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        result,
+        vec![S2Record {
+            id: 3,
+            key: "key_3".to_string(),
+            value: "value_3".to_string(),
+            a: 3,
+            b: 0
+        }]
+    );
+
+    // the bounds must match the types of the index parts
+    assert!(pk.range(("3",)..).is_err());
+    assert!(pk.range(..=("6",)).is_err());
+}
+
 pub fn test_box_len() {
     let space = Space::find("test_s2").unwrap();
     assert_eq!(space.len().unwrap(), 20 as usize);
@@ -339,6 +462,63 @@ pub fn test_box_update() {
     );
 }
 
+pub fn test_box_update_ops() {
+    let mut space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+    space.insert(&(1, "Original")).unwrap();
+
+    let ops = UpdateOps::new()
+        .splice("text", 1, 1, "o")
+        .insert(-1, 10)
+        .add(2, 5)
+        .bit_or(2, 0b10000);
+    assert_eq!(ops.len(), 4);
+    let res = space.update(&(1,), &ops).unwrap().unwrap();
+    assert_eq!(
+        res.into_struct::<(u32, String, u32)>().unwrap(),
+        (1, "original".into(), 31)
+    );
+
+    let ops = UpdateOps::new()
+        .sub(-1, 1)
+        .bit_and(2, 0b1100)
+        .bit_xor(2, 0b0110)
+        .assign(1, "New");
+    let res = space.update(&(1,), &ops).unwrap().unwrap();
+    assert_eq!(
+        res.into_struct::<(u32, String, u32)>().unwrap(),
+        (1, "New".into(), 0b1010)
+    );
+
+    let res = space.update(&(1,), &UpdateOps::new().delete(2, 1)).unwrap().unwrap();
+    assert_eq!(
+        res.into_struct::<S1Record>().unwrap(),
+        S1Record { id: 1, text: "New".into() }
+    );
+}
+
+pub fn test_box_upsert_ops() {
+    let mut space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+    space.insert(&(1, "Original")).unwrap();
+
+    let ops = UpdateOps::new().assign("text", "Test 1");
+    space.upsert(&(1, "New"), &ops).unwrap();
+    space.upsert(&(2, "New"), &ops).unwrap();
+
+    let output = space.get(&(1,)).unwrap();
+    assert_eq!(
+        output.unwrap().into_struct::<S1Record>().unwrap().text,
+        "Test 1"
+    );
+
+    let output = space.get(&(2,)).unwrap();
+    assert_eq!(
+        output.unwrap().into_struct::<S1Record>().unwrap().text,
+        "New"
+    );
+}
+
 pub fn test_box_upsert() {
     let mut space = Space::find("test_s1").unwrap();
     space.truncate().unwrap();
@@ -429,6 +609,47 @@ pub fn test_box_sequence_set() {
     assert_eq!(seq.next().unwrap(), 100);
 }
 
+pub fn test_box_sequence_create() {
+    let opts = SequenceOptions { start: Some(10), step: Some(5), ..Default::default() };
+    let mut seq = Sequence::create("test_seq_new", &opts).unwrap();
+    assert!(Sequence::create("test_seq_new", &opts).is_err());
+    let if_not_exists = SequenceOptions { if_not_exists: true, ..opts };
+    assert_eq!(Sequence::create("test_seq_new", &if_not_exists).unwrap().id(), seq.id());
+
+    assert_eq!(seq.current().unwrap(), None);
+    assert_eq!(seq.next().unwrap(), 10);
+    assert_eq!(seq.next().unwrap(), 15);
+    assert_eq!(seq.current().unwrap(), Some(15));
+
+    // the current value is kept
+    seq.alter(&SequenceOptions { max: Some(20), cycle: Some(true), ..Default::default() })
+        .unwrap();
+    assert_eq!(seq.next().unwrap(), 20);
+    assert_eq!(seq.next().unwrap(), 1);
+
+    seq.reset().unwrap();
+    assert_eq!(seq.current().unwrap(), None);
+
+    // the sequence fills the primary key
+    let mut space = Space::create("test_seq_space", &Default::default()).unwrap();
+    space.create_index("pk", &Default::default()).unwrap();
+    // field numbers are 1-based
+    assert!(space.attach_sequence(&seq, Some(0)).is_err());
+    space.attach_sequence(&seq, None).unwrap();
+    let t = space.insert(&((), "a")).unwrap().unwrap();
+    assert_eq!(t.field::<u32>(0).unwrap(), Some(10));
+    assert_eq!(seq.current().unwrap(), Some(10));
+
+    space.detach_sequence().unwrap();
+    assert!(space.insert(&((), "b")).is_err());
+    // the sequence isn't dropped, as it wasn't generated for the space
+    assert!(Sequence::find("test_seq_new").unwrap().is_some());
+
+    space.drop().unwrap();
+    seq.drop().unwrap();
+    assert!(Sequence::find("test_seq_new").unwrap().is_none());
+}
+
 pub fn test_space_create_opt_default() {
     let opts = SpaceCreateOptions::default();
 
@@ -439,6 +660,19 @@ pub fn test_space_create_opt_default() {
     drop_space("new_space_1");
 }
 
+pub fn test_space_create_is_sync() {
+    let opts = SpaceCreateOptions { is_sync: true, ..Default::default() };
+    if tarantool::supports(Feature::SyncReplication) {
+        let space = Space::create("test_space_sync", &opts).unwrap();
+        let flags = space.meta().unwrap().flags;
+        assert_eq!(flags.get("is_sync"), Some(&serde_json::Value::Bool(true)));
+        space.drop().unwrap();
+    } else {
+        assert!(Space::create("test_space_sync", &opts).is_err());
+        assert!(Space::find("test_space_sync").is_none());
+    }
+}
+
 pub fn test_space_create_opt_if_not_exists() {
     let mut opts = SpaceCreateOptions::default();
     let _result = Space::create("new_space_2", &opts);
@@ -515,6 +749,59 @@ pub fn test_space_drop() {
     }
 }
 
+pub fn test_space_alter() {
+    let opts = SpaceCreateOptions {
+        format: Some(vec![SpaceFieldFormat::new("id", SpaceFieldType::Unsigned)]),
+        ..Default::default()
+    };
+    let mut space = Space::create("test_alter", &opts).unwrap();
+    space.create_index("pk", &Default::default()).unwrap();
+    space.insert(&(1,)).unwrap();
+
+    space.alter(&SpaceAlterOptions {
+        name: Some("test_alter_2".into()),
+        ..Default::default()
+    }).unwrap();
+    assert!(Space::find("test_alter").is_none());
+    assert_eq!(Space::find("test_alter_2").unwrap().id(), space.id());
+
+    // a nullable field can be added to the existing data
+    let id = SpaceFieldFormat::new("id", SpaceFieldType::Unsigned);
+    let name = SpaceFieldFormat { is_nullable: true, ..SpaceFieldFormat::new("name", SpaceFieldType::String) };
+    space.set_format(&[id.clone(), name.clone()]).unwrap();
+    assert_eq!(space.format().unwrap(), vec![id.clone(), name.clone()]);
+    space.insert(&(2, "Bob")).unwrap();
+
+    // but a required one can't
+    let age = SpaceFieldFormat::new("age", SpaceFieldType::Unsigned);
+    let err = space.set_format(&[id.clone(), name.clone(), age]).unwrap_err();
+    assert!(
+        matches!(err, Error::IncompatibleFormat(FormatError::MissingField { field: 3, tuple_len: 1 })),
+        "{}", err
+    );
+
+    // the format must match the indexes
+    let err = space.set_format(&[SpaceFieldFormat::new("id", SpaceFieldType::String)]).unwrap_err();
+    assert!(
+        matches!(err, Error::IncompatibleFormat(FormatError::IndexedFieldType { field: 1, .. })),
+        "{}", err
+    );
+    let nullable_id = SpaceFieldFormat { is_nullable: true, ..id.clone() };
+    let err = space.set_format(&[nullable_id]).unwrap_err();
+    assert!(
+        matches!(err, Error::IncompatibleFormat(FormatError::NullableIndexedField { field: 1, .. })),
+        "{}", err
+    );
+
+    // the field types are checked by tarantool
+    let name = SpaceFieldFormat { field_type: SpaceFieldType::Unsigned, ..name };
+    let err = space.set_format(&[id.clone(), name]).unwrap_err();
+    assert!(matches!(err, Error::Tarantool(_)), "{}", err);
+    assert_eq!(space.format().unwrap().len(), 2);
+
+    space.drop().unwrap();
+}
+
 pub fn test_index_create_drop() {
     let space_opts = SpaceCreateOptions::default();
     let space = Space::create("new_space_7", &space_opts).unwrap();
@@ -539,6 +826,130 @@ pub fn test_index_create_drop() {
     drop_space("new_space_7");
 }
 
+pub fn test_index_alter() {
+    let mut space = Space::create("test_index_alter", &SpaceCreateOptions::default()).unwrap();
+    space.create_index("pk", &IndexOptions::default()).unwrap();
+    let sk_opts = IndexOptions {
+        parts: Some(vec![IndexPart::new(2, IndexFieldType::String)]),
+        ..Default::default()
+    };
+    let sk = space.create_index("sk", &sk_opts).unwrap();
+    space.insert(&(1, "a")).unwrap();
+    assert!(space.insert(&(2, "a")).is_err());
+
+    sk.alter(&IndexAlterOptions { unique: Some(false), ..Default::default() }).unwrap();
+    assert_eq!(sk.unique().unwrap(), false);
+    // the options which aren't specified stay the same
+    assert_eq!(sk.index_type().unwrap(), IndexType::Tree);
+    let parts = sk.parts().unwrap();
+    assert_eq!(parts.len(), 1);
+    assert_eq!((parts[0].field_index, parts[0].field_type), (2, IndexFieldType::String));
+    space.insert(&(2, "a")).unwrap();
+    assert_eq!(sk.select(IteratorType::Eq, &("a",)).unwrap().count(), 2);
+
+    // the parts and the type are changed, the data is rebuilt
+    sk.alter(&IndexAlterOptions {
+        index_type: Some(IndexType::Hash),
+        parts: Some(vec![
+            IndexPart::new(2, IndexFieldType::String),
+            IndexPart::new(1, IndexFieldType::Unsigned),
+        ]),
+        unique: Some(true),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(sk.index_type().unwrap(), IndexType::Hash);
+    assert_eq!(sk.parts().unwrap().len(), 2);
+    assert!(sk.get(&("a", 2)).unwrap().is_some());
+
+    sk.rename("sk2").unwrap();
+    assert!(space.index("sk").is_none());
+    assert_eq!(space.index("sk2").unwrap().id(), sk.id());
+
+    sk.drop().unwrap();
+    assert!(sk.alter(&IndexAlterOptions::default()).is_err());
+
+    space.drop().unwrap();
+}
+
+pub fn test_space_on_replace() {
+    let mut space = Space::create("test_on_replace", &SpaceCreateOptions::default()).unwrap();
+    space.create_index("pk", &IndexOptions::default()).unwrap();
+
+    let log = Rc::new(RefCell::new(vec![]));
+    let trigger = space
+        .on_replace({
+            let log = log.clone();
+            move |old, new| {
+                let field = |t: Option<Tuple>| t.map(|t| t.field::<String>(1).unwrap().unwrap());
+                log.borrow_mut().push((field(old), field(new)));
+                Ok(())
+            }
+        })
+        .unwrap();
+    space.insert(&(1, "a")).unwrap();
+    space.replace(&(1, "b")).unwrap();
+    space.delete(&(1,)).unwrap();
+    assert_eq!(*log.borrow(), vec![
+        (None, Some("a".to_string())),
+        (Some("a".to_string()), Some("b".to_string())),
+        (Some("b".to_string()), None),
+    ]);
+
+    // the change is rolled back if the trigger fails
+    let _reject = space
+        .on_replace(|_, new| match new {
+            Some(t) if t.field::<String>(1)?.as_deref() == Some("bad") => panic!("bad tuple"),
+            _ => Ok(()),
+        })
+        .unwrap();
+    assert!(space.insert(&(2, "bad")).is_err());
+    assert!(space.get(&(2,)).unwrap().is_none());
+
+    drop(trigger);
+    space.insert(&(3, "c")).unwrap();
+    assert_eq!(log.borrow().len(), 3);
+
+    space.drop().unwrap();
+    // the triggers can't be registered on a dropped space
+    assert!(space.on_replace(|_, _| Ok(())).is_err());
+    assert!(space.before_replace(|_, new| Ok(new)).is_err());
+}
+
+pub fn test_space_before_replace() {
+    let mut space = Space::create("test_before_replace", &SpaceCreateOptions::default()).unwrap();
+    space.create_index("pk", &IndexOptions::default()).unwrap();
+
+    let _trigger = space
+        .before_replace(|old, new| {
+            let new = match new {
+                Some(new) => new,
+                None => return Ok(None),
+            };
+            let (id, value): (u32, String) = new.decode()?;
+            match value.as_str() {
+                "keep" => Ok(old),
+                "reject" => {
+                    tarantool::set_error!(TarantoolErrorCode::ProcC, "rejected");
+                    Err(tarantool::error::TarantoolError::last().into())
+                }
+                _ => Ok(Some(Tuple::from_struct(&(id, value.to_uppercase()))?)),
+            }
+        })
+        .unwrap();
+    space.insert(&(1, "a")).unwrap();
+    assert_eq!(space.get(&(1,)).unwrap().unwrap().field::<String>(1).unwrap().unwrap(), "A");
+    space.replace(&(1, "keep")).unwrap();
+    assert_eq!(space.get(&(1,)).unwrap().unwrap().field::<String>(1).unwrap().unwrap(), "A");
+    match space.insert(&(2, "reject")) {
+        Err(Error::Tarantool(e)) => assert_eq!(e.error_code(), TarantoolErrorCode::ProcC),
+        _ => panic!("the insert must be rejected"),
+    }
+    assert!(space.get(&(2,)).unwrap().is_none());
+
+    space.drop().unwrap();
+}
+
 pub fn drop_space(name: &str) {
     let result = Space::find(name).unwrap().drop();
     assert_eq!(result.is_err(), false);
@@ -6,7 +6,7 @@ use std::os::unix::net::UnixStream;
 use std::time::Duration;
 
 use tarantool::coio::{channel, coio_call, CoIOListener, CoIOStream, Receiver, Sender};
-use tarantool::fiber::{sleep, Fiber};
+use tarantool::fiber::{self, sleep, Fiber};
 
 pub fn test_coio_accept() {
     let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
@@ -50,6 +50,24 @@ pub fn test_coio_read_write() {
     writer_fiber.join();
 }
 
+pub fn test_coio_read_write_async() {
+    let (reader_soc, writer_soc) = UnixStream::pair().unwrap();
+    let mut reader = CoIOStream::new(reader_soc).unwrap();
+    let mut writer = CoIOStream::new(writer_soc).unwrap();
+
+    let jh = fiber::spawn_async(async move {
+        let mut buf: Vec<u8> = vec![0; 4];
+        let len = reader.read_async(&mut buf).await.unwrap();
+        buf.truncate(len);
+        buf
+    });
+
+    let len = fiber::block_on(writer.write_async(&[1, 2, 3, 4])).unwrap();
+    assert_eq!(len, 4);
+
+    assert_eq!(jh.join(), vec![1, 2, 3, 4]);
+}
+
 pub fn test_coio_call() {
     let res = coio_call(
         &mut |x| {
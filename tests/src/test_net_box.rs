@@ -7,6 +7,7 @@ use tarantool::fiber::Fiber;
 use tarantool::index::IteratorType;
 use tarantool::net_box::{Conn, ConnOptions, ConnTriggers, Options};
 use tarantool::space::Space;
+use tarantool::update::UpdateOps;
 
 use crate::common::{QueryOperation, S1Record, S2Record};
 use std::cell::{Cell, RefCell};
@@ -411,6 +412,53 @@ pub fn test_upsert() {
     );
 }
 
+pub fn test_update_ops() {
+    let mut local_space = Space::find("test_s1").unwrap();
+    local_space.truncate().unwrap();
+    local_space.insert(&(1, "Original")).unwrap();
+
+    let conn = Conn::new(
+        "localhost:3301",
+        ConnOptions {
+            user: "test_user".to_string(),
+            password: "password".to_string(),
+            ..ConnOptions::default()
+        },
+        None,
+    )
+    .unwrap();
+    let mut remote_space = conn.space("test_s1").unwrap().unwrap();
+
+    let ops = UpdateOps::new()
+        .assign("text", "New")
+        .insert(2, 10)
+        .sub(2, 3);
+    let res = remote_space
+        .update(&(1,), &ops, &Options::default())
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        res.into_struct::<(u32, String, u32)>().unwrap(),
+        (1, "New".into(), 7)
+    );
+
+    let ops = UpdateOps::new().delete(-1, 1);
+    remote_space.upsert(&(1, "Test"), &ops, &Options::default()).unwrap();
+    remote_space.upsert(&(2, "Test"), &ops, &Options::default()).unwrap();
+
+    let output = local_space.get(&(1,)).unwrap();
+    assert_eq!(
+        output.unwrap().into_struct::<S1Record>().unwrap(),
+        S1Record { id: 1, text: "New".into() }
+    );
+
+    let output = local_space.get(&(2,)).unwrap();
+    assert_eq!(
+        output.unwrap().into_struct::<S1Record>().unwrap(),
+        S1Record { id: 2, text: "Test".into() }
+    );
+}
+
 pub fn test_delete() {
     let mut local_space = Space::find("test_s1").unwrap();
     local_space.truncate().unwrap();
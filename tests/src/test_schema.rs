@@ -0,0 +1,334 @@
+use tarantool::index::{IndexFieldType, IndexOptions, IndexPart, IteratorType};
+use tarantool::error::{Error, TarantoolErrorCode};
+use tarantool::schema::func::{self, FuncCreateOptions};
+use tarantool::schema::migrations::{Ddl, Migrations, PendingMigration};
+use tarantool::schema::role::{self, RoleCreateOptions};
+use tarantool::schema::snapshot::{Change, ObjectKind, Snapshot};
+use tarantool::schema::user::{
+    self, GrantOptions, Object, Privileges, RevokeOptions, UserCreateOptions,
+};
+use tarantool::sequence::Sequence;
+use tarantool::space::{Space, SpaceAlterOptions, SpaceFieldFormat, SpaceFieldType};
+
+fn find(name: &str) -> Space {
+    Space::find(name).unwrap()
+}
+
+pub fn migrations() {
+    let mut migrations = Migrations::with_space_name("test_migrations");
+    migrations
+        .add(1, "create space", Ddl::CreateSpace {
+            name: "test_migrated".into(),
+            opts: Default::default(),
+        })
+        .add(2, "create index", Ddl::CreateIndex {
+            space: "test_migrated".into(),
+            name: "pk".into(),
+            opts: IndexOptions {
+                parts: Some(vec![IndexPart::new(1, IndexFieldType::Unsigned)]),
+                ..Default::default()
+            },
+        })
+        .add_fn(3, "insert data", || {
+            find("test_migrated").insert(&(1, "one"))?;
+            Ok(())
+        })
+        .add(4, "set format", Ddl::AlterSpace {
+            name: "test_migrated".into(),
+            opts: SpaceAlterOptions {
+                format: Some(vec![
+                    SpaceFieldFormat::new("id", SpaceFieldType::Unsigned),
+                    SpaceFieldFormat::new("name", SpaceFieldType::String),
+                ]),
+                ..Default::default()
+            },
+        })
+        .add(5, "create name index", Ddl::CreateIndex {
+            space: "test_migrated".into(),
+            name: "name".into(),
+            opts: IndexOptions {
+                parts: Some(vec![IndexPart::new(2, IndexFieldType::String)]),
+                ..Default::default()
+            },
+        })
+        .add(6, "drop name index", Ddl::DropIndex {
+            space: "test_migrated".into(),
+            name: "name".into(),
+        })
+        .add(7, "create temporary space", Ddl::CreateSpace {
+            name: "test_migrated_tmp".into(),
+            opts: Default::default(),
+        })
+        .add(8, "drop temporary space", Ddl::DropSpace { name: "test_migrated_tmp".into() });
+
+    assert_eq!(
+        migrations.pending().unwrap()[..3],
+        [
+            PendingMigration {
+                version: 1,
+                name: "create space".into(),
+                ddl: Some("create space 'test_migrated'".into()),
+            },
+            PendingMigration {
+                version: 2,
+                name: "create index".into(),
+                ddl: Some("create index 'pk' on space 'test_migrated'".into()),
+            },
+            PendingMigration { version: 3, name: "insert data".into(), ddl: None },
+        ]
+    );
+    // dry run doesn't change anything
+    assert!(Space::find("test_migrated").is_none());
+
+    assert_eq!(
+        migrations.pending().unwrap()[7].ddl.as_deref(),
+        Some("drop space 'test_migrated_tmp'")
+    );
+    assert_eq!(migrations.apply().unwrap(), (1..=8).collect::<Vec<_>>());
+    assert!(migrations.pending().unwrap().is_empty());
+    assert_eq!(find("test_migrated").len().unwrap(), 1);
+    assert_eq!(find("test_migrated").format().unwrap().len(), 2);
+    assert!(find("test_migrated").index("name").is_none());
+    assert!(Space::find("test_migrated_tmp").is_none());
+
+    // each migration is applied only once
+    assert_eq!(migrations.apply().unwrap(), Vec::<u32>::new());
+    assert_eq!(find("test_migrated").len().unwrap(), 1);
+
+    // a failed migration is rolled back
+    migrations
+        .add_fn(9, "fail", || {
+            let mut space = find("test_migrated");
+            space.insert(&(2, "two"))?;
+            space.insert(&(1, "duplicate"))?;
+            Ok(())
+        })
+        .add_fn(10, "never applied", || Ok(()));
+    assert!(migrations.apply().is_err());
+    assert_eq!(find("test_migrated").len().unwrap(), 1);
+    assert_eq!(
+        migrations.applied().unwrap().into_iter().collect::<Vec<_>>(),
+        (1..=8).collect::<Vec<_>>()
+    );
+    assert_eq!(migrations.pending().unwrap().len(), 2);
+
+    find("test_migrated").drop().unwrap();
+    find("test_migrations").drop().unwrap();
+}
+
+fn error_code(e: Error) -> TarantoolErrorCode {
+    match e {
+        Error::Tarantool(e) => e.error_code(),
+        e => panic!("unexpected error: {}", e),
+    }
+}
+
+pub fn users() {
+    let opts = UserCreateOptions { password: Some("secret".into()), ..Default::default() };
+    let id = user::create("test_user", &opts).unwrap();
+    assert_eq!(
+        error_code(user::create("test_user", &opts).unwrap_err()),
+        TarantoolErrorCode::UserExists
+    );
+    let if_not_exists = UserCreateOptions { if_not_exists: true, ..opts };
+    assert_eq!(user::create("test_user", &if_not_exists).unwrap(), id);
+
+    // the user can connect
+    assert_eq!(
+        user::privileges("test_user", &Object::Universe).unwrap(),
+        Privileges::SESSION | Privileges::USAGE
+    );
+    let auth = find("_user").get(&(id,)).unwrap().unwrap().field::<serde_json::Value>(4).unwrap();
+    // base64(sha1(sha1("secret")))
+    assert_eq!(auth, Some(serde_json::json!({"chap-sha1": "FOZVZ6vbUTXQz9mnCzAywXmknuc="})));
+
+    user::passwd("test_user", "another secret").unwrap();
+    let auth2 = find("_user").get(&(id,)).unwrap().unwrap().field::<serde_json::Value>(4).unwrap();
+    assert_ne!(auth, auth2);
+
+    // privileges are merged
+    let space = Object::Space("test_s1".into());
+    user::grant("test_user", Privileges::READ, &space, &Default::default()).unwrap();
+    user::grant("test_user", Privileges::WRITE, &space, &Default::default()).unwrap();
+    assert_eq!(
+        user::privileges("test_user", &space).unwrap(),
+        Privileges::READ | Privileges::WRITE
+    );
+    assert_eq!(
+        error_code(user::grant("test_user", Privileges::READ, &space, &Default::default())
+            .unwrap_err()),
+        TarantoolErrorCode::PrivGranted
+    );
+    let if_not_exists = GrantOptions { if_not_exists: true, ..Default::default() };
+    user::grant("test_user", Privileges::READ, &space, &if_not_exists).unwrap();
+
+    user::revoke("test_user", Privileges::READ, &space, &Default::default()).unwrap();
+    assert_eq!(user::privileges("test_user", &space).unwrap(), Privileges::WRITE);
+    user::revoke("test_user", Privileges::WRITE, &space, &Default::default()).unwrap();
+    assert_eq!(user::privileges("test_user", &space).unwrap(), Privileges::empty());
+    assert_eq!(
+        error_code(user::revoke("test_user", Privileges::READ, &space, &Default::default())
+            .unwrap_err()),
+        TarantoolErrorCode::PrivNotGranted
+    );
+    let if_exists = RevokeOptions { if_exists: true };
+    user::revoke("test_user", Privileges::READ, &space, &if_exists).unwrap();
+
+    let no_such_space = Object::Space("no_such_space".into());
+    assert_eq!(
+        error_code(user::grant("test_user", Privileges::READ, &no_such_space, &Default::default())
+            .unwrap_err()),
+        TarantoolErrorCode::NoSuchSpace
+    );
+
+    user::grant("test_user", Privileges::READ, &space, &Default::default()).unwrap();
+    user::drop("test_user").unwrap();
+    assert!(find("_vuser").index("name").unwrap().get(&("test_user",)).unwrap().is_none());
+    assert_eq!(find("_vpriv").select(IteratorType::Eq, &(id,)).unwrap().count(), 0);
+    assert_eq!(
+        error_code(user::drop("test_user").unwrap_err()),
+        TarantoolErrorCode::NoSuchUser
+    );
+    assert_eq!(
+        error_code(user::drop("admin").unwrap_err()),
+        TarantoolErrorCode::DropUser
+    );
+}
+
+pub fn roles() {
+    role::create("test_role", &RoleCreateOptions::default()).unwrap();
+    user::create("test_role_user", &Default::default()).unwrap();
+    // a role isn't a user
+    assert_eq!(
+        error_code(user::passwd("test_role", "secret").unwrap_err()),
+        TarantoolErrorCode::NoSuchUser
+    );
+    // a user and a role can't have the same name
+    let if_not_exists = UserCreateOptions { if_not_exists: true, ..Default::default() };
+    assert_eq!(
+        error_code(user::create("test_role", &if_not_exists).unwrap_err()),
+        TarantoolErrorCode::RoleExists
+    );
+    let if_not_exists = RoleCreateOptions { if_not_exists: true };
+    assert_eq!(
+        error_code(role::create("test_role_user", &if_not_exists).unwrap_err()),
+        TarantoolErrorCode::UserExists
+    );
+    assert!(role::create("test_role", &if_not_exists).is_ok());
+
+    let space = Object::Space("test_s1".into());
+    role::grant("test_role", Privileges::READ, &space, &Default::default()).unwrap();
+    assert_eq!(user::privileges("test_role", &space).unwrap(), Privileges::READ);
+
+    let the_role = Object::Role("test_role".into());
+    user::grant("test_role_user", Privileges::EXECUTE, &the_role, &Default::default()).unwrap();
+    assert_eq!(
+        error_code(user::grant("test_role_user", Privileges::EXECUTE, &the_role, &Default::default())
+            .unwrap_err()),
+        TarantoolErrorCode::RoleGranted
+    );
+
+    // dropping the role revokes it from the users
+    role::drop("test_role").unwrap();
+    assert_eq!(
+        error_code(user::privileges("test_role_user", &the_role).unwrap_err()),
+        TarantoolErrorCode::NoSuchRole
+    );
+    assert_eq!(
+        error_code(role::revoke("test_role", Privileges::READ, &space, &Default::default())
+            .unwrap_err()),
+        TarantoolErrorCode::NoSuchRole
+    );
+    user::drop("test_role_user").unwrap();
+}
+
+pub fn funcs() {
+    let opts = FuncCreateOptions {
+        body: Some("function(tuple) return {tuple[2]:lower()} end".into()),
+        is_deterministic: true,
+        is_sandboxed: true,
+        ..Default::default()
+    };
+    let id = func::create("test_lower", &opts).unwrap();
+    assert_eq!(
+        error_code(func::create("test_lower", &opts).unwrap_err()),
+        TarantoolErrorCode::FunctionExists
+    );
+    let if_not_exists = FuncCreateOptions { if_not_exists: true, ..opts };
+    assert_eq!(func::create("test_lower", &if_not_exists).unwrap(), id);
+
+    // functional index
+    let mut space = Space::create("test_func_index", &Default::default()).unwrap();
+    space.create_index("pk", &Default::default()).unwrap();
+    let lower = space
+        .create_index("lower", &IndexOptions {
+            func: Some("test_lower".into()),
+            parts: Some(vec![IndexPart::new(1, IndexFieldType::String)]),
+            ..Default::default()
+        })
+        .unwrap();
+    space.insert(&(1, "Foo")).unwrap();
+    space.insert(&(2, "BAR")).unwrap();
+    assert!(space.insert(&(3, "foo")).is_err());
+    let t = lower.get(&("bar",)).unwrap().unwrap();
+    assert_eq!(t.field::<u32>(0).unwrap(), Some(2));
+
+    // the function is in use, its grants are kept
+    let object = Object::Function("test_lower".into());
+    user::grant("guest", Privileges::EXECUTE, &object, &Default::default()).unwrap();
+    assert!(func::drop("test_lower").is_err());
+    assert_eq!(user::privileges("guest", &object).unwrap(), Privileges::EXECUTE);
+    space.drop().unwrap();
+    func::drop("test_lower").unwrap();
+    assert_eq!(
+        error_code(func::drop("test_lower").unwrap_err()),
+        TarantoolErrorCode::NoSuchFunction
+    );
+}
+
+pub fn snapshot() {
+    let space = Space::create("test_snapshot", &Default::default()).unwrap();
+    space.create_index("pk", &Default::default()).unwrap();
+    space
+        .create_index("name", &IndexOptions {
+            parts: Some(vec![IndexPart::new(2, IndexFieldType::String)]),
+            ..Default::default()
+        })
+        .unwrap();
+    let seq = Sequence::create("test_snapshot_seq", &Default::default()).unwrap();
+    space.attach_sequence(&seq, None).unwrap();
+    role::create("test_snapshot_role", &Default::default()).unwrap();
+    let object = Object::Space("test_snapshot".into());
+    role::grant("test_snapshot_role", Privileges::READ, &object, &Default::default()).unwrap();
+
+    let before = Snapshot::take().unwrap();
+    let json = serde_json::to_string(&before).unwrap();
+    assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), before);
+    let def = before.spaces.iter().find(|s| s.name == "test_snapshot").unwrap();
+    assert_eq!(def.indexes.len(), 2);
+    assert_eq!(def.sequence.as_ref().unwrap().name, "test_snapshot_seq");
+
+    space.drop().unwrap();
+    seq.drop().unwrap();
+    role::drop("test_snapshot_role").unwrap();
+    let after = Snapshot::take().unwrap();
+    let changes = after.diff(&before);
+    assert_eq!(changes.len(), 3, "{:?}", changes);
+    assert!(changes.contains(&Change::Added {
+        kind: ObjectKind::Space,
+        name: "test_snapshot".into(),
+    }));
+
+    before.apply().unwrap();
+    assert_eq!(Snapshot::take().unwrap().diff(&before), vec![]);
+    // applying again changes nothing
+    before.apply().unwrap();
+    assert_eq!(
+        user::privileges("test_snapshot_role", &object).unwrap(),
+        Privileges::READ
+    );
+
+    find("test_snapshot").drop().unwrap();
+    Sequence::find("test_snapshot_seq").unwrap().unwrap().drop().unwrap();
+    role::drop("test_snapshot_role").unwrap();
+}
@@ -1,8 +1,14 @@
+use std::cell::RefCell;
 use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
 
-use tarantool::error::Error;
+use tarantool::error::{Error, TransactionError};
+use tarantool::fiber::{Cond, Fiber};
 use tarantool::space::Space;
-use tarantool::transaction::start_transaction;
+use tarantool::transaction::{
+    start_nested_transaction, start_transaction, IsolationLevel, Transaction,
+};
 
 use crate::common::S1Record;
 
@@ -42,3 +48,188 @@ pub fn test_transaction_rollback() {
     let output = space.get(&(1,)).unwrap();
     assert!(output.is_none());
 }
+
+pub fn test_transaction_panic() {
+    let mut space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        start_transaction(|| -> Result<(), Error> {
+            space.insert(&S1Record { id: 1, text: "test".to_string() })?;
+            panic!("oops")
+        })
+    }));
+    assert!(result.is_err());
+    assert!(!tarantool::transaction::is_in_transaction());
+    assert!(space.get(&(1,)).unwrap().is_none());
+}
+
+pub fn test_transaction_savepoint() {
+    let mut space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+
+    let tx = Transaction::begin().unwrap();
+    space.insert(&S1Record { id: 1, text: "one".to_string() }).unwrap();
+    let savepoint = tx.savepoint().unwrap();
+    space.insert(&S1Record { id: 2, text: "two".to_string() }).unwrap();
+    tx.rollback_to(&savepoint).unwrap();
+    space.insert(&S1Record { id: 3, text: "three".to_string() }).unwrap();
+    tx.commit().unwrap();
+
+    assert!(space.get(&(1,)).unwrap().is_some());
+    assert!(space.get(&(2,)).unwrap().is_none());
+    assert!(space.get(&(3,)).unwrap().is_some());
+}
+
+pub fn test_transaction_hooks() {
+    let mut space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+    let log = Rc::new(RefCell::new(vec![]));
+    let hook = |event: &'static str| {
+        let log = log.clone();
+        move || log.borrow_mut().push(event)
+    };
+
+    let mut tx = Transaction::begin().unwrap();
+    tx.on_commit(hook("commit"));
+    tx.on_rollback(hook("rollback"));
+    space.insert(&S1Record { id: 1, text: "test".to_string() }).unwrap();
+    tx.commit().unwrap();
+    assert_eq!(*log.borrow(), vec!["commit"]);
+
+    // dropping the transaction rolls it back
+    let mut tx = Transaction::begin().unwrap();
+    tx.on_commit(hook("commit"));
+    tx.on_rollback(hook("rollback"));
+    space.insert(&S1Record { id: 2, text: "test".to_string() }).unwrap();
+    drop(tx);
+    assert_eq!(*log.borrow(), vec!["commit", "rollback"]);
+    assert!(space.get(&(2,)).unwrap().is_none());
+}
+
+pub fn test_transaction_isolation() {
+    let result = Transaction::begin_with_isolation(IsolationLevel::ReadCommitted);
+    if tarantool::supports(tarantool::version::Feature::TxnIsolation) {
+        let tx = result.unwrap();
+        assert!(Transaction::begin().is_err());
+        tx.rollback().unwrap();
+    } else {
+        assert!(result.is_err());
+    }
+    Transaction::begin_with_isolation(IsolationLevel::Default)
+        .unwrap()
+        .commit()
+        .unwrap();
+}
+
+pub fn test_transaction_nested() {
+    let mut space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+    let log = Rc::new(RefCell::new(vec![]));
+
+    let result = start_transaction(|| -> Result<(), Error> {
+        assert!(matches!(
+            start_transaction(|| -> Result<(), Error> { Ok(()) }),
+            Err(Error::Transaction(TransactionError::AlreadyStarted))
+        ));
+        space.insert(&S1Record { id: 1, text: "outer".to_string() })?;
+
+        // only the changes of the failed nested transaction are rolled back
+        let result = start_nested_transaction(|| -> Result<(), Error> {
+            space.insert(&S1Record { id: 2, text: "failed".to_string() })?;
+            Err(Error::IO(io::ErrorKind::Interrupted.into()))
+        });
+        assert!(result.is_err());
+        assert!(space.get(&(2,)).unwrap().is_none());
+
+        let mut tx = Transaction::begin_nested()?;
+        assert!(tx.is_nested());
+        let hook_log = log.clone();
+        tx.on_commit(move || hook_log.borrow_mut().push("nested commit"));
+        space.insert(&S1Record { id: 3, text: "nested".to_string() })?;
+        tx.commit()?;
+        assert!(log.borrow().is_empty());
+        Ok(())
+    });
+    assert!(result.is_ok());
+    // the hooks of the nested transaction wait for the outer one
+    assert_eq!(*log.borrow(), vec!["nested commit"]);
+    assert!(space.get(&(1,)).unwrap().is_some());
+    assert!(space.get(&(3,)).unwrap().is_some());
+
+    // without an outer transaction a nested one is a regular transaction
+    let tx = Transaction::begin_nested().unwrap();
+    assert!(!tx.is_nested());
+    space.insert(&S1Record { id: 4, text: "plain".to_string() }).unwrap();
+    tx.rollback().unwrap();
+    assert!(!tarantool::transaction::is_in_transaction());
+    assert!(space.get(&(4,)).unwrap().is_none());
+
+    // the hooks wait for an outer transaction started from lua too
+    log.borrow_mut().clear();
+    let lua = tarantool::global_lua();
+    lua.exec("box.begin()").unwrap();
+    let mut tx = Transaction::begin_nested().unwrap();
+    assert!(tx.is_nested());
+    let hook_log = log.clone();
+    tx.on_commit(move || hook_log.borrow_mut().push("lua commit"));
+    space.insert(&S1Record { id: 5, text: "lua".to_string() }).unwrap();
+    tx.commit().unwrap();
+    assert!(log.borrow().is_empty());
+    lua.exec("box.commit()").unwrap();
+    assert_eq!(*log.borrow(), vec!["lua commit"]);
+    assert!(space.get(&(5,)).unwrap().is_some());
+}
+
+pub fn test_transaction_alloc() {
+    let tx = Transaction::begin().unwrap();
+    let mut buf = tx.alloc(16).unwrap();
+    assert_eq!(&*buf, &[0; 16]);
+    buf.copy_from_slice(b"0123456789abcdef");
+    let other = tx.alloc(4).unwrap();
+    assert_eq!(&*other, &[0; 4]);
+    assert_eq!(&buf[10..], b"abcdef");
+    assert!(tx.alloc(0).unwrap().is_empty());
+    tx.commit().unwrap();
+}
+
+pub fn test_transaction_fibers() {
+    let mut space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+    let log = Rc::new(RefCell::new(vec![]));
+
+    // memtx aborts a transaction with changes on a yield, so the fibers
+    // yield before making any
+    let started = Cond::new();
+    let committed = Cond::new();
+    let mut fiber = Fiber::new("test_fiber", &mut |_| {
+        let mut tx = Transaction::begin().unwrap();
+        let log = log.clone();
+        tx.on_commit(move || log.borrow_mut().push("fiber"));
+        started.wait();
+        Space::find("test_s1")
+            .unwrap()
+            .insert(&S1Record { id: 1, text: "fiber".to_string() })
+            .unwrap();
+        tx.commit().unwrap();
+        committed.signal();
+        0
+    });
+    fiber.set_joinable(true);
+    // the fiber runs until it waits for `started`
+    fiber.start(());
+
+    // the transaction of the other fiber is still active
+    let mut tx = Transaction::begin().unwrap();
+    let hook_log = log.clone();
+    tx.on_commit(move || hook_log.borrow_mut().push("main"));
+    started.signal();
+    committed.wait();
+    space.insert(&S1Record { id: 2, text: "main".to_string() }).unwrap();
+    tx.commit().unwrap();
+    fiber.join();
+
+    assert_eq!(*log.borrow(), vec!["fiber", "main"]);
+    assert!(space.get(&(1,)).unwrap().is_some());
+    assert!(space.get(&(2,)).unwrap().is_some());
+}
@@ -1,7 +1,17 @@
 use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::rc::Rc;
 
+use tarantool::decimal::Decimal;
+use tarantool::index::{IndexFieldType, IndexOptions, IndexPart, IteratorType};
+use tarantool::space::{Space, SpaceCreateOptions};
 use tarantool::tlua::AsLua;
-use tarantool::tuple::{FieldType, KeyDef, KeyDefItem, Tuple};
+use tarantool::tuple::{
+    AsTuple, FieldType, KeyDef, KeyDefItem, OrderedTuple, Record, Tuple, TupleBuilder,
+    TupleFormat,
+};
+use tarantool::uuid::Uuid;
 
 use crate::common::{S1Record, S2Key, S2Record};
 
@@ -72,6 +82,45 @@ pub fn test_tuple_into_struct() {
     );
 }
 
+pub fn test_tuple_decode() {
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Borrowed<'a> {
+        id: u32,
+        key: &'a str,
+        value: &'a str,
+    }
+
+    let tuple = Tuple::from_struct(&S2Record {
+        id: 1,
+        key: "key".to_string(),
+        value: "value".to_string(),
+        a: 2,
+        b: 3,
+    })
+    .unwrap();
+    let output: Borrowed = tuple.decode().unwrap();
+    assert_eq!(output, Borrowed { id: 1, key: "key", value: "value" });
+
+    // the strings point into the tuple memory
+    let data = tuple.data().as_ptr_range();
+    assert!(data.contains(&output.key.as_ptr()));
+    assert!(data.contains(&output.value.as_ptr()));
+}
+
+pub fn test_tuple_data() {
+    let tuple = Tuple::from_struct(&(1, "ab")).unwrap();
+    assert_eq!(tuple.data(), &[0x92, 0x01, 0xa2, b'a', b'b']);
+
+    let tuple = Tuple::from_struct(&()).unwrap();
+    assert_eq!(tuple.data(), &[0x90]);
+
+    let tuple = Tuple::from_struct(&vec![0; 20]).unwrap();
+    let mut expected = vec![0xdc, 0x00, 20];
+    expected.extend(&[0; 20]);
+    assert_eq!(tuple.data(), &expected[..]);
+    assert_eq!(tuple.data().len(), tuple.bsize());
+}
+
 pub fn test_tuple_clone() {
     let tuple_1 = {
         let tuple_2 = Tuple::from_struct(&S1Record {
@@ -144,6 +193,201 @@ pub fn test_tuple_get_field() {
     assert_eq!(tuple.field::<i32>(5).unwrap(), None);
 }
 
+pub fn test_tuple_get_field_complex() {
+    #[derive(serde::Serialize)]
+    struct Complex {
+        id: u32,
+        array: Vec<Vec<u32>>,
+        map: std::collections::BTreeMap<String, Option<f64>>,
+        decimal: Decimal,
+        uuid: Uuid,
+        text: String,
+    }
+    impl AsTuple for Complex {}
+
+    let uuid = Uuid::random();
+    let decimal = Decimal::try_from("3.14").unwrap();
+    let map: std::collections::BTreeMap<_, _> = vec![
+        ("a".to_string(), Some(1.5)),
+        ("b".to_string(), None),
+    ].into_iter().collect();
+    let tuple = Tuple::from_struct(&Complex {
+        id: 1,
+        array: vec![vec![1, 2], vec![], vec![3; 20]],
+        map: map.clone(),
+        decimal,
+        uuid,
+        text: "x".repeat(300),
+    })
+    .unwrap();
+
+    assert_eq!(
+        tuple.field::<Vec<Vec<u32>>>(1).unwrap(),
+        Some(vec![vec![1, 2], vec![], vec![3; 20]])
+    );
+    assert_eq!(tuple.field(2).unwrap(), Some(map));
+    assert_eq!(tuple.field(3).unwrap(), Some(decimal));
+    assert_eq!(tuple.field(4).unwrap(), Some(uuid));
+    assert_eq!(tuple.field(5).unwrap(), Some("x".repeat(300)));
+    assert_eq!(tuple.field::<u32>(6).unwrap(), None);
+
+    let mut iterator = tuple.iter().unwrap();
+    assert_eq!(iterator.seek::<Decimal>(3).unwrap(), Some(decimal));
+    assert_eq!(iterator.next::<Uuid>().unwrap(), Some(uuid));
+}
+
+pub fn test_tuple_get_by_path() {
+    #[derive(serde::Serialize)]
+    struct Nested {
+        b: Vec<u32>,
+    }
+    let mut map = std::collections::HashMap::new();
+    map.insert("a", Nested { b: vec![10, 20] });
+    let tuple = Tuple::from_struct(&(1, map)).unwrap();
+
+    assert_eq!(tuple.get_by_path("[1]").unwrap(), Some(1));
+    assert_eq!(tuple.get_by_path("[2].a.b[2]").unwrap(), Some(20));
+    assert_eq!(tuple.get_by_path("[2].a.b").unwrap(), Some(vec![10, 20]));
+    assert_eq!(tuple.get_by_path::<u32>("[2].a.b[3]").unwrap(), None);
+    assert_eq!(tuple.get_by_path::<u32>("[2].c").unwrap(), None);
+    assert_eq!(tuple.get_by_path::<u32>("[3]").unwrap(), None);
+}
+
+pub fn test_tuple_get_by_name() {
+    let mut space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+    let tuple = space.insert(&(1, "text")).unwrap().unwrap();
+
+    assert_eq!(tuple.get_by_name("id").unwrap(), Some(1));
+    assert_eq!(tuple.get_by_name("text").unwrap(), Some("text".to_string()));
+    assert_eq!(tuple.get_by_name::<String>("unknown").unwrap(), None);
+    // names are not paths
+    assert_eq!(tuple.get_by_name::<u32>("[1]").unwrap(), None);
+    assert_eq!(tuple.get_by_name::<u32>("id.x").unwrap(), None);
+
+    // tuples with default format don't have field names
+    let tuple = Tuple::from_struct(&(1, "text")).unwrap();
+    assert_eq!(tuple.get_by_name::<u32>("id").unwrap(), None);
+}
+
+pub fn test_tuple_builder() {
+    let mut builder = TupleBuilder::new();
+    assert!(builder.is_empty());
+    builder.append(&1).unwrap();
+    builder.append("text").unwrap();
+    builder.append(&[1, 2, 3]).unwrap();
+    assert_eq!(builder.len(), 3);
+
+    let tuple = builder.build().unwrap();
+    assert_eq!(tuple.len(), 3);
+    assert_eq!(
+        tuple.into_struct::<(u32, String, Vec<u32>)>().unwrap(),
+        (1, "text".into(), vec![1, 2, 3])
+    );
+
+    let tuple = TupleBuilder::new().build().unwrap();
+    assert_eq!(tuple.len(), 0);
+}
+
+pub fn test_tuple_format_for_space() {
+    let space = Space::find("test_s1").unwrap();
+    let format = TupleFormat::for_space(&space).unwrap();
+
+    let tuple = Tuple::from_struct_with_format(&(1, "text"), &format).unwrap();
+    assert_eq!(tuple.into_struct::<(u32, String)>().unwrap(), (1, "text".into()));
+
+    let err = Tuple::from_struct_with_format(&(1, 2), &format).err().unwrap();
+    assert!(err.to_string().contains("field 2"), "{}", err);
+
+    let err = Tuple::from_struct_with_format(&("1", "text"), &format).err().unwrap();
+    assert!(err.to_string().contains("field 1"), "{}", err);
+
+    let err = Tuple::from_struct_with_format(&(1,), &format).err().unwrap();
+    assert!(err.to_string().contains("field 2"), "{}", err);
+
+    let mut builder = TupleBuilder::new();
+    builder.append(&1).unwrap();
+    builder.append("text").unwrap();
+    builder.append(&"extra fields are allowed").unwrap();
+    builder.build_with_format(&format).unwrap();
+}
+
+#[derive(Debug, PartialEq, Tuple)]
+struct User {
+    id: u32,
+    name: String,
+    #[tuple(field = 3)]
+    email: Option<String>,
+    #[tuple(optional)]
+    age: Option<u32>,
+}
+
+pub fn test_tuple_derive() {
+    let user = User { id: 1, name: "Alice".into(), email: None, age: None };
+    let tuple = Tuple::from_struct(&user).unwrap();
+    assert_eq!(tuple.len(), 4);
+    assert_eq!(tuple.field::<()>(2).unwrap(), Some(()));
+    assert_eq!(tuple.field::<Option<String>>(3).unwrap(), Some(None));
+    assert_eq!(tuple.into_struct::<User>().unwrap(), user);
+
+    let user = User { id: 2, name: "Bob".into(), email: Some("bob@mail".into()), age: Some(42) };
+    let tuple = Tuple::from_struct(&user).unwrap();
+    assert_eq!(tuple.len(), 5);
+    assert_eq!(tuple.field::<u32>(4).unwrap(), Some(42));
+    assert_eq!(tuple.into_struct::<User>().unwrap(), user);
+
+    // unmapped fields are ignored
+    let mut builder = TupleBuilder::new();
+    builder.append(&3).unwrap();
+    builder.append("Eve").unwrap();
+    builder.append("ignored").unwrap();
+    builder.append(&"eve@mail").unwrap();
+    builder.append(&33).unwrap();
+    builder.append(&"extra").unwrap();
+    assert_eq!(
+        builder.build().unwrap().into_struct::<User>().unwrap(),
+        User { id: 3, name: "Eve".into(), email: Some("eve@mail".into()), age: Some(33) }
+    );
+
+    // nullable field is required
+    let tuple = Tuple::from_struct(&(4, "Mallory")).unwrap();
+    assert!(tuple.into_struct::<User>().is_err());
+
+    let format: Vec<_> = User::format()
+        .into_iter()
+        .map(|f| (f.name, f.field_type.to_string(), f.is_nullable))
+        .collect();
+    assert_eq!(
+        format,
+        vec![
+            ("id".to_string(), "Unsigned".to_string(), false),
+            ("name".to_string(), "String".to_string(), false),
+            ("field_3".to_string(), "Any".to_string(), true),
+            ("email".to_string(), "String".to_string(), true),
+            ("age".to_string(), "Unsigned".to_string(), true),
+        ]
+    );
+}
+
+pub fn test_tuple_derive_space_format() {
+    let opts = SpaceCreateOptions {
+        format: Some(User::format()),
+        ..Default::default()
+    };
+    let mut space = Space::create("test_derive", &opts).unwrap();
+    space.create_index("pk", &Default::default()).unwrap();
+
+    let user = User { id: 1, name: "Alice".into(), email: None, age: None };
+    let tuple = space.insert(&user).unwrap().unwrap();
+    assert_eq!(tuple.get_by_name::<String>("name").unwrap(), Some("Alice".into()));
+    assert_eq!(space.get(&(1,)).unwrap().unwrap().into_struct::<User>().unwrap(), user);
+
+    // the format is checked by the space
+    assert!(space.insert(&(2, 3)).is_err());
+
+    space.drop().unwrap();
+}
+
 pub fn test_tuple_compare() {
     let tuple_a = Tuple::from_struct(&S2Record {
         id: 1,
@@ -205,6 +449,73 @@ pub fn test_tuple_compare_with_key() {
     assert_eq!(key_def.compare_with_key(&tuple, &key_value), Ordering::Less);
 }
 
+pub fn test_key_def_from_index() {
+    let mut space = Space::create("test_key_def", &Default::default()).unwrap();
+    space.create_index("pk", &Default::default()).unwrap();
+    let index = space.create_index("sk", &IndexOptions {
+        unique: Some(false),
+        parts: Some(vec![
+            IndexPart {
+                collation: Some("unicode_ci".into()),
+                ..IndexPart::new(2, IndexFieldType::String)
+            },
+            IndexPart {
+                is_nullable: Some(true),
+                ..IndexPart::new(3, IndexFieldType::Number)
+            },
+        ]),
+        ..Default::default()
+    }).unwrap();
+    let key_def = KeyDef::from_index(&index).unwrap();
+
+    let a = Tuple::from_struct(&(1, "abc", 1)).unwrap();
+    let b = Tuple::from_struct(&(2, "ABC", 1.0)).unwrap();
+    let c = Tuple::from_struct(&(3, "abc", ())).unwrap();
+    let d = Tuple::from_struct(&(4, "abd", 0.5)).unwrap();
+
+    // collation and numbers of different types
+    assert_eq!(key_def.compare(&a, &b), Ordering::Equal);
+    assert_eq!(key_def.hash(&a), key_def.hash(&b));
+    // nil is less than anything
+    assert_eq!(key_def.compare(&c, &a), Ordering::Less);
+    assert_eq!(key_def.compare(&a, &d), Ordering::Less);
+    assert_ne!(key_def.hash(&a), key_def.hash(&d));
+
+    let key = key_def.extract_key(&b).unwrap();
+    assert_eq!(key.into_struct::<(String, f64)>().unwrap(), ("ABC".into(), 1.0));
+    let key = key_def.extract_key(&c).unwrap();
+    assert_eq!(key.into_struct::<(String, ())>().unwrap(), ("abc".into(), ()));
+
+    // the key part is missing
+    let e = Tuple::from_struct(&(5,)).unwrap();
+    assert!(key_def.extract_key(&e).is_err());
+
+    // tuples are ordered the same way as in the index (equal keys are
+    // ordered by the primary key in the index, the sort is stable)
+    let key_def = Rc::new(key_def);
+    let mut sorted: Vec<_> = [&d, &a, &c, &b].iter()
+        .map(|&t| OrderedTuple::new(t.clone(), key_def.clone()))
+        .collect();
+    sorted.sort();
+    for t in [&c, &d, &b, &a].iter() {
+        let data: (u32, String, Option<f64>) = t.decode().unwrap();
+        space.insert(&data).unwrap();
+    }
+    let ids = |tuples: Vec<Tuple>| -> Vec<u32> {
+        tuples.into_iter().map(|t| t.field(0).unwrap().unwrap()).collect()
+    };
+    let expected = ids(index.select(IteratorType::All, &()).unwrap().collect());
+    assert_eq!(ids(sorted.into_iter().map(OrderedTuple::into_tuple).collect()), expected);
+
+    let mut set = HashSet::new();
+    for t in [&a, &b, &c, &d].iter() {
+        set.insert(OrderedTuple::new((*t).clone(), key_def.clone()));
+    }
+    assert_eq!(set.len(), 3);
+
+    space.drop().unwrap();
+}
+
 pub fn to_and_from_lua() {
     let tuple = Tuple::from_struct(&S2Record {
         id: 42,
@@ -0,0 +1,53 @@
+use tarantool::version::{Feature, Version};
+
+pub fn parse() {
+    let v: Version = "2.8.2-0-gfc96d10f5".parse().unwrap();
+    assert_eq!(v, Version::new(2, 8, 2));
+    assert_eq!(v.to_string(), "2.8.2");
+
+    let v: Version = "2.10.0-beta2-91-g08c9b4963-r472".parse().unwrap();
+    assert_eq!((v.major, v.minor, v.patch), (2, 10, 0));
+    assert_eq!(v.pre_release.as_deref(), Some("beta2"));
+    assert_eq!(v.commits, 91);
+    assert_eq!(v.to_string(), "2.10.0-beta2-91");
+
+    assert_eq!("1.10.13".parse::<Version>().unwrap(), Version::new(1, 10, 13));
+
+    assert!("".parse::<Version>().is_err());
+    assert!("2.10".parse::<Version>().is_err());
+    assert!("2.x.1".parse::<Version>().is_err());
+    assert!("2.10.0-beta1-rc1".parse::<Version>().is_err());
+}
+
+pub fn compare() {
+    let versions = [
+        "1.10.13-0-g1d2c5aad5",
+        "2.10.0-beta1-0-g7da4b1438",
+        "2.10.0-beta2-91-g08c9b4963",
+        "2.10.0-beta10-0-g1d2c5aad5",
+        "2.10.0-rc1-0-gc7e6a3426",
+        "2.10.0-rc2-0-gc7e6a3426",
+        "2.10.0-rc10-0-gc7e6a3426",
+        "2.10.0-0-g7da4b1438",
+        "2.10.0-17-g3a5fb0b8f",
+        "2.10.1-0-g482d91c66",
+        "2.11.0-entrypoint-2-gf3bbd4a01",
+        "2.11.0-alpha1-0-g3a5fb0b8f",
+    ];
+    let versions: Vec<Version> = versions.iter().map(|v| v.parse().unwrap()).collect();
+    for pair in versions.windows(2) {
+        assert!(pair[0] < pair[1], "{} < {}", pair[0], pair[1]);
+    }
+}
+
+pub fn running() {
+    let lua = tarantool::global_lua();
+    let expected: String = lua.eval("return box.info.version").unwrap();
+    assert_eq!(tarantool::version(), expected.parse().unwrap());
+
+    let v = tarantool::version();
+    for feature in [Feature::SyncReplication, Feature::IndexHint, Feature::Datetime] {
+        assert_eq!(tarantool::supports(feature), v >= feature.since());
+    }
+    assert!(tarantool::supports(Feature::FunctionalIndexes));
+}